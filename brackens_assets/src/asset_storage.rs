@@ -1,12 +1,29 @@
 //===============================================================
 
-use std::{collections::HashMap, fs::File, io::Read, sync::Arc};
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    sync::{Arc, Weak},
+};
 
 use crossbeam::channel::TryRecvError;
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    handle::{Handle, HandleID, HandleInner},
-    Asset, AssetFileLoadable,
+    default_implementations::register_default_loaders,
+    events::{AssetEvent, AssetEventKind, AssetEventReader, EventQueue},
+    folder::{FolderHandle, FolderSource},
+    handle::{Handle, HandleAllocator, HandleID, HandleInner, UntypedHandle, WeakHandle},
+    loader::{
+        labelled_path, split_label, AssetLoader, FileLoader, LabelledAssets, LoadContext,
+        LoaderRegistry,
+    },
+    meta::{meta_path, AssetMeta},
+    retention::{AssetStats, ResidentAsset, RetentionPolicy, UnusedAsset},
+    vfs::{AssetSource, Vfs},
+    worker_pool::WorkerPool,
+    Asset, AssetError, AssetFileLoadable, FallbackPolicy, ReceiverType, SenderType,
 };
 
 //===============================================================
@@ -15,6 +32,10 @@ use crate::{
 pub enum AssetStorageError {
     AssetNotExist,
     AssetIsDifferentType,
+    AssetNotLoaded,
+    Stale,
+    DependencyCycle,
+    AssetShared,
 }
 impl std::fmt::Display for AssetStorageError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -26,6 +47,22 @@ impl std::fmt::Display for AssetStorageError {
                 f,
                 "AssetStorageError: The given type doesn't match the stored type"
             ),
+            AssetStorageError::AssetNotLoaded => write!(
+                f,
+                "AssetStorageError: The requested asset is still loading or failed to load"
+            ),
+            AssetStorageError::Stale => write!(
+                f,
+                "AssetStorageError: The id refers to an asset that has since been unloaded"
+            ),
+            AssetStorageError::DependencyCycle => write!(
+                f,
+                "AssetStorageError: The dependency would make the asset depend on itself"
+            ),
+            AssetStorageError::AssetShared => write!(
+                f,
+                "AssetStorageError: The asset data is shared and can't be borrowed mutably"
+            ),
         }
     }
 }
@@ -40,12 +77,70 @@ pub enum ReferenceCountSignal {
 
 //===============================================================
 
+#[derive(Clone, Debug)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    Failed(Arc<AssetError>),
+}
+
+impl LoadState {
+    #[inline]
+    pub fn is_loaded(&self) -> bool {
+        matches!(self, LoadState::Loaded)
+    }
+}
+
+/// Data sent back from a worker thread once an asynchronous load has finished.
+enum LoadResult {
+    // Asset is ready to be used and can be inserted straight away.
+    Ready(Arc<dyn Asset>),
+    // Asset has been decoded but still needs to be finalized on the main thread. Stored
+    // alongside the type ids of the intermediate data and the final asset.
+    Staged(Box<dyn Any + Send + Sync>, (TypeId, TypeId)),
+}
+
+type StagedData = Vec<(HandleInner, Box<dyn Any + Send + Sync>)>;
+
+type StageFn = Arc<dyn Fn(&[u8]) -> anyhow::Result<Box<dyn Any + Send + Sync>> + Send + Sync>;
+
+/// How an asset is loaded again when reloaded
+#[derive(Clone)]
+enum ReloadSource {
+    Loader(Arc<dyn AssetLoader>),
+    // Decoded into intermediate data that is finalized by `finalize_staged`. Holds the
+    // path of the file, which differs from the asset path of files loaded with settings.
+    Staged {
+        path: String,
+        stage: StageFn,
+        key: (TypeId, TypeId),
+    },
+}
+
+struct LoadMessage {
+    id: HandleInner,
+    result: Result<LoadResult, AssetError>,
+}
+
+/// Everything produced by loading a file with its registered loader
+struct LoadedFile {
+    loader: Arc<dyn AssetLoader>,
+    data: Arc<dyn Asset>,
+    dependencies: Vec<UntypedHandle>,
+    labelled: LabelledAssets,
+}
+
+//===============================================================
+
 pub struct AssetStorage {
     sender: crossbeam::channel::Sender<ReferenceCountSignal>,
     receiver: crossbeam::channel::Receiver<ReferenceCountSignal>,
 
-    current_id: HandleInner,
+    ids: HandleAllocator,
     loaded: HashMap<HandleInner, Arc<dyn Asset>>,
+    // Type of every asset tracked by the storage, including ones that are still loading.
+    asset_types: HashMap<HandleInner, TypeId>,
+    load_states: HashMap<HandleInner, LoadState>,
 
     // Hashmap containing path to asset as a key. Used to check if data is already loaded
     // and if so, create a handle to it.
@@ -53,31 +148,106 @@ pub struct AssetStorage {
     // Hashmap that is the opposite of loaded_paths, used to access the file path when
     // unloading the data.
     asset_paths: HashMap<HandleInner, String>,
+    // Files loaded with settings given in code, by cache key. Holds the path of the file
+    // and its settings.
+    meta_overrides: HashMap<String, (String, AssetMeta)>,
 
-    // Path to load assets from
-    load_path: String,
+    // Filesystem all asset files are read through. Shared with workers loading in the
+    // background, who keep using the mounts that existed when their load started.
+    vfs: Arc<Vfs>,
+    // What to do when an asset fails to load from file
+    fallback_policy: FallbackPolicy,
+    // Loaders used to load assets by file extension
+    loaders: LoaderRegistry,
+    // How each asset loaded from file was loaded, used when reloading
+    reload_sources: HashMap<HandleInner, ReloadSource>,
 
     // Keep track of how many handles in existance so we can unload when finished with.
     handle_count: HashMap<HandleInner, u32>,
+    // Dropped when an asset is unloaded so weak handles know they can't be upgraded.
+    alive_tokens: HashMap<HandleInner, Arc<()>>,
 
     removed_assets: Vec<HandleInner>,
+
+    // Assets without any handles that are kept loaded by the retention policy
+    unused_assets: HashMap<HandleInner, UnusedAsset>,
+    retention: RetentionPolicy,
+
+    // Strong handles to the assets each asset depends on, keeping them loaded for as long
+    // as the asset is, and the reverse lookup used to pass reloads on to dependents.
+    dependencies: HashMap<HandleInner, Vec<UntypedHandle>>,
+    dependents: HashMap<HandleInner, HashSet<HandleInner>>,
+    // File each sub-asset was loaded from. The file holds a handle to each of its
+    // sub-assets and is kept loaded while anything else still uses one of them.
+    labelled_parents: HashMap<HandleInner, HandleInner>,
+    // Paths currently being loaded, used to catch dependency cycles
+    loading_paths: HashSet<String>,
+
+    pending_reloads: Vec<HandleInner>,
+    reloaded_assets: Vec<HandleInner>,
+
+    // Number of times tick has been called
+    tick_count: u64,
+
+    // Lifecycle events of each asset type, kept until every reader has read them
+    events: HashMap<TypeId, EventQueue>,
+
+    // Background workers used for asynchronous loading. Only created once first needed.
+    workers: Option<WorkerPool>,
+    load_sender: SenderType<LoadMessage>,
+    load_receiver: ReceiverType<LoadMessage>,
+    // Decoded data waiting to be finalized on the main thread, grouped by the type ids of
+    // the intermediate data and the final asset.
+    staged: HashMap<(TypeId, TypeId), StagedData>,
 }
 
 impl Default for AssetStorage {
     fn default() -> Self {
         let (sender, receiver) = crossbeam::channel::unbounded();
+        let (load_sender, load_receiver) = crossbeam::channel::unbounded();
+
+        let mut loaders = LoaderRegistry::new();
+        register_default_loaders(&mut loaders);
+
         Self {
             sender,
             receiver,
-            current_id: HandleInner::from_id(0),
+            ids: HandleAllocator::default(),
             loaded: HashMap::new(),
+            asset_types: HashMap::new(),
+            load_states: HashMap::new(),
 
             loaded_paths: HashMap::new(),
             asset_paths: HashMap::new(),
-            load_path: "".into(),
+            meta_overrides: HashMap::new(),
+            vfs: Arc::new(Vfs::with_working_directory()),
+            fallback_policy: FallbackPolicy::default(),
+            loaders,
+            reload_sources: HashMap::new(),
 
             handle_count: HashMap::new(),
+            alive_tokens: HashMap::new(),
             removed_assets: Vec::new(),
+
+            unused_assets: HashMap::new(),
+            retention: RetentionPolicy::default(),
+
+            dependencies: HashMap::new(),
+            dependents: HashMap::new(),
+            labelled_parents: HashMap::new(),
+            loading_paths: HashSet::new(),
+
+            pending_reloads: Vec::new(),
+            reloaded_assets: Vec::new(),
+
+            tick_count: 0,
+
+            events: HashMap::new(),
+
+            workers: None,
+            load_sender,
+            load_receiver,
+            staged: HashMap::new(),
         }
     }
 }
@@ -92,35 +262,140 @@ impl AssetStorage {
     //----------------------------------------------
 
     fn get_next_id(&mut self) -> HandleInner {
-        let id = self.current_id;
-        self.current_id.next();
-        id
+        self.ids.allocate()
+    }
+
+    /// Error for an id that isn't tracked by the storage
+    fn missing_error(&self, id: HandleInner) -> AssetStorageError {
+        match self.ids.is_stale(id) {
+            true => AssetStorageError::Stale,
+            false => AssetStorageError::AssetNotExist,
+        }
+    }
+
+    /// Check if an id refers to an asset that has since been unloaded
+    pub fn is_stale<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        self.ids.is_stale(id.into())
     }
 
     //----------------------------------------------
 
-    fn insert_data_path<T: Asset>(&mut self, data: T, path: &str) -> Handle<T> {
-        let handle = self.insert_data(data);
+    pub fn fallback_policy(&self) -> FallbackPolicy {
+        self.fallback_policy
+    }
+
+    pub fn set_fallback_policy(&mut self, policy: FallbackPolicy) {
+        self.fallback_policy = policy;
+    }
+
+    /// Read and load an asset from file, using a placeholder instead if loading fails and
+    /// the fallback policy allows it.
+    fn load_file_with_policy<T, L, D>(
+        vfs: &Vfs,
+        path: &str,
+        policy: FallbackPolicy,
+        load: L,
+        load_default: D,
+    ) -> Result<T, AssetError>
+    where
+        L: FnOnce(&str, &[u8]) -> Result<T, AssetError>,
+        D: FnOnce() -> Option<T>,
+    {
+        match vfs.read(path).and_then(|bytes| load(path, &bytes)) {
+            Ok(data) => Ok(data),
+            Err(e) if policy == FallbackPolicy::Placeholder => match load_default() {
+                Some(placeholder) => {
+                    warn!("Warning: Using placeholder asset - {}", e);
+                    Ok(placeholder)
+                }
+                None => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    //----------------------------------------------
+
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// Mount a source on top of the virtual filesystem. Files in the source override
+    /// files of the same path in sources mounted before it.
+    pub fn mount<S: AssetSource>(&mut self, mount_point: &str, source: S) {
+        Arc::make_mut(&mut self.vfs).mount(mount_point, source);
+    }
+
+    pub fn unmount(&mut self, mount_point: &str) -> bool {
+        Arc::make_mut(&mut self.vfs).unmount(mount_point)
+    }
+
+    //----------------------------------------------
+
+    /// Register a loader to be used by `load_untyped` for each of its extensions
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.loaders.register(loader);
+    }
+
+    pub fn loaders(&self) -> &LoaderRegistry {
+        &self.loaders
+    }
 
-        self.loaded_paths.insert(path.into(), handle.inner_id());
-        self.asset_paths.insert(handle.inner_id(), path.into());
+    //----------------------------------------------
 
+    fn insert_data_path<T: Asset>(&mut self, data: T, path: &str) -> Handle<T> {
+        let handle = self.insert_data(data);
+        self.insert_path(handle.inner_id(), path);
         handle
     }
 
-    pub fn insert_data<T: Asset>(&mut self, data: T) -> Handle<T> {
-        let id = self.get_next_id();
+    fn insert_path(&mut self, id: HandleInner, path: &str) {
+        self.loaded_paths.insert(path.into(), id);
+        self.asset_paths.insert(id, path.into());
+    }
 
-        let handle_inner = Arc::new(data);
+    pub fn insert_data<T: Asset>(&mut self, data: T) -> Handle<T> {
+        let handle = self.reserve_handle::<T>(LoadState::Loading);
 
         // Add references to data to storage
-        self.loaded.insert(id, handle_inner.clone());
-        self.handle_count.insert(id, 0);
+        self.set_loaded(handle.inner_id(), Arc::new(data));
+
+        handle
+    }
+
+    fn set_loaded(&mut self, id: HandleInner, data: Arc<dyn Asset>) {
+        self.loaded.insert(id, data);
+        self.load_states.insert(id, LoadState::Loaded);
+        self.add_asset_dependencies(id);
+        self.push_event(id, AssetEventKind::Added);
+    }
+
+    /// Create a handle for an asset without any data attached to it yet.
+    fn reserve_handle<T: Asset>(&mut self, state: LoadState) -> Handle<T> {
+        let id = self.track_asset(TypeId::of::<T>(), state);
 
         // Construct Handle and return
         let handle_id = HandleID::new(id);
-        let handle = Handle::new(handle_id, self.sender.clone(), handle_inner);
-        handle
+        Handle::new(handle_id, self.sender.clone(), self.alive_token(id))
+    }
+
+    /// Start keeping track of a new asset and return its id
+    fn track_asset(&mut self, type_id: TypeId, state: LoadState) -> HandleInner {
+        let id = self.get_next_id();
+
+        self.asset_types.insert(id, type_id);
+        self.load_states.insert(id, state);
+        self.handle_count.insert(id, 0);
+        self.alive_tokens.insert(id, Arc::new(()));
+
+        id
+    }
+
+    fn alive_token(&self, id: HandleInner) -> Weak<()> {
+        self.alive_tokens
+            .get(&id)
+            .map(Arc::downgrade)
+            .unwrap_or_default()
     }
 
     //----------------------------------------------
@@ -156,13 +431,28 @@ impl AssetStorage {
             return Ok(handle);
         }
 
-        let data = T::load_from_file(&format!("{}{}", self.load_path, &path));
+        let data = Self::load_file_with_policy(
+            &self.vfs,
+            path,
+            self.fallback_policy,
+            T::load_from_bytes,
+            T::load_default,
+        )?;
         let handle = self.insert_data_path(data, path);
+        self.set_file_loader::<T>(handle.inner_id());
         Ok(handle)
     }
 
+    /// Remember how an asset loaded from a type was loaded so it can be reloaded
+    fn set_file_loader<T: AssetFileLoadable>(&mut self, id: HandleInner) {
+        self.reload_sources.insert(
+            id,
+            ReloadSource::Loader(Arc::new(FileLoader::<T>::new(&[]))),
+        );
+    }
+
     /// Load an asset from a given file path and function
-    pub fn load_from_data<T: Asset, F: Fn(&[u8]) -> T>(
+    pub fn load_from_data<T: Asset, F: Fn(&[u8]) -> anyhow::Result<T>>(
         &mut self,
         path: &str,
         load_data: F,
@@ -173,131 +463,1776 @@ impl AssetStorage {
         }
 
         // Load the file into bytes
-        let mut file = File::open(path)?;
-        let mut bytes = vec![];
-        let _count = file.read_to_end(&mut bytes)?;
+        let bytes = self.vfs.read(path)?;
 
         // Pass the loaded bytes into provided function and get data
-        let data = load_data(&bytes);
+        let data = load_data(&bytes).map_err(|e| AssetError::decode(path, e))?;
         // Insert new data to get handle
         let handle = self.insert_data_path(data, path);
 
         Ok(handle)
     }
 
-    //----------------------------------------------
+    /// Load an asset from a given file path and function, passing along the import settings
+    /// of the file. Settings given in code are used instead of the file's `.meta` file, and
+    /// the same file loaded with different settings produces separate assets.
+    pub fn load_from_data_with_settings<T, S, F>(
+        &mut self,
+        path: &str,
+        settings: Option<S>,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        T: Asset,
+        S: Serialize + DeserializeOwned + Default,
+        F: Fn(&[u8], &S) -> anyhow::Result<T>,
+    {
+        let key = match &settings {
+            Some(settings) => AssetMeta::from_settings(settings)
+                .map_err(|e| AssetError::decode(&meta_path(path), e))?
+                .cache_key(path),
+            None => path.to_string(),
+        };
 
-    pub fn is_file_loaded(&self, path: &str) -> bool {
-        self.loaded_paths.contains_key(path)
+        if let Some(handle) = self.load_file_get_already_loaded(&key)? {
+            return Ok(handle);
+        }
+
+        let settings = match settings {
+            Some(settings) => settings,
+            None => self.load_settings(path)?,
+        };
+
+        let bytes = self.vfs.read(path)?;
+        let data = load_data(&bytes, &settings).map_err(|e| AssetError::decode(path, e))?;
+
+        Ok(self.insert_data_path(data, &key))
     }
 
-    pub fn get_file_loaded<T: Asset>(&self, path: &str) -> Result<Handle<T>, AssetStorageError> {
-        if let Some(id) = self.loaded_paths.get(path) {
-            return self.get_handle(*id);
+    /// Read the import settings of a file from its `.meta` sidecar file, if it has one
+    pub fn read_meta(&self, path: &str) -> Result<Option<AssetMeta>, AssetError> {
+        let meta_path = meta_path(path);
+
+        match self.vfs.exists(&meta_path) {
+            true => Ok(Some(AssetMeta::parse(self.vfs.read_string(&meta_path)?))),
+            false => Ok(None),
         }
-        Err(AssetStorageError::AssetNotExist)
     }
 
-    //----------------------------------------------
-
-    /// Get and cast an id into a handle
-    pub fn get_handle<T: Asset, HI: Into<HandleInner>>(
+    /// Read the import settings of a file, or the default settings if it has none
+    pub fn load_settings<S: DeserializeOwned + Default>(
         &self,
-        id: HI,
-    ) -> Result<Handle<T>, AssetStorageError> {
-        let id = id.into();
+        path: &str,
+    ) -> Result<S, AssetError> {
+        match self.read_meta(path)? {
+            Some(meta) => meta.deserialize(path),
+            None => Ok(S::default()),
+        }
+    }
 
-        let val = self
-            .loaded
-            .get(&id)
-            // If asset doesn't exist, return custom error
-            .ok_or(AssetStorageError::AssetNotExist)?
-            .clone();
+    /// Load an asset from a given file path using the loader registered for its extension.
+    /// Any dependencies declared by the loader are loaded as well.
+    ///
+    /// Sub-assets added by the loader can be loaded using a labelled path such as
+    /// `"level.obj#mesh/Cube"`, which loads the file if it isn't already.
+    pub fn load_untyped(&mut self, path: &str) -> anyhow::Result<UntypedHandle> {
+        Ok(self.load_untyped_inner(path)?)
+    }
 
-        let inner: Arc<T> = val
-            .into_any_arc()
-            .downcast()
-            .map_err(|_| AssetStorageError::AssetIsDifferentType)?;
+    /// Load an asset like `load_untyped`, using the given import settings instead of the
+    /// file's `.meta` file. The same file loaded with different settings produces separate
+    /// assets.
+    pub fn load_untyped_with_settings<S: Serialize>(
+        &mut self,
+        path: &str,
+        settings: &S,
+    ) -> anyhow::Result<UntypedHandle> {
+        let meta = AssetMeta::from_settings(settings)
+            .map_err(|e| AssetError::decode(&meta_path(path), e))?;
+        let key = meta.cache_key(path);
 
-        let id = HandleID::new(id);
+        self.meta_overrides
+            .entry(key.clone())
+            .or_insert_with(|| (path.to_string(), meta));
 
-        let handle = Handle::new(id, self.sender.clone(), inner);
-        Ok(handle)
+        let result = self.load_untyped_inner(&key);
+        if !self.loaded_paths.contains_key(&key) {
+            self.meta_overrides.remove(&key);
+        }
+
+        Ok(result?)
     }
 
-    pub fn get_data<T: Asset>(&self, id: HandleID<T>) -> Result<&T, AssetStorageError> {
-        self.get_data_raw(id)
+    pub fn load_with_settings<T: Asset, S: Serialize>(
+        &mut self,
+        path: &str,
+        settings: &S,
+    ) -> anyhow::Result<Handle<T>> {
+        let handle = self.load_untyped_with_settings(path, settings)?;
+        Ok(handle
+            .downcast()
+            .ok_or(AssetStorageError::AssetIsDifferentType)?)
     }
 
-    pub fn get_data_raw<T: Asset, HI: Into<HandleInner>>(
-        &self,
-        id: HI,
-    ) -> Result<&T, AssetStorageError> {
-        match self.loaded.get(&id.into()) {
-            Some(val) => match val.as_ref().as_any().downcast_ref() {
-                Some(of_type) => Ok(of_type),
-                None => Err(AssetStorageError::AssetIsDifferentType)?,
-            },
-            None => Err(AssetStorageError::AssetNotExist)?,
-        }
+    /// Load an asset using the loader registered for its extension and cast the handle.
+    /// See `load_untyped`.
+    pub fn load<T: Asset>(&mut self, path: &str) -> anyhow::Result<Handle<T>> {
+        let handle = self.load_untyped_inner(path)?;
+        Ok(handle
+            .downcast()
+            .ok_or(AssetStorageError::AssetIsDifferentType)?)
     }
 
-    //----------------------------------------------
+    fn load_untyped_inner(&mut self, path: &str) -> Result<UntypedHandle, AssetError> {
+        if let Some(id) = self.loaded_paths.get(path) {
+            let id = *id;
+            return Ok(UntypedHandle::new(
+                id,
+                self.asset_types[&id],
+                self.sender.clone(),
+                self.alive_token(id),
+            ));
+        }
 
-    pub fn tick(&mut self) {
-        self.check_asset_changes();
-        self.removed_pending_assets();
+        if let (file_path, Some(label)) = split_label(path) {
+            // Loading the file adds all of its sub-assets. The file stays loaded for as long
+            // as the sub-asset is used, see `labelled_parents`.
+            let _file = self.load_untyped_inner(file_path)?;
+
+            return match self.loaded_paths.get(path) {
+                Some(id) => Ok(UntypedHandle::new(
+                    *id,
+                    self.asset_types[id],
+                    self.sender.clone(),
+                    self.alive_token(*id),
+                )),
+                None => Err(AssetError::missing_label(file_path, label)),
+            };
+        }
+
+        let loaded = self.load_file_and_dependencies(path, None)?;
+
+        let type_id = loaded.data.as_ref().as_any().type_id();
+        let id = self.track_asset(type_id, LoadState::Loading);
+
+        self.set_loaded(id, loaded.data);
+        self.insert_path(id, path);
+        self.reload_sources
+            .insert(id, ReloadSource::Loader(loaded.loader));
+
+        let mut dependencies = loaded.dependencies;
+        let (labelled, replaced) = self.insert_labelled_assets(id, path, loaded.labelled);
+        dependencies.extend(labelled);
+        self.set_dependencies(id, dependencies);
+        self.add_asset_dependencies(id);
+
+        for id in replaced {
+            self.push_event(id, AssetEventKind::Modified);
+        }
+
+        Ok(UntypedHandle::new(
+            id,
+            type_id,
+            self.sender.clone(),
+            self.alive_token(id),
+        ))
     }
 
-    pub(crate) fn check_asset_changes(&mut self) {
-        self.removed_assets.clear();
+    /// Add the sub-assets of a file. Sub-assets still loaded from an earlier load of the
+    /// file keep their id and have their data replaced. Returns handles to every sub-asset,
+    /// which the file's asset holds on to, and the ids of the replaced sub-assets.
+    fn insert_labelled_assets(
+        &mut self,
+        parent: HandleInner,
+        path: &str,
+        labelled: LabelledAssets,
+    ) -> (Vec<UntypedHandle>, Vec<HandleInner>) {
+        let mut handles = Vec::with_capacity(labelled.len());
+        let mut replaced = Vec::new();
 
-        // Loop through each recieved signal and act accordingly
-        loop {
-            let data = match self.receiver.try_recv() {
-                Ok(data) => data,
-                Err(TryRecvError::Empty) => break,
-                Err(TryRecvError::Disconnected) => {
-                    panic!("Error: Handle has been disconnected from Asset Storage")
+        for (label, data) in labelled {
+            let sub_path = labelled_path(path, &label);
+            let type_id = data.as_ref().as_any().type_id();
+
+            let id = match self.loaded_paths.get(&sub_path) {
+                Some(id) if self.asset_types[id] == type_id => {
+                    let id = *id;
+                    self.replace_loaded(id, data);
+                    replaced.push(id);
+                    id
+                }
+                Some(_) => {
+                    warn!(
+                        "Warning: Unable to replace sub-asset '{}' - loader produced a different type",
+                        sub_path
+                    );
+                    continue;
+                }
+                None => {
+                    let id = self.track_asset(type_id, LoadState::Loading);
+                    self.set_loaded(id, data);
+                    self.insert_path(id, &sub_path);
+                    id
                 }
             };
 
-            match data {
-                ReferenceCountSignal::Increase(id) => match self.handle_count.get_mut(&id) {
-                    Some(count) => *count += 1,
-                    None => panic!(
-                        "Error: Handle amount increased but asset with id {:?} doesn't exist.",
-                        id
-                    ),
-                },
-                ReferenceCountSignal::Decrease(id) => match self.handle_count.get_mut(&id) {
-                    Some(count) => {
-                        *count -= 1;
-                        if *count == 0 {
-                            self.removed_assets.push(id);
-                        }
-                    }
-                    None => panic!(
-                        "Error: Handle amount decreased but asset with id {:?} doesn't exist.",
-                        id
-                    ),
-                },
-            }
+            self.labelled_parents.insert(id, parent);
+            handles.push(UntypedHandle::new(
+                id,
+                type_id,
+                self.sender.clone(),
+                self.alive_token(id),
+            ));
         }
+
+        (handles, replaced)
     }
 
-    pub(crate) fn removed_pending_assets(&mut self) {
-        for to_remove in &self.removed_assets {
-            self.loaded.remove(&to_remove);
-            self.handle_count.remove(&to_remove);
+    /// Load a file with the given loader or its registered loader, followed by every
+    /// dependency it declares. The path may be the cache key of a file loaded with settings
+    /// given in code.
+    fn load_file_and_dependencies(
+        &mut self,
+        path: &str,
+        loader: Option<Arc<dyn AssetLoader>>,
+    ) -> Result<LoadedFile, AssetError> {
+        let (file_path, meta) = match self.meta_overrides.get(path) {
+            Some((file_path, meta)) => (file_path.clone(), Some(meta.clone())),
+            None => (path.to_string(), self.read_meta(path)?),
+        };
 
-            if let Some(val) = &self.asset_paths.remove(&to_remove) {
-                self.loaded_paths.remove(val);
-            }
-        }
+        let loader = match loader {
+            Some(loader) => loader,
+            None => self.loaders.get_path_loader(&file_path)?.clone(),
+        };
+        let mut context = LoadContext::new(&file_path, meta);
+
+        let data = Self::load_file_with_policy(
+            &self.vfs,
+            &file_path,
+            self.fallback_policy,
+            |_, bytes| loader.load(&mut context, bytes),
+            || loader.load_default(),
+        )?;
+
+        self.loading_paths.insert(path.into());
+
+        let (dependencies, labelled) = context.into_parts();
+
+        let dependencies = dependencies
+            .iter()
+            .map(|dependency| {
+                if self.loading_paths.contains(dependency) {
+                    return Err(AssetError::dependency(
+                        path,
+                        AssetError::decode(dependency, anyhow::anyhow!("dependency cycle")),
+                    ));
+                }
+
+                self.load_untyped_inner(dependency)
+                    .map_err(|e| AssetError::dependency(path, e))
+            })
+            .collect::<Result<Vec<_>, _>>();
+
+        self.loading_paths.remove(path);
+
+        Ok(LoadedFile {
+            loader,
+            data,
+            dependencies: dependencies?,
+            labelled,
+        })
     }
 
     //----------------------------------------------
-}
 
-//===============================================================
+    /// Load an asset from a given file path on a background worker. The returned handle
+    /// starts in the `Loading` state and is updated during `tick`.
+    pub fn load_from_file_async<T: AssetFileLoadable>(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<Handle<T>> {
+        if let Some(handle) = self.load_file_get_already_loaded(path)? {
+            return Ok(handle);
+        }
+
+        let file_path = path.to_string();
+        let vfs = self.vfs.clone();
+        let policy = self.fallback_policy;
+
+        let handle = self.load_async(path, move || {
+            let data = Self::load_file_with_policy(
+                &vfs,
+                &file_path,
+                policy,
+                T::load_from_bytes,
+                T::load_default,
+            )?;
+            Ok(LoadResult::Ready(Arc::new(data)))
+        });
+        self.set_file_loader::<T>(handle.inner_id());
+
+        Ok(handle)
+    }
+
+    /// Load an asset from a given file path and function on a background worker. The
+    /// returned handle starts in the `Loading` state and is updated during `tick`.
+    pub fn load_from_data_async<T, F>(
+        &mut self,
+        path: &str,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        T: Asset,
+        F: FnOnce(&[u8]) -> anyhow::Result<T> + Send + 'static,
+    {
+        if let Some(handle) = self.load_file_get_already_loaded(path)? {
+            return Ok(handle);
+        }
+
+        let file_path = path.to_string();
+        let vfs = self.vfs.clone();
+
+        Ok(self.load_async(path, move || {
+            let bytes = vfs.read(&file_path)?;
+            let data = load_data(&bytes).map_err(|e| AssetError::decode(&file_path, e))?;
+            Ok(LoadResult::Ready(Arc::new(data)))
+        }))
+    }
+
+    /// Read and decode a file on a background worker into intermediate data `I`. The
+    /// intermediate data is then turned into the final asset on the main thread using
+    /// `finalize_staged`. Useful for assets that need GPU resources to be created.
+    /// `tick` doesn't finalize staged data, so the handle stays `Loading` until
+    /// `finalize_staged` is called for the pair of types. Reloads are staged the same way.
+    pub fn load_from_data_staged<I, T, F>(
+        &mut self,
+        path: &str,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: Fn(&[u8]) -> anyhow::Result<I> + Send + Sync + 'static,
+    {
+        if let Some(handle) = self.load_file_get_already_loaded(path)? {
+            return Ok(handle);
+        }
+
+        let stage = Self::stage_fn(load_data);
+        let key = (TypeId::of::<I>(), TypeId::of::<T>());
+
+        let file_path = path.to_string();
+        let vfs = self.vfs.clone();
+        let job_stage = stage.clone();
+
+        let handle = self.load_async(path, move || {
+            let bytes = vfs.read(&file_path)?;
+            let data = job_stage(&bytes).map_err(|e| AssetError::decode(&file_path, e))?;
+            Ok(LoadResult::Staged(data, key))
+        });
+
+        self.reload_sources.insert(
+            handle.inner_id(),
+            ReloadSource::Staged {
+                path: path.to_string(),
+                stage,
+                key,
+            },
+        );
+
+        Ok(handle)
+    }
+
+    /// Reload an asset loaded from a function by decoding its file into intermediate data
+    /// `I`, which is turned back into the asset by `finalize_staged`. Lets assets loaded
+    /// with `load_from_data` or `load_from_data_with_settings` be reloaded.
+    pub fn set_reload_staged<I, T, F>(
+        &mut self,
+        id: HandleID<T>,
+        path: &str,
+        load_data: F,
+    ) -> Result<(), AssetStorageError>
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: Fn(&[u8]) -> anyhow::Result<I> + Send + Sync + 'static,
+    {
+        let id = id.into();
+        if !self.asset_types.contains_key(&id) {
+            return Err(self.missing_error(id));
+        }
+
+        self.reload_sources.insert(
+            id,
+            ReloadSource::Staged {
+                path: path.to_string(),
+                stage: Self::stage_fn(load_data),
+                key: (TypeId::of::<I>(), TypeId::of::<T>()),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn stage_fn<I, F>(load_data: F) -> StageFn
+    where
+        I: Send + Sync + 'static,
+        F: Fn(&[u8]) -> anyhow::Result<I> + Send + Sync + 'static,
+    {
+        Arc::new(move |bytes| Ok(Box::new(load_data(bytes)?) as Box<dyn Any + Send + Sync>))
+    }
+
+    fn load_async<T: Asset, F>(&mut self, path: &str, job: F) -> Handle<T>
+    where
+        F: FnOnce() -> Result<LoadResult, AssetError> + Send + 'static,
+    {
+        let handle = self.reserve_handle::<T>(LoadState::Loading);
+        self.insert_path(handle.inner_id(), path);
+
+        let id = handle.inner_id();
+        let sender = self.load_sender.clone();
+
+        self.workers
+            .get_or_insert_with(WorkerPool::new)
+            .execute(move || {
+                let result = job();
+                // Storage may have been dropped before the job finished
+                sender.send(LoadMessage { id, result }).ok();
+            });
+
+        handle
+    }
+
+    //----------------------------------------------
+
+    /// Turn all staged intermediate data of type `I` into assets of type `T`. Should be
+    /// called on the main thread after `tick`. Staged reloads replace the data of the
+    /// asset and report it as reloaded.
+    pub fn finalize_staged<I, T, F>(&mut self, mut finalize: F)
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: FnMut(I) -> anyhow::Result<T>,
+    {
+        let staged = match self.staged.remove(&(TypeId::of::<I>(), TypeId::of::<T>())) {
+            Some(staged) => staged,
+            None => return,
+        };
+
+        for (id, data) in staged {
+            // Asset may have been unloaded while waiting to be finalized
+            if !self.handle_count.contains_key(&id) {
+                continue;
+            }
+
+            let data = *data
+                .downcast::<I>()
+                .expect("Error: Staged asset data stored with incorrect type");
+
+            match finalize(data) {
+                Ok(asset) if self.loaded.contains_key(&id) => {
+                    self.replace_loaded(id, Arc::new(asset));
+                    self.mark_reloaded(id);
+                }
+                Ok(asset) => self.set_loaded(id, Arc::new(asset)),
+                Err(e) => {
+                    let path = self.asset_paths.get(&id).cloned().unwrap_or_default();
+                    self.set_load_failed(id, AssetError::decode(&path, e));
+                }
+            }
+        }
+    }
+
+    fn set_load_failed(&mut self, id: HandleInner, error: AssetError) {
+        warn!("Warning: Failed to load asset {:?} - {}", id, error);
+        let error = Arc::new(error);
+        self.load_states
+            .insert(id, LoadState::Failed(error.clone()));
+        self.push_event(id, AssetEventKind::LoadFailed(error));
+    }
+
+    //----------------------------------------------
+
+    fn push_event(&mut self, id: HandleInner, kind: AssetEventKind) {
+        if let Some(type_id) = self.asset_types.get(&id) {
+            self.events
+                .entry(*type_id)
+                .or_default()
+                .push(self.tick_count, id, kind);
+        }
+    }
+
+    /// Reader that only receives events sent after it was created
+    pub fn event_reader<T: Asset>(&mut self) -> AssetEventReader<T> {
+        self.events.entry(TypeId::of::<T>()).or_default().reader()
+    }
+
+    /// Get all events of type `T` the reader hasn't read yet
+    pub fn read_events<T: Asset>(&self, reader: &mut AssetEventReader<T>) -> Vec<AssetEvent<T>> {
+        match self.events.get(&TypeId::of::<T>()) {
+            Some(queue) => queue.read(reader),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        self.retention
+    }
+
+    /// Set how long assets without any handles are kept loaded for
+    pub fn set_retention_policy(&mut self, policy: RetentionPolicy) {
+        self.retention = policy;
+    }
+
+    /// List every asset currently loaded along with its size
+    pub fn stats(&self) -> AssetStats {
+        let mut stats = AssetStats::default();
+
+        for (id, data) in &self.loaded {
+            let byte_size = data.byte_size();
+            let unused = self.unused_assets.contains_key(id);
+
+            stats.total_bytes += byte_size;
+            if unused {
+                stats.unused_bytes += byte_size;
+            }
+
+            stats.assets.push(ResidentAsset {
+                id: *id,
+                type_id: self.asset_types[id],
+                asset_name: data.asset_name().to_string(),
+                path: self.asset_paths.get(id).cloned(),
+                byte_size,
+                handle_count: self.handle_count.get(id).copied().unwrap_or_default(),
+                unused,
+            });
+        }
+
+        stats
+    }
+
+    //----------------------------------------------
+
+    fn prune_events(&mut self) {
+        for queue in self.events.values_mut() {
+            queue.prune(self.tick_count, self.retention.event_ticks);
+        }
+    }
+
+    //----------------------------------------------
+
+    /// Replace the data of an asset that is already loaded, along with the dependencies
+    /// it declares
+    fn replace_loaded(&mut self, id: HandleInner, data: Arc<dyn Asset>) {
+        self.loaded.insert(id, data);
+        self.load_states.insert(id, LoadState::Loaded);
+        self.set_dependencies(id, Vec::new());
+        self.add_asset_dependencies(id);
+    }
+
+    /// Record the dependencies an asset declares through `Asset::dependencies`
+    fn add_asset_dependencies(&mut self, id: HandleInner) {
+        let dependencies = match self.loaded.get(&id) {
+            Some(data) => data.dependencies(),
+            None => return,
+        };
+
+        for dependency in dependencies {
+            if let Err(e) = self.add_dependency(id, dependency) {
+                warn!(
+                    "Warning: Unable to add dependency {:?} of asset {:?} - {}",
+                    dependency, id, e
+                );
+            }
+        }
+    }
+
+    /// Replace the dependencies of an asset, releasing any it previously depended on.
+    /// Dependencies that would form a cycle are skipped.
+    fn set_dependencies(&mut self, id: HandleInner, dependencies: Vec<UntypedHandle>) {
+        if let Some(previous) = self.dependencies.remove(&id) {
+            for dependency in previous {
+                self.remove_dependent(dependency.inner_id(), id);
+            }
+        }
+
+        let dependencies = dependencies
+            .into_iter()
+            .filter(
+                |dependency| match self.depends_on(dependency.inner_id(), id) {
+                    true => {
+                        warn!(
+                            "Warning: Skipping dependency {:?} of asset {:?} - dependency cycle",
+                            dependency.inner_id(),
+                            id
+                        );
+                        false
+                    }
+                    false => true,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if dependencies.is_empty() {
+            return;
+        }
+
+        for dependency in &dependencies {
+            self.dependents
+                .entry(dependency.inner_id())
+                .or_default()
+                .insert(id);
+        }
+
+        self.dependencies.insert(id, dependencies);
+    }
+
+    /// Check if an asset is or depends on the target, directly or through its dependencies
+    fn depends_on(&self, id: HandleInner, target: HandleInner) -> bool {
+        let mut to_check = vec![id];
+        let mut checked = HashSet::new();
+
+        while let Some(id) = to_check.pop() {
+            if id == target {
+                return true;
+            }
+            if !checked.insert(id) {
+                continue;
+            }
+
+            if let Some(dependencies) = self.dependencies.get(&id) {
+                to_check.extend(dependencies.iter().map(|handle| handle.inner_id()));
+            }
+        }
+
+        false
+    }
+
+    fn remove_dependent(&mut self, dependency: HandleInner, dependent: HandleInner) {
+        if let Some(dependents) = self.dependents.get_mut(&dependency) {
+            dependents.remove(&dependent);
+            if dependents.is_empty() {
+                self.dependents.remove(&dependency);
+            }
+        }
+    }
+
+    /// Mark an asset as depending on another. The dependency won't be unloaded while the
+    /// dependent asset is still loaded and reloading it also reports the dependent asset
+    /// as reloaded. Fails if the dependency already depends on the asset.
+    pub fn add_dependency<P: Into<HandleInner>, D: Into<HandleInner>>(
+        &mut self,
+        dependent: P,
+        dependency: D,
+    ) -> Result<(), AssetStorageError> {
+        let dependent = dependent.into();
+        let dependency = self.get_untyped_handle(dependency)?;
+
+        if !self.asset_types.contains_key(&dependent) {
+            return Err(self.missing_error(dependent));
+        }
+
+        if self.depends_on(dependency.inner_id(), dependent) {
+            return Err(AssetStorageError::DependencyCycle);
+        }
+
+        let dependencies = self.dependencies.entry(dependent).or_default();
+        if dependencies.contains(&dependency) {
+            return Ok(());
+        }
+
+        self.dependents
+            .entry(dependency.inner_id())
+            .or_default()
+            .insert(dependent);
+        dependencies.push(dependency);
+
+        Ok(())
+    }
+
+    pub fn get_dependencies<HI: Into<HandleInner>>(&self, id: HI) -> Vec<HandleInner> {
+        match self.dependencies.get(&id.into()) {
+            Some(dependencies) => dependencies
+                .iter()
+                .map(|handle| handle.inner_id())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_dependents<HI: Into<HandleInner>>(&self, id: HI) -> Vec<HandleInner> {
+        match self.dependents.get(&id.into()) {
+            Some(dependents) => dependents.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Load state of an asset combined with the states of everything it depends on.
+    /// Only `Loaded` once the asset and all of its dependencies are loaded.
+    pub fn dependency_load_state<HI: Into<HandleInner>>(&self, id: HI) -> Option<LoadState> {
+        self.dependency_load_state_inner(id.into(), &mut HashSet::new())
+    }
+
+    fn dependency_load_state_inner(
+        &self,
+        id: HandleInner,
+        checked: &mut HashSet<HandleInner>,
+    ) -> Option<LoadState> {
+        let state = self.load_states.get(&id)?;
+
+        // Dependency cycles count as loaded once every asset in them has been checked
+        if !state.is_loaded() || !checked.insert(id) {
+            return Some(state.clone());
+        }
+
+        let mut combined = LoadState::Loaded;
+
+        for dependency in self.dependencies.get(&id).into_iter().flatten() {
+            match self.dependency_load_state_inner(dependency.inner_id(), checked) {
+                Some(LoadState::Loaded) => {}
+                Some(LoadState::Failed(e)) => return Some(LoadState::Failed(e)),
+                Some(LoadState::Loading) | None => combined = LoadState::Loading,
+            }
+        }
+
+        Some(combined)
+    }
+
+    pub fn is_fully_loaded<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        matches!(self.dependency_load_state(id), Some(LoadState::Loaded))
+    }
+
+    //----------------------------------------------
+
+    /// Load every file in a folder that has a loader registered for its extension
+    pub fn load_folder(&mut self, path: &str, recursive: bool) -> anyhow::Result<FolderHandle> {
+        let mut folder = FolderHandle::new(FolderSource::Directory {
+            path: path.into(),
+            recursive,
+        });
+        self.rescan(&mut folder)?;
+        Ok(folder)
+    }
+
+    /// Load every file matching a glob pattern such as `"sprites/**/*.png"` that has a
+    /// loader registered for its extension
+    pub fn load_glob(&mut self, pattern: &str) -> anyhow::Result<FolderHandle> {
+        let mut folder = FolderHandle::new(FolderSource::Glob(pattern.into()));
+        self.rescan(&mut folder)?;
+        Ok(folder)
+    }
+
+    /// Load files added to a folder since it was loaded and retry files that failed to
+    /// load. Returns the number of newly loaded files.
+    pub fn rescan(&mut self, folder: &mut FolderHandle) -> anyhow::Result<usize> {
+        let paths = match folder.source() {
+            FolderSource::Directory { path, recursive } => self.vfs.list(path, *recursive),
+            FolderSource::Glob(pattern) => self.vfs.glob(pattern)?,
+        };
+
+        let mut loaded = 0;
+        let mut failed = Vec::new();
+
+        for path in paths {
+            // Skip files such as .meta files that can't be loaded as assets
+            if folder.contains(&path) || self.loaders.get_path_loader(&path).is_err() {
+                continue;
+            }
+
+            match self.load_untyped_inner(&path) {
+                Ok(handle) => {
+                    folder.push(path, handle);
+                    loaded += 1;
+                }
+                Err(e) => {
+                    warn!("Warning: Unable to load asset in folder - {}", e);
+                    failed.push((path, Arc::new(e)));
+                }
+            }
+        }
+
+        folder.set_failed(failed);
+        Ok(loaded)
+    }
+
+    /// Combined load state of every asset in a folder, including their dependencies
+    pub fn folder_load_state(&self, folder: &FolderHandle) -> LoadState {
+        if let Some((_, error)) = folder.failed().first() {
+            return LoadState::Failed(error.clone());
+        }
+
+        let mut combined = LoadState::Loaded;
+
+        for handle in folder.handles() {
+            match self.dependency_load_state(handle.inner_id()) {
+                Some(LoadState::Loaded) => {}
+                Some(LoadState::Failed(e)) => return LoadState::Failed(e),
+                Some(LoadState::Loading) | None => combined = LoadState::Loading,
+            }
+        }
+
+        combined
+    }
+
+    pub fn is_folder_loaded(&self, folder: &FolderHandle) -> bool {
+        self.folder_load_state(folder).is_loaded()
+    }
+
+    //----------------------------------------------
+
+    /// Reload an asset from file the same way it was loaded during the next `tick`.
+    /// Everything depending on the asset is reported as reloaded along with it.
+    pub fn reload<HI: Into<HandleInner>>(&mut self, id: HI) {
+        self.pending_reloads.push(id.into());
+    }
+
+    pub fn reload_path(&mut self, path: &str) -> bool {
+        match self.loaded_paths.get(path) {
+            Some(id) => {
+                self.pending_reloads.push(*id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Assets reloaded during the last `tick`, including the assets depending on them
+    pub fn get_reloaded_assets(&self) -> &Vec<HandleInner> {
+        &self.reloaded_assets
+    }
+
+    pub(crate) fn process_reloads(&mut self) {
+        self.reloaded_assets.clear();
+
+        let mut pending = std::mem::take(&mut self.pending_reloads);
+
+        while let Some(id) = pending.pop() {
+            // Asset may have been unloaded since the reload was requested
+            let type_id = match self.asset_types.get(&id) {
+                Some(type_id) => *type_id,
+                None => continue,
+            };
+
+            // Staged assets are finalized after the tick, once GPU resources can be created
+            if let Some(ReloadSource::Staged { path, stage, key }) =
+                self.reload_sources.get(&id).cloned()
+            {
+                let staged = self
+                    .vfs
+                    .read(&path)
+                    .and_then(|bytes| stage(&bytes).map_err(|e| AssetError::decode(&path, e)));
+
+                match staged {
+                    Ok(data) => self.staged.entry(key).or_default().push((id, data)),
+                    Err(e) => warn!("Warning: Unable to reload asset - {}", e),
+                }
+                continue;
+            }
+
+            let path = match self.asset_paths.get(&id) {
+                Some(path) => path.clone(),
+                None => {
+                    warn!(
+                        "Warning: Unable to reload asset {:?} - asset wasn't loaded from file",
+                        id
+                    );
+                    continue;
+                }
+            };
+
+            // Sub-assets are reloaded by reloading the file they come from
+            if let (file_path, Some(_)) = split_label(&path) {
+                match self.loaded_paths.get(file_path) {
+                    Some(file_id) => pending.push(*file_id),
+                    None => {
+                        if let Err(e) = self.load_untyped_inner(file_path) {
+                            warn!("Warning: Unable to reload asset - {}", e);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let loader = match self.reload_sources.get(&id) {
+                Some(ReloadSource::Loader(loader)) => loader.clone(),
+                _ => {
+                    warn!(
+                        "Warning: Unable to reload asset '{}' - asset was loaded from a function without a reload source",
+                        path
+                    );
+                    continue;
+                }
+            };
+
+            let loaded = match self.load_file_and_dependencies(&path, Some(loader)) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    warn!("Warning: Unable to reload asset - {}", e);
+                    continue;
+                }
+            };
+
+            if loaded.data.as_ref().as_any().type_id() != type_id {
+                warn!(
+                    "Warning: Unable to reload asset '{}' - loader produced a different type",
+                    path
+                );
+                continue;
+            }
+
+            self.loaded.insert(id, loaded.data);
+            self.load_states.insert(id, LoadState::Loaded);
+
+            let mut dependencies = loaded.dependencies;
+            let (labelled, replaced) = self.insert_labelled_assets(id, &path, loaded.labelled);
+            dependencies.extend(labelled);
+            self.set_dependencies(id, dependencies);
+            self.add_asset_dependencies(id);
+
+            for sub_asset in replaced {
+                self.mark_reloaded(sub_asset);
+            }
+            self.mark_reloaded(id);
+        }
+    }
+
+    /// Add an asset and everything depending on it to the reloaded assets
+    fn mark_reloaded(&mut self, id: HandleInner) {
+        let mut to_mark = vec![id];
+
+        while let Some(id) = to_mark.pop() {
+            if self.reloaded_assets.contains(&id) {
+                continue;
+            }
+            self.reloaded_assets.push(id);
+            self.push_event(id, AssetEventKind::Modified);
+
+            if let Some(dependents) = self.dependents.get(&id) {
+                to_mark.extend(dependents.iter().copied());
+            }
+        }
+    }
+
+    //----------------------------------------------
+
+    pub fn is_file_loaded(&self, path: &str) -> bool {
+        self.loaded_paths.contains_key(path)
+    }
+
+    pub fn load_state<HI: Into<HandleInner>>(&self, id: HI) -> Option<&LoadState> {
+        self.load_states.get(&id.into())
+    }
+
+    pub fn is_loaded<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        match self.load_state(id) {
+            Some(state) => state.is_loaded(),
+            None => false,
+        }
+    }
+
+    pub fn get_file_loaded<T: Asset>(&self, path: &str) -> Result<Handle<T>, AssetStorageError> {
+        if let Some(id) = self.loaded_paths.get(path) {
+            return self.get_handle(*id);
+        }
+        Err(AssetStorageError::AssetNotExist)
+    }
+
+    //----------------------------------------------
+
+    /// Get and cast an id into a handle
+    pub fn get_handle<T: Asset, HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<Handle<T>, AssetStorageError> {
+        let id = id.into();
+
+        let type_id = self
+            .asset_types
+            .get(&id)
+            // If asset doesn't exist, return custom error
+            .ok_or_else(|| self.missing_error(id))?;
+
+        if *type_id != TypeId::of::<T>() {
+            return Err(AssetStorageError::AssetIsDifferentType);
+        }
+
+        let handle_id = HandleID::new(id);
+
+        let handle = Handle::new(handle_id, self.sender.clone(), self.alive_token(id));
+        Ok(handle)
+    }
+
+    /// Get a handle that doesn't keep the asset loaded
+    pub fn get_weak_handle<T: Asset, HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<WeakHandle<T>, AssetStorageError> {
+        let id = id.into();
+
+        let type_id = self
+            .asset_types
+            .get(&id)
+            .ok_or_else(|| self.missing_error(id))?;
+
+        if *type_id != TypeId::of::<T>() {
+            return Err(AssetStorageError::AssetIsDifferentType);
+        }
+
+        let handle_id = HandleID::new(id);
+        Ok(WeakHandle::new(
+            handle_id,
+            self.sender.clone(),
+            self.alive_token(id),
+        ))
+    }
+
+    /// Get a handle to an asset without needing to know its type
+    pub fn get_untyped_handle<HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<UntypedHandle, AssetStorageError> {
+        let id = id.into();
+
+        let type_id = self
+            .asset_types
+            .get(&id)
+            .ok_or_else(|| self.missing_error(id))?;
+
+        Ok(UntypedHandle::new(
+            id,
+            *type_id,
+            self.sender.clone(),
+            self.alive_token(id),
+        ))
+    }
+
+    pub fn get_data<T: Asset>(&self, id: HandleID<T>) -> Result<&T, AssetStorageError> {
+        self.get_data_raw(id)
+    }
+
+    pub fn get_data_raw<T: Asset, HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<&T, AssetStorageError> {
+        let id = id.into();
+        match self.loaded.get(&id) {
+            Some(val) => match val.as_ref().as_any().downcast_ref() {
+                Some(of_type) => Ok(of_type),
+                None => Err(AssetStorageError::AssetIsDifferentType)?,
+            },
+            None if self.asset_types.contains_key(&id) => Err(AssetStorageError::AssetNotLoaded)?,
+            None => Err(self.missing_error(id))?,
+        }
+    }
+
+    /// Mutable access to the data of an asset. Fails if something else still references
+    /// the data, see `make_mut` to copy it instead. Sends a `Modified` event.
+    pub fn get_mut<T: Asset>(&mut self, id: HandleID<T>) -> Result<&mut T, AssetStorageError> {
+        let id = self.check_mutable::<T>(id.into())?;
+
+        if let Some(data) = self.loaded.get_mut(&id) {
+            if Arc::get_mut(data).is_none() {
+                return Err(AssetStorageError::AssetShared);
+            }
+        }
+
+        self.push_event(id, AssetEventKind::Modified);
+
+        self.loaded
+            .get_mut(&id)
+            .and_then(Arc::get_mut)
+            .and_then(|data| data.as_any_mut().downcast_mut())
+            .ok_or(AssetStorageError::AssetNotLoaded)
+    }
+
+    /// Mutable access to the data of an asset like `get_mut`. If the data is shared it is
+    /// copied first so anything still holding the old data isn't affected.
+    pub fn make_mut<T: Asset + Clone>(
+        &mut self,
+        id: HandleID<T>,
+    ) -> Result<&mut T, AssetStorageError> {
+        let id = self.check_mutable::<T>(id.into())?;
+
+        self.push_event(id, AssetEventKind::Modified);
+
+        let data = self
+            .loaded
+            .get_mut(&id)
+            .ok_or(AssetStorageError::AssetNotLoaded)?;
+
+        // Copy on write if something else still references the data
+        if Arc::get_mut(data).is_none() {
+            let copy = data
+                .as_ref()
+                .as_any()
+                .downcast_ref::<T>()
+                .ok_or(AssetStorageError::AssetIsDifferentType)?
+                .clone();
+            *data = Arc::new(copy);
+        }
+
+        Arc::get_mut(data)
+            .and_then(|data| data.as_any_mut().downcast_mut())
+            .ok_or(AssetStorageError::AssetIsDifferentType)
+    }
+
+    /// Check an asset is loaded with the given type before borrowing it mutably
+    fn check_mutable<T: Asset>(&self, id: HandleInner) -> Result<HandleInner, AssetStorageError> {
+        match self.loaded.get(&id) {
+            Some(val) if !val.as_ref().as_any().is::<T>() => {
+                Err(AssetStorageError::AssetIsDifferentType)
+            }
+            Some(_) => Ok(id),
+            None if self.asset_types.contains_key(&id) => Err(AssetStorageError::AssetNotLoaded),
+            None => Err(self.missing_error(id)),
+        }
+    }
+
+    /// Modify the data of an asset in place. See `get_mut`.
+    pub fn modify<T: Asset, R, F: FnOnce(&mut T) -> R>(
+        &mut self,
+        id: HandleID<T>,
+        modify: F,
+    ) -> Result<R, AssetStorageError> {
+        Ok(modify(self.get_mut(id)?))
+    }
+
+    //----------------------------------------------
+
+    pub fn tick(&mut self) {
+        self.tick_count += 1;
+
+        self.prune_events();
+        self.check_asset_changes();
+        self.retain_unused_assets();
+        self.removed_pending_assets();
+        self.receive_loaded_assets();
+        self.process_reloads();
+    }
+
+    pub(crate) fn receive_loaded_assets(&mut self) {
+        for LoadMessage { id, result } in self.load_receiver.try_iter().collect::<Vec<_>>() {
+            // Asset has been unloaded while it was loading. Data can be discarded.
+            if !self.handle_count.contains_key(&id) {
+                continue;
+            }
+
+            match result {
+                Ok(LoadResult::Ready(asset)) => self.set_loaded(id, asset),
+                Ok(LoadResult::Staged(data, key)) => {
+                    self.staged.entry(key).or_default().push((id, data));
+                }
+                Err(e) => self.set_load_failed(id, e),
+            }
+        }
+    }
+
+    pub(crate) fn check_asset_changes(&mut self) {
+        self.removed_assets.clear();
+
+        // Loop through each recieved signal and act accordingly
+        loop {
+            let data = match self.receiver.try_recv() {
+                Ok(data) => data,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    panic!("Error: Handle has been disconnected from Asset Storage")
+                }
+            };
+
+            // A weak handle can be upgraded on another thread just as its asset is being
+            // unloaded, so signals for missing assets are possible.
+            match data {
+                ReferenceCountSignal::Increase(id) => match self.handle_count.get_mut(&id) {
+                    Some(count) => {
+                        *count += 1;
+                        self.unused_assets.remove(&id);
+
+                        // A sub-asset used outside of its file keeps the file loaded
+                        if *count > 1 {
+                            if let Some(parent) = self.labelled_parents.get(&id) {
+                                self.unused_assets.remove(parent);
+                            }
+                        }
+                    }
+                    None => warn!(
+                        "Warning: Handle amount increased but asset with id {:?} doesn't exist.",
+                        id
+                    ),
+                },
+                ReferenceCountSignal::Decrease(id) => match self.handle_count.get_mut(&id) {
+                    Some(count) => {
+                        *count -= 1;
+                        match *count {
+                            0 => self.removed_assets.push(id),
+                            // Only the file holds the sub-asset now, so it may be unused
+                            1 => {
+                                if let Some(parent) = self.labelled_parents.get(&id) {
+                                    self.removed_assets.push(*parent);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => warn!(
+                        "Warning: Handle amount decreased but asset with id {:?} doesn't exist.",
+                        id
+                    ),
+                },
+            }
+        }
+
+        // New handles may have been created after the count reached zero
+        let mut checked = HashSet::new();
+        let removed = std::mem::take(&mut self.removed_assets);
+        self.removed_assets = removed
+            .into_iter()
+            .filter(|id| {
+                self.handle_count.get(id) == Some(&0)
+                    && !self.sub_assets_in_use(*id)
+                    && checked.insert(*id)
+            })
+            .collect();
+    }
+
+    /// Whether anything other than the file itself holds a handle to one of its sub-assets
+    fn sub_assets_in_use(&self, id: HandleInner) -> bool {
+        self.dependencies.get(&id).is_some_and(|dependencies| {
+            dependencies.iter().any(|dependency| {
+                let dependency = dependency.inner_id();
+                self.labelled_parents.get(&dependency) == Some(&id)
+                    && self
+                        .handle_count
+                        .get(&dependency)
+                        .is_some_and(|count| *count > 1)
+            })
+        })
+    }
+
+    /// Keep newly unused assets loaded according to the retention policy. Replaces the
+    /// assets to remove with the unused assets that should be unloaded now.
+    pub(crate) fn retain_unused_assets(&mut self) {
+        for id in self.removed_assets.drain(..) {
+            self.unused_assets
+                .insert(id, UnusedAsset::new(self.tick_count));
+        }
+
+        let policy = self.retention;
+        let tick = self.tick_count;
+
+        let mut to_remove = self
+            .unused_assets
+            .iter()
+            .filter(|(_, unused)| unused.is_expired(policy.time, tick))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in &to_remove {
+            self.unused_assets.remove(id);
+        }
+
+        if let Some(budget) = policy.byte_budget {
+            let mut total_bytes = self
+                .loaded
+                .iter()
+                .filter(|(id, _)| !to_remove.contains(id))
+                .map(|(_, data)| data.byte_size())
+                .sum::<usize>();
+
+            // Evict least recently used first
+            let mut unused = self
+                .unused_assets
+                .iter()
+                .map(|(id, unused)| (*id, *unused))
+                .collect::<Vec<_>>();
+            unused.sort_by_key(|(_, unused)| (unused.tick, unused.instant));
+
+            for (id, _) in unused {
+                if total_bytes <= budget {
+                    break;
+                }
+
+                total_bytes -= self.loaded.get(&id).map_or(0, |data| data.byte_size());
+                self.unused_assets.remove(&id);
+                to_remove.push(id);
+            }
+        }
+
+        self.removed_assets = to_remove;
+    }
+
+    pub(crate) fn removed_pending_assets(&mut self) {
+        for to_remove in &self.removed_assets {
+            self.loaded.remove(&to_remove);
+            if let Some(type_id) = self.asset_types.remove(to_remove) {
+                self.events.entry(type_id).or_default().push(
+                    self.tick_count,
+                    *to_remove,
+                    AssetEventKind::Removed,
+                );
+            }
+            self.load_states.remove(to_remove);
+            self.handle_count.remove(&to_remove);
+            self.alive_tokens.remove(to_remove);
+            self.ids.free(*to_remove);
+
+            // Release dependencies, letting them unload once nothing else uses them
+            if let Some(dependencies) = self.dependencies.remove(to_remove) {
+                for dependency in dependencies {
+                    if let Some(dependents) = self.dependents.get_mut(&dependency.inner_id()) {
+                        dependents.remove(to_remove);
+                        if dependents.is_empty() {
+                            self.dependents.remove(&dependency.inner_id());
+                        }
+                    }
+                }
+            }
+            self.dependents.remove(to_remove);
+            self.labelled_parents.remove(to_remove);
+
+            if let Some(val) = &self.asset_paths.remove(&to_remove) {
+                self.loaded_paths.remove(val);
+                self.meta_overrides.remove(val);
+            }
+            self.reload_sources.remove(to_remove);
+        }
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{vfs::EmbeddedSource, DataAsset, RetentionTime};
+
+    struct Text(String);
+
+    impl Asset for Text {
+        fn asset_name(&self) -> &str {
+            "Text"
+        }
+    }
+
+    impl AssetFileLoadable for Text {
+        fn load_from_bytes(_path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+            Ok(Self(String::from_utf8_lossy(bytes).into_owned()))
+        }
+    }
+
+    // Loader registered for the same extension producing a different type
+    struct Length(usize);
+
+    impl Asset for Length {
+        fn asset_name(&self) -> &str {
+            "Length"
+        }
+    }
+
+    impl AssetFileLoadable for Length {
+        fn load_from_bytes(_path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+            Ok(Self(bytes.len()))
+        }
+    }
+
+    // Asset with a chosen size for testing byte budgets
+    struct Blob(usize);
+
+    impl Asset for Blob {
+        fn asset_name(&self) -> &str {
+            "Blob"
+        }
+
+        fn byte_size(&self) -> usize {
+            self.0
+        }
+    }
+
+    struct Uses(Handle<Text>);
+
+    impl Asset for Uses {
+        fn asset_name(&self) -> &str {
+            "Uses"
+        }
+
+        fn dependencies(&self) -> Vec<HandleInner> {
+            vec![self.0.inner_id()]
+        }
+    }
+
+    // Loads a file as its text with its length as a sub-asset, counting every load
+    struct SheetLoader(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl AssetLoader for SheetLoader {
+        fn extensions(&self) -> &[&str] {
+            &["sheet"]
+        }
+
+        fn load(
+            &self,
+            context: &mut LoadContext,
+            bytes: &[u8],
+        ) -> Result<Arc<dyn Asset>, AssetError> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            context.add_labelled_asset("length", Length(bytes.len()));
+            Ok(Arc::new(Text(String::from_utf8_lossy(bytes).into_owned())))
+        }
+    }
+
+    fn storage_with(files: &[(&str, &'static str)]) -> AssetStorage {
+        let mut storage = AssetStorage::new();
+        storage.unmount("");
+        storage.register_loader(FileLoader::<Length>::new(&["txt"]));
+        storage.mount("", embedded(files));
+        storage
+    }
+
+    fn embedded(files: &[(&str, &'static str)]) -> EmbeddedSource {
+        files
+            .iter()
+            .fold(EmbeddedSource::new(), |source, (path, text)| {
+                source.with(path, text.as_bytes())
+            })
+    }
+
+    /// Tick until the background workers have finished loading an asset
+    fn wait_for_load<HI: Into<HandleInner>>(storage: &mut AssetStorage, id: HI) {
+        let id = id.into();
+        for _ in 0..1000 {
+            storage.tick();
+            if !matches!(storage.load_state(id), Some(LoadState::Loading)) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("asset {:?} didn't finish loading", id);
+    }
+
+    #[test]
+    fn async_load_reports_loading_until_ticked() {
+        let mut storage = storage_with(&[("note.txt", "hello")]);
+
+        let text = storage.load_from_file_async::<Text>("note.txt").unwrap();
+        let missing = storage.load_from_file_async::<Text>("missing.txt").unwrap();
+
+        // Results are only received during a tick
+        assert!(matches!(
+            storage.load_state(text.id()),
+            Some(LoadState::Loading)
+        ));
+        assert!(matches!(
+            storage.load_state(missing.id()),
+            Some(LoadState::Loading)
+        ));
+        assert!(!storage.is_loaded(text.id()));
+
+        wait_for_load(&mut storage, text.id());
+        wait_for_load(&mut storage, missing.id());
+
+        assert!(matches!(
+            storage.load_state(text.id()),
+            Some(LoadState::Loaded)
+        ));
+        assert_eq!(storage.get_data(text.id()).unwrap().0, "hello");
+
+        match storage.load_state(missing.id()) {
+            Some(LoadState::Failed(error)) => assert!(matches!(**error, AssetError::Io { .. })),
+            _ => panic!("expected the missing file to fail loading"),
+        }
+    }
+
+    #[test]
+    fn async_loads_of_same_path_are_deduplicated() {
+        let mut storage = storage_with(&[("note.txt", "hello")]);
+        let mut reader = storage.event_reader::<Text>();
+
+        let first = storage.load_from_file_async::<Text>("note.txt").unwrap();
+        let second = storage.load_from_file_async::<Text>("note.txt").unwrap();
+        assert!(first.id() == second.id());
+
+        wait_for_load(&mut storage, first.id());
+
+        let events = storage.read_events(&mut reader);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], AssetEvent::Added(id) if id == first.id()));
+    }
+
+    #[test]
+    fn failed_load_sends_event() {
+        let mut storage = storage_with(&[]);
+        let mut reader = storage.event_reader::<Text>();
+
+        let text = storage.load_from_file_async::<Text>("missing.txt").unwrap();
+        wait_for_load(&mut storage, text.id());
+
+        let events = storage.read_events(&mut reader);
+        match &events[..] {
+            [AssetEvent::LoadFailed(id, error)] => {
+                assert!(*id == text.id());
+                assert!(matches!(**error, AssetError::Io { .. }));
+            }
+            other => panic!("expected a load failed event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rescan_loads_only_new_files() {
+        let mut storage = storage_with(&[("notes/a.txt", "a"), ("notes/b.txt", "bb")]);
+
+        let mut folder = storage.load_folder("notes", false).unwrap();
+        assert_eq!(folder.len(), 2);
+        let first = folder.get("notes/a.txt").unwrap().inner_id();
+
+        assert_eq!(storage.rescan(&mut folder).unwrap(), 0);
+        assert_eq!(folder.len(), 2);
+
+        storage.mount("notes", embedded(&[("c.txt", "ccc")]));
+        assert_eq!(storage.rescan(&mut folder).unwrap(), 1);
+
+        assert_eq!(
+            folder.paths().collect::<Vec<_>>(),
+            vec!["notes/a.txt", "notes/b.txt", "notes/c.txt"]
+        );
+        assert!(folder.get("notes/a.txt").unwrap().inner_id() == first);
+        assert_eq!(folder.typed::<Length>().len(), 3);
+        assert!(storage.is_folder_loaded(&folder));
+    }
+
+    #[test]
+    fn rescan_reports_and_retries_failed_files() {
+        let mut storage = storage_with(&[("data/good.ron", "1"), ("data/bad.ron", "one")]);
+        storage.register_loader(FileLoader::<DataAsset<u32>>::new(&["ron"]));
+
+        let mut folder = storage.load_folder("data", false).unwrap();
+        assert_eq!(folder.paths().collect::<Vec<_>>(), vec!["data/good.ron"]);
+
+        match folder.failed() {
+            [(path, error)] => {
+                assert_eq!(path, "data/bad.ron");
+                assert!(matches!(**error, AssetError::Parse { .. }));
+            }
+            other => panic!("expected one failed file, got {:?}", other),
+        }
+        assert!(matches!(
+            storage.folder_load_state(&folder),
+            LoadState::Failed(_)
+        ));
+
+        // Fixing the file loads it on the next rescan
+        storage.mount("data", embedded(&[("bad.ron", "2")]));
+        assert_eq!(storage.rescan(&mut folder).unwrap(), 1);
+
+        assert!(folder.failed().is_empty());
+        assert_eq!(folder.len(), 2);
+        assert!(matches!(
+            storage.folder_load_state(&folder),
+            LoadState::Loaded
+        ));
+    }
+
+    #[test]
+    fn reload_uses_loader_from_load() {
+        let mut storage = storage_with(&[("note.txt", "first"), ("other.txt", "other")]);
+        let text = storage.load_from_file::<Text>("note.txt").unwrap();
+
+        // The extension resolves to a different loader than the typed load used
+        let length = storage.load::<Length>("other.txt").unwrap();
+        assert_eq!(storage.get_data(length.id()).unwrap().0, 5);
+
+        storage.mount("", embedded(&[("note.txt", "second")]));
+        storage.reload(text.id());
+        storage.tick();
+
+        assert_eq!(storage.get_data(text.id()).unwrap().0, "second");
+        assert!(storage.get_reloaded_assets().contains(&text.inner_id()));
+    }
+
+    #[test]
+    fn staged_reload_is_finalized() {
+        let mut storage = storage_with(&[("note.txt", "first")]);
+        let text = storage.insert_data(Text("first".into()));
+        storage
+            .set_reload_staged(text.id(), "note.txt", |bytes| {
+                Ok(String::from_utf8_lossy(bytes).into_owned())
+            })
+            .unwrap();
+
+        storage.mount("", embedded(&[("note.txt", "second")]));
+        storage.reload(text.id());
+        storage.tick();
+        assert_eq!(storage.get_data(text.id()).unwrap().0, "first");
+
+        storage.finalize_staged(|text: String| Ok(Text(text)));
+        assert_eq!(storage.get_data(text.id()).unwrap().0, "second");
+        assert!(storage.get_reloaded_assets().contains(&text.inner_id()));
+    }
+
+    #[test]
+    fn declared_dependencies_recorded_and_reloaded() {
+        let mut storage = storage_with(&[("note.txt", "first")]);
+        let text = storage.load_from_file::<Text>("note.txt").unwrap();
+        let uses = storage.insert_data(Uses(text.clone()));
+
+        assert_eq!(storage.get_dependencies(uses.id()), vec![text.inner_id()]);
+
+        storage.reload(text.id());
+        storage.tick();
+        assert!(storage.get_reloaded_assets().contains(&uses.inner_id()));
+    }
+
+    #[test]
+    fn modify_without_clone_sends_event() {
+        let mut storage = storage_with(&[]);
+        let text = storage.insert_data(Text("first".into()));
+        let mut reader = storage.event_reader::<Text>();
+
+        // Text isn't Clone, so this must work without copy on write
+        storage
+            .modify(text.id(), |text| text.0.push_str(" edit"))
+            .unwrap();
+        assert_eq!(storage.get_data(text.id()).unwrap().0, "first edit");
+
+        let events = storage.read_events(&mut reader);
+        assert!(matches!(events[..], [AssetEvent::Modified(id)] if id == text.id()));
+    }
+
+    #[test]
+    fn sub_asset_keeps_file_loaded() {
+        let loads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut storage = storage_with(&[("data.sheet", "sheet")]);
+        storage.register_loader(SheetLoader(loads.clone()));
+
+        let length = storage.load::<Length>("data.sheet#length").unwrap();
+        assert_eq!(storage.get_data(length.id()).unwrap().0, 5);
+
+        storage.tick();
+        storage.tick();
+
+        // The file wasn't unloaded, so loading it again doesn't read it
+        let sheet = storage.load::<Text>("data.sheet").unwrap();
+        assert_eq!(loads.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(storage.get_data(sheet.id()).unwrap().0, "sheet");
+
+        let sheet_id = sheet.inner_id();
+        let length_id = length.inner_id();
+        drop(sheet);
+        drop(length);
+
+        storage.tick();
+        storage.tick();
+        assert!(storage.load_state(sheet_id).is_none());
+        assert!(storage.load_state(length_id).is_none());
+    }
+
+    #[test]
+    fn dependency_cycles_rejected() {
+        let mut storage = storage_with(&[]);
+        let a = storage.insert_data(Text("a".into()));
+        let b = storage.insert_data(Text("b".into()));
+        let c = storage.insert_data(Text("c".into()));
+
+        storage.add_dependency(a.id(), b.id()).unwrap();
+        storage.add_dependency(b.id(), c.id()).unwrap();
+
+        assert!(matches!(
+            storage.add_dependency(c.id(), a.id()),
+            Err(AssetStorageError::DependencyCycle)
+        ));
+        assert!(matches!(
+            storage.add_dependency(a.id(), a.id()),
+            Err(AssetStorageError::DependencyCycle)
+        ));
+    }
+
+    #[test]
+    fn retention_frames_keeps_unused_assets() {
+        let mut storage = storage_with(&[]);
+        storage.set_retention_policy(RetentionPolicy::new(RetentionTime::Frames(2)));
+
+        let blob = storage.insert_data(Blob(1));
+        let id = blob.id();
+        drop(blob);
+
+        storage.tick();
+        assert!(storage.get_data(id).is_ok());
+        storage.tick();
+        assert!(storage.get_data(id).is_ok());
+        storage.tick();
+        assert!(storage.get_data(id).is_err());
+
+        // Getting a new handle while unused keeps the asset loaded again
+        let blob = storage.insert_data(Blob(1));
+        let id = blob.id();
+        drop(blob);
+        storage.tick();
+
+        let handle = storage.get_handle::<Blob, _>(id).unwrap();
+        for _ in 0..5 {
+            storage.tick();
+        }
+        assert!(storage.get_data(handle.id()).is_ok());
+    }
+
+    #[test]
+    fn retention_seconds_and_forever() {
+        let mut storage = storage_with(&[]);
+
+        storage.set_retention_policy(RetentionPolicy::new(RetentionTime::Forever));
+        let forever = storage.insert_data(Blob(1)).id();
+        storage.tick();
+        for _ in 0..100 {
+            storage.tick();
+        }
+        assert!(storage.get_data(forever).is_ok());
+
+        storage.set_retention_policy(RetentionPolicy::new(RetentionTime::Seconds(60.)));
+        let long = storage.insert_data(Blob(1)).id();
+        storage.tick();
+        storage.tick();
+        assert!(storage.get_data(long).is_ok());
+
+        storage.set_retention_policy(RetentionPolicy::new(RetentionTime::Seconds(0.)));
+        storage.tick();
+        assert!(storage.get_data(long).is_err());
+        assert!(storage.get_data(forever).is_err());
+    }
+
+    #[test]
+    fn byte_budget_evicts_least_recently_used() {
+        let mut storage = storage_with(&[]);
+        storage.set_retention_policy(
+            RetentionPolicy::new(RetentionTime::Forever).with_byte_budget(13),
+        );
+
+        let used = storage.insert_data(Blob(4));
+        let first = storage.insert_data(Blob(3));
+        let second = storage.insert_data(Blob(3));
+        let third = storage.insert_data(Blob(3));
+        let ids = [first.id(), second.id(), third.id()];
+
+        // Released in the order first, third, second while under the budget
+        drop(first);
+        storage.tick();
+        drop(third);
+        storage.tick();
+        drop(second);
+        storage.tick();
+
+        assert_eq!(storage.stats().total_bytes, 13);
+        assert_eq!(storage.stats().unused_bytes, 9);
+
+        // 5 more bytes need the two least recently used assets gone
+        let added = storage.insert_data(Blob(5));
+        storage.tick();
+
+        assert!(storage.get_data(ids[0]).is_err());
+        assert!(storage.get_data(ids[2]).is_err());
+        assert!(storage.get_data(ids[1]).is_ok());
+        assert!(storage.get_data(used.id()).is_ok());
+        assert!(storage.get_data(added.id()).is_ok());
+        assert_eq!(storage.stats().total_bytes, 12);
+    }
+}
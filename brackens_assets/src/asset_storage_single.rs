@@ -2,14 +2,22 @@
 
 use crossbeam::channel::TryRecvError;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Weak},
+};
 
 // use log::info;
 
-use crate::{asset_storage::ReferenceCountSignal, handle::HandleInner, ReceiverType, SenderType};
+use crate::{
+    asset_storage::{AssetStorageError, ReferenceCountSignal},
+    events::{AssetEvent, AssetEventKind, AssetEventReader, EventQueue},
+    handle::{HandleAllocator, HandleInner},
+    ReceiverType, SenderType,
+};
 
 use super::{
-    handle::{Handle, HandleID},
+    handle::{Handle, HandleID, WeakHandle},
     Asset,
 };
 
@@ -19,16 +27,23 @@ pub struct AssetStorageSingle<T: Asset> {
     sender: SenderType<ReferenceCountSignal>,
     receiver: ReceiverType<ReferenceCountSignal>,
 
-    current_id: HandleInner,
+    ids: HandleAllocator,
 
     // The currently loaded data
     loaded: HashMap<HandleInner, Arc<T>>,
 
     // Keep track of how many strong handles in existance
     handle_count: HashMap<HandleInner, u32>,
+    // Dropped when an asset is unloaded so weak handles know they can't be upgraded
+    alive_tokens: HashMap<HandleInner, Arc<()>>,
     just_added: Vec<HandleInner>,
+    just_modified: Vec<HandleInner>,
     removed_assets: Vec<HandleInner>,
 
+    // Lifecycle events, kept until every reader has read them
+    events: EventQueue,
+    tick_count: u64,
+
     // Hashmap containing path to asset as a key. Used to check if data
     // is already loaded and if so, create a handle to it.
     loaded_paths: HashMap<String, HandleInner>,
@@ -50,12 +65,17 @@ where
         Self {
             sender,
             receiver,
-            current_id: HandleInner::from_id(0),
+            ids: HandleAllocator::default(),
             loaded: HashMap::new(),
             handle_count: HashMap::new(),
+            alive_tokens: HashMap::new(),
             just_added: Vec::new(),
+            just_modified: Vec::new(),
             removed_assets: Vec::new(),
 
+            events: EventQueue::default(),
+            tick_count: 0,
+
             loaded_paths: HashMap::new(),
             asset_paths: HashMap::new(),
         }
@@ -75,21 +95,43 @@ where
     //----------------------------------------------
 
     fn get_next_id(&mut self) -> HandleInner {
-        let to_return = self.current_id;
-        self.current_id.next();
-        to_return
+        self.ids.allocate()
+    }
+
+    /// Error for an id that isn't tracked by the storage
+    fn missing_error(&self, id: HandleInner) -> AssetStorageError {
+        match self.ids.is_stale(id) {
+            true => AssetStorageError::Stale,
+            false => AssetStorageError::AssetNotExist,
+        }
+    }
+
+    /// Check if an id refers to an asset that has since been unloaded
+    pub fn is_stale<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        self.ids.is_stale(id.into())
+    }
+
+    fn alive_token(&self, id: HandleInner) -> Weak<()> {
+        self.alive_tokens
+            .get(&id)
+            .map(Arc::downgrade)
+            .unwrap_or_default()
+    }
+
+    fn new_handle(&self, id: HandleInner) -> Handle<T> {
+        let handle_id = HandleID::new(id);
+        Handle::new(handle_id, self.sender.clone(), self.alive_token(id))
     }
 
     pub fn add_asset(&mut self, asset: T) -> Handle<T> {
         let id = self.get_next_id();
-        let handle_inner = Arc::new(asset);
-
-        self.loaded.insert(id, handle_inner.clone());
+        self.loaded.insert(id, Arc::new(asset));
         self.handle_count.insert(id, 0);
+        self.alive_tokens.insert(id, Arc::new(()));
         self.just_added.push(id);
+        self.events.push(self.tick_count, id, AssetEventKind::Added);
 
-        let handle_id = HandleID::new(id);
-        Handle::new(handle_id, self.sender.clone(), handle_inner)
+        self.new_handle(id)
     }
 
     pub fn add_asset_file<P: AsRef<str>>(&mut self, asset: T, path: P) -> Handle<T> {
@@ -109,19 +151,30 @@ where
 
     //----------------------------------------------
 
-    pub fn get_handle<HI: Into<HandleInner>>(&self, id: HI) -> Option<Handle<T>> {
+    pub fn get_handle<HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<Handle<T>, AssetStorageError> {
         let id = id.into();
-        match self.loaded.get(&id) {
-            Some(inner) => {
-                let handle_id = HandleID::new(id);
-                Some(
-                    Handle::new(handle_id, self.sender.clone(), inner.clone()), // Handle::strong(
-                                                                                // id.clone(),
-                                                                                // self.sender.clone(),
-                                                                                // data.clone(),
-                )
-            }
-            None => None,
+        match self.loaded.contains_key(&id) {
+            true => Ok(self.new_handle(id)),
+            false => Err(self.missing_error(id)),
+        }
+    }
+
+    /// Get a handle that doesn't keep the asset loaded
+    pub fn get_weak_handle<HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<WeakHandle<T>, AssetStorageError> {
+        let id = id.into();
+        match self.loaded.contains_key(&id) {
+            true => Ok(WeakHandle::new(
+                HandleID::new(id),
+                self.sender.clone(),
+                self.alive_token(id),
+            )),
+            false => Err(self.missing_error(id)),
         }
     }
 
@@ -136,29 +189,86 @@ where
             //     T::asset_name(),
             //     id
             // );
-            let handle_inner = self.loaded.get(id).unwrap().clone();
-            let handle_id = HandleID::new(*id);
-            return Some(Handle::new(handle_id, self.sender.clone(), handle_inner));
+            return Some(self.new_handle(*id));
         }
 
         None
     }
 
-    pub fn get_data<HI: Into<HandleInner>>(&self, id: HI) -> Option<&T> {
+    pub fn get_data<HI: Into<HandleInner>>(&self, id: HI) -> Result<&T, AssetStorageError> {
         let id = id.into();
         match self.loaded.get(&id) {
-            Some(val) => Some(val.as_ref()),
-            None => None,
+            Some(val) => Ok(val.as_ref()),
+            None => Err(self.missing_error(id)),
+        }
+    }
+
+    /// Mutable access to the data of an asset. Fails if something else still references
+    /// the data, see `make_mut` to copy it instead.
+    pub fn get_mut<HI: Into<HandleInner>>(&mut self, id: HI) -> Result<&mut T, AssetStorageError> {
+        let id = id.into();
+        match self.loaded.get(&id) {
+            Some(data) if Arc::strong_count(data) > 1 || Arc::weak_count(data) > 0 => {
+                return Err(AssetStorageError::AssetShared)
+            }
+            Some(_) => {}
+            None => return Err(self.missing_error(id)),
+        }
+
+        self.mark_modified(id);
+
+        self.loaded
+            .get_mut(&id)
+            .and_then(Arc::get_mut)
+            .ok_or(AssetStorageError::AssetShared)
+    }
+
+    /// Mutable access to the data of an asset like `get_mut`. If the data is shared it is
+    /// copied first so anything still holding the old data isn't affected.
+    pub fn make_mut<HI: Into<HandleInner>>(&mut self, id: HI) -> Result<&mut T, AssetStorageError>
+    where
+        T: Clone,
+    {
+        let id = id.into();
+        if !self.loaded.contains_key(&id) {
+            return Err(self.missing_error(id));
         }
+
+        self.mark_modified(id);
+
+        self.loaded
+            .get_mut(&id)
+            .map(Arc::make_mut)
+            .ok_or(AssetStorageError::AssetNotExist)
+    }
+
+    fn mark_modified(&mut self, id: HandleInner) {
+        if !self.just_modified.contains(&id) {
+            self.just_modified.push(id);
+            self.events
+                .push(self.tick_count, id, AssetEventKind::Modified);
+        }
+    }
+
+    pub fn modify<HI: Into<HandleInner>, R, F: FnOnce(&mut T) -> R>(
+        &mut self,
+        id: HI,
+        modify: F,
+    ) -> Result<R, AssetStorageError> {
+        Ok(modify(self.get_mut(id)?))
     }
 
     //----------------------------------------------
 
     pub fn tick(&mut self) {
+        self.tick_count += 1;
+        self.events.prune(self.tick_count, None);
+
         self.check_asset_changes();
         self.remove_pending_assets();
 
         self.clear_just_added();
+        self.clear_just_modified();
     }
 
     pub fn check_asset_changes(&mut self) {
@@ -178,19 +288,29 @@ where
                 }
             };
 
+            // Weak handles upgraded while their asset is unloading can signal missing assets
             match data {
                 ReferenceCountSignal::Increase(id) => {
-                    *self.handle_count.get_mut(&id).unwrap() += 1;
+                    if let Some(count) = self.handle_count.get_mut(&id) {
+                        *count += 1;
+                    }
                 }
                 ReferenceCountSignal::Decrease(id) => {
-                    let count = self.handle_count.get_mut(&id).unwrap();
-                    *count -= 1;
-                    if *count == 0 {
-                        self.removed_assets.push(id);
+                    if let Some(count) = self.handle_count.get_mut(&id) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.removed_assets.push(id);
+                        }
                     }
                 }
             }
         }
+
+        // New handles may have been created after the count reached zero
+        let handle_count = &self.handle_count;
+        let mut checked = HashSet::new();
+        self.removed_assets
+            .retain(|id| handle_count.get(id) == Some(&0) && checked.insert(*id));
     }
 
     pub fn remove_pending_assets(&mut self) {
@@ -203,6 +323,10 @@ where
 
             self.loaded.remove(&to_remove); //Remove Asset
             self.handle_count.remove(&to_remove); //Remove Counter
+            self.alive_tokens.remove(to_remove);
+            self.ids.free(*to_remove);
+            self.events
+                .push(self.tick_count, *to_remove, AssetEventKind::Removed);
         }
 
         for to_remove in &self.removed_assets {
@@ -219,24 +343,75 @@ where
         self.just_added.clear();
     }
 
+    pub fn clear_just_modified(&mut self) {
+        self.just_modified.clear();
+    }
+
     //----------------------------------------------
 
     pub fn get_just_added(&self) -> Vec<Handle<T>> {
         self.just_added
             .iter()
-            .map(|id| {
-                let handle_id = HandleID::new(*id);
-                let inner = self.loaded.get(id).unwrap().clone();
-                Handle::new(handle_id, self.sender.clone(), inner)
-            })
+            .map(|id| self.new_handle(*id))
             .collect()
     }
 
+    pub fn get_just_modified(&self) -> &Vec<HandleInner> {
+        &self.just_modified
+    }
+
     pub fn get_removed_assets(&self) -> &Vec<HandleInner> {
         &self.removed_assets
     }
 
+    /// Reader that only receives events sent after it was created
+    pub fn event_reader(&mut self) -> AssetEventReader<T> {
+        self.events.reader()
+    }
+
+    /// Get all events the reader hasn't read yet
+    pub fn read_events(&self, reader: &mut AssetEventReader<T>) -> Vec<AssetEvent<T>> {
+        self.events.read(reader)
+    }
+
     //----------------------------------------------
 }
 
 //===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Text(String);
+
+    impl Asset for Text {
+        fn asset_name(&self) -> &str {
+            "Text"
+        }
+    }
+
+    #[test]
+    fn lifecycle_sends_events() {
+        let mut storage = AssetStorageSingle::<Text>::new();
+        let mut reader = storage.event_reader();
+
+        let text = storage.add_asset(Text("first".into()));
+        let id = text.id();
+        storage.modify(id, |text| text.0.push_str(" edit")).unwrap();
+        storage
+            .modify(id, |text| text.0.push_str(" again"))
+            .unwrap();
+
+        drop(text);
+        storage.tick();
+        storage.tick();
+
+        let events = storage.read_events(&mut reader);
+        assert!(matches!(
+            events[..],
+            [AssetEvent::Added(a), AssetEvent::Modified(b), AssetEvent::Removed(c)]
+                if a == id && b == id && c == id
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//===============================================================
+
+use std::process::ExitCode;
+
+use brackens_assets::pack::{pack_directory, PackCompression, PackFilter};
+
+//===============================================================
+
+const USAGE: &str = "\
+Build an asset pack from a directory
+
+Usage: brackens_pack <INPUT_DIR> <OUTPUT_FILE> [OPTIONS]
+
+Options:
+  -i, --include <GLOB>  Only pack files matching the glob. Can be repeated
+  -e, --exclude <GLOB>  Skip files matching the glob. Can be repeated
+      --no-compress     Store every file uncompressed
+  -h, --help            Print this message";
+
+//===============================================================
+
+struct Args {
+    input: String,
+    output: String,
+    filter: PackFilter,
+    compression: PackCompression,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut positional = Vec::new();
+    let mut filter = PackFilter::new();
+    let mut compression = PackCompression::Deflate;
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-i" | "--include" | "-e" | "--exclude" => {
+                let pattern = args
+                    .next()
+                    .ok_or_else(|| format!("Missing glob after '{}'", arg))?;
+
+                let result = match arg.as_str() {
+                    "-i" | "--include" => filter.include(&pattern),
+                    _ => filter.exclude(&pattern),
+                };
+                filter = result.map_err(|e| format!("Invalid glob '{}' - {}", pattern, e))?;
+            }
+            "--no-compress" => compression = PackCompression::None,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    match <[String; 2]>::try_from(positional) {
+        Ok([input, output]) => Ok(Some(Args {
+            input,
+            output,
+            filter,
+            compression,
+        })),
+        Err(_) => Err("Expected an input directory and an output file".into()),
+    }
+}
+
+//===============================================================
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let writer = match pack_directory(&args.input, &args.filter, args.compression) {
+        Ok(writer) => writer,
+        Err(e) => {
+            eprintln!("Error: Unable to pack directory '{}' - {}", args.input, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = writer.write_to_file(&args.output) {
+        eprintln!("Error: Unable to write pack '{}' - {}", args.output, e);
+        return ExitCode::FAILURE;
+    }
+
+    println!("Packed {} files into '{}'", writer.len(), args.output);
+    ExitCode::SUCCESS
+}
+
+//===============================================================
@@ -0,0 +1,466 @@
+//===============================================================
+
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{data_asset::line_column, Asset, AssetError, AssetFileLoadable};
+
+//===============================================================
+
+/// Area of a page texture containing a character and how to place it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BitmapGlyph {
+    /// Pixel rect of the glyph in its page
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Offset from the pen position to the top left of the glyph, with y going down
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// Distance to move the pen after the glyph
+    pub x_advance: i32,
+    pub page: usize,
+}
+
+//===============================================================
+
+/// Font made of pre-rendered glyphs in one or more page textures, loaded from an AngelCode
+/// BMFont `.fnt` file in either the text or the XML format. The page textures aren't
+/// loaded with the font.
+#[derive(Clone, Debug, Default)]
+pub struct BitmapFont {
+    pub face: String,
+    /// Size the font was rendered at. Negative if it matches the height of the characters
+    /// rather than the cells.
+    pub size: i32,
+    /// Distance between the top of each line
+    pub line_height: u32,
+    /// Distance from the top of a line to the baseline
+    pub base: u32,
+    /// Size of the page textures
+    pub scale_w: u32,
+    pub scale_h: u32,
+    /// Paths of the page textures, relative to the same directory as the `.fnt` file
+    pub pages: Vec<String>,
+
+    glyphs: HashMap<char, BitmapGlyph>,
+    kernings: HashMap<(char, char), i32>,
+}
+
+impl Asset for BitmapFont {
+    fn asset_name(&self) -> &str {
+        "Bitmap Font"
+    }
+
+    fn byte_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.glyphs.len() * std::mem::size_of::<(char, BitmapGlyph)>()
+            + self.kernings.len() * std::mem::size_of::<((char, char), i32)>()
+    }
+}
+
+impl AssetFileLoadable for BitmapFont {
+    fn load_from_bytes(path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+        Self::parse(path, bytes)
+    }
+}
+
+impl BitmapFont {
+    //----------------------------------------------
+
+    /// Parse a font file. Page paths are made relative to the directory of the given path.
+    pub fn parse(path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+        let source = std::str::from_utf8(bytes).map_err(|e| AssetError::decode(path, e))?;
+
+        let elements = match source.trim_start().starts_with('<') {
+            true => xml_elements(source),
+            false => text_elements(source),
+        };
+
+        elements
+            .and_then(|elements| Self::from_elements(path, elements))
+            .map_err(|(offset, message)| {
+                let (line, column) = line_column(source, offset);
+                AssetError::parse(path, line, column, message)
+            })
+    }
+
+    fn from_elements(path: &str, elements: Vec<Element>) -> Result<Self, (usize, String)> {
+        let mut font = BitmapFont::default();
+
+        for element in elements {
+            match element.name {
+                "info" => {
+                    font.face = element.value("face").unwrap_or_default().to_string();
+                    font.size = element.get("size")?.unwrap_or(0);
+                }
+                "common" => {
+                    font.line_height = element.get("lineHeight")?.unwrap_or(0);
+                    font.base = element.get("base")?.unwrap_or(0);
+                    font.scale_w = element.get("scaleW")?.unwrap_or(0);
+                    font.scale_h = element.get("scaleH")?.unwrap_or(0);
+                }
+                "page" => {
+                    let id: usize = element.get("id")?.unwrap_or(0);
+                    let file = element.required("file")?;
+
+                    if font.pages.len() <= id {
+                        font.pages.resize(id + 1, String::new());
+                    }
+                    font.pages[id] = relative_path(path, file);
+                }
+                "char" => {
+                    let id: u32 = element.required("id")?.parse().map_err(|_| {
+                        (element.offset, "char id must be a positive number".into())
+                    })?;
+
+                    // Some tools write an invalid id for the glyph of missing characters
+                    let character = match char::from_u32(id) {
+                        Some(character) => character,
+                        None => continue,
+                    };
+
+                    font.glyphs.insert(
+                        character,
+                        BitmapGlyph {
+                            x: element.get("x")?.unwrap_or(0),
+                            y: element.get("y")?.unwrap_or(0),
+                            width: element.get("width")?.unwrap_or(0),
+                            height: element.get("height")?.unwrap_or(0),
+                            x_offset: element.get("xoffset")?.unwrap_or(0),
+                            y_offset: element.get("yoffset")?.unwrap_or(0),
+                            x_advance: element.get("xadvance")?.unwrap_or(0),
+                            page: element.get("page")?.unwrap_or(0),
+                        },
+                    );
+                }
+                "kerning" => {
+                    let first = element.get::<u32>("first")?.and_then(char::from_u32);
+                    let second = element.get::<u32>("second")?.and_then(char::from_u32);
+                    let amount: i32 = element.get("amount")?.unwrap_or(0);
+
+                    if let (Some(first), Some(second)) = (first, second) {
+                        font.kernings.insert((first, second), amount);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(page) = font.pages.iter().position(|page| page.is_empty()) {
+            return Err((0, format!("page {} is missing", page)));
+        }
+
+        if let Some((character, glyph)) = font
+            .glyphs
+            .iter()
+            .find(|(_, glyph)| glyph.page >= font.pages.len())
+        {
+            return Err((
+                0,
+                format!(
+                    "char '{}' uses page {} which doesn't exist",
+                    character, glyph.page
+                ),
+            ));
+        }
+
+        Ok(font)
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn glyph(&self, character: char) -> Option<&BitmapGlyph> {
+        self.glyphs.get(&character)
+    }
+
+    #[inline]
+    pub fn glyphs(&self) -> impl Iterator<Item = (&char, &BitmapGlyph)> {
+        self.glyphs.iter()
+    }
+
+    /// Extra distance to move the pen between two characters
+    #[inline]
+    pub fn kerning(&self, first: char, second: char) -> i32 {
+        self.kernings.get(&(first, second)).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Tag of a font file with its attributes
+struct Element<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    /// Byte offset of the tag in the file
+    offset: usize,
+}
+
+impl<'a> Element<'a> {
+    fn value(&self, key: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| *value)
+    }
+
+    fn required(&self, key: &str) -> Result<&'a str, (usize, String)> {
+        self.value(key)
+            .ok_or_else(|| (self.offset, format!("{} without '{}'", self.name, key)))
+    }
+
+    fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, (usize, String)> {
+        match self.value(key) {
+            Some(value) => value.parse().map(Some).map_err(|_| {
+                (
+                    self.offset,
+                    format!("invalid value '{}' for '{}'", value, key),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+type ElementResult<'a> = Result<Vec<Element<'a>>, (usize, String)>;
+
+/// Lines of the form `tag key=value key="quoted value"`
+fn text_elements(source: &str) -> ElementResult<'_> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+
+    for line in source.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.trim_end().is_empty() {
+            continue;
+        }
+        let start = line_offset + (line.len() - trimmed.len());
+
+        let (name, attributes) = match trimmed.split_once(char::is_whitespace) {
+            Some((name, attributes)) => (name, attributes),
+            None => (trimmed.trim_end(), ""),
+        };
+
+        elements.push(Element {
+            name,
+            attributes: parse_attributes(attributes, start + name.len() + 1)?,
+            offset: start,
+        });
+    }
+
+    Ok(elements)
+}
+
+/// Elements of the form `<tag key="value" />`. Declarations, comments and closing tags
+/// are skipped.
+fn xml_elements(source: &str) -> ElementResult<'_> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = source[offset..].find('<') {
+        let start = offset + start;
+
+        // Comments can contain '>' so skip to their end
+        if source[start..].starts_with("<!--") {
+            offset = match source[start..].find("-->") {
+                Some(end) => start + end + 3,
+                None => return Err((start, "unclosed comment".into())),
+            };
+            continue;
+        }
+
+        let end = match source[start..].find('>') {
+            Some(end) => start + end,
+            None => return Err((start, "unclosed tag".into())),
+        };
+        offset = end + 1;
+
+        let tag = &source[start + 1..end];
+        if tag.starts_with(['?', '!', '/']) {
+            continue;
+        }
+        let tag = tag.strip_suffix('/').unwrap_or(tag);
+
+        let (name, attributes) = match tag.split_once(char::is_whitespace) {
+            Some((name, attributes)) => (name, attributes),
+            None => (tag, ""),
+        };
+
+        elements.push(Element {
+            name,
+            attributes: parse_attributes(attributes, start + name.len() + 2)?,
+            offset: start,
+        });
+    }
+
+    Ok(elements)
+}
+
+/// Parse `key=value` pairs separated by whitespace. Values can be quoted to contain spaces.
+fn parse_attributes(source: &str, offset: usize) -> Result<Vec<(&str, &str)>, (usize, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = source;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(attributes);
+        }
+        let position = offset + (source.len() - rest.len());
+
+        let (key, after) = match rest.split_once('=') {
+            Some((key, after)) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+                (key, after.trim_start())
+            }
+            _ => return Err((position, "expected key=value".into())),
+        };
+
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some(split) => split,
+                None => return Err((position, format!("unclosed quote for '{}'", key))),
+            },
+            None => after.split_at(after.find(char::is_whitespace).unwrap_or(after.len())),
+        };
+
+        attributes.push((key, value));
+        rest = after;
+    }
+}
+
+/// Path of a file in the same directory as another
+fn relative_path(path: &str, file: &str) -> String {
+    match path.rfind(['/', '\\']) {
+        Some(index) => format!("{}/{}", &path[..index], file),
+        None => file.to_string(),
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT_FONT: &str = "info face=\"Pixel Font\" size=-12
+common lineHeight=14 base=11 scaleW=64 scaleH=32 pages=1
+page id=0 file=\"pixel_0.png\"
+chars count=2
+char id=65 x=1 y=2 width=5 height=7 xoffset=0 yoffset=4 xadvance=6 page=0
+char id=66 x=7 y=2 width=5 height=7 xoffset=1 yoffset=4 xadvance=6 page=0
+kernings count=1
+kerning first=65 second=66 amount=-1
+";
+
+    const XML_FONT: &str = r#"<?xml version="1.0"?>
+<font>
+  <info face="Pixel Font" size="-12" />
+  <common lineHeight="14" base="11" scaleW="64" scaleH="32" pages="2" />
+  <pages>
+    <page id="0" file="pixel_0.png" />
+    <page id="1" file="pixel_1.png" />
+  </pages>
+  <!-- glyphs > comments -->
+  <chars count="1">
+    <char id="65" x="1" y="2" width="5" height="7" xoffset="0" yoffset="4" xadvance="6" page="1" />
+  </chars>
+  <kernings count="1">
+    <kerning first="65" second="65" amount="2" />
+  </kernings>
+</font>
+"#;
+
+    #[test]
+    fn text_font_parsed() {
+        let font = BitmapFont::parse("fonts/pixel.fnt", TEXT_FONT.as_bytes()).unwrap();
+
+        assert_eq!(font.face, "Pixel Font");
+        assert_eq!(font.size, -12);
+        assert_eq!((font.line_height, font.base), (14, 11));
+        assert_eq!((font.scale_w, font.scale_h), (64, 32));
+        assert_eq!(font.pages, vec!["fonts/pixel_0.png".to_string()]);
+        assert_eq!(font.len(), 2);
+        assert_eq!(
+            font.glyph('B'),
+            Some(&BitmapGlyph {
+                x: 7,
+                y: 2,
+                width: 5,
+                height: 7,
+                x_offset: 1,
+                y_offset: 4,
+                x_advance: 6,
+                page: 0,
+            })
+        );
+        assert_eq!(font.kerning('A', 'B'), -1);
+        assert_eq!(font.kerning('B', 'A'), 0);
+    }
+
+    #[test]
+    fn xml_font_parsed() {
+        let font = BitmapFont::parse("pixel.fnt", XML_FONT.as_bytes()).unwrap();
+
+        assert_eq!(font.face, "Pixel Font");
+        assert_eq!(font.line_height, 14);
+        assert_eq!(
+            font.pages,
+            vec!["pixel_0.png".to_string(), "pixel_1.png".to_string()]
+        );
+        assert_eq!(font.glyph('A').map(|glyph| glyph.page), Some(1));
+        assert_eq!(font.kerning('A', 'A'), 2);
+    }
+
+    #[test]
+    fn missing_page_reports_error() {
+        let source = "page id=1 file=\"pixel_1.png\"\n";
+
+        match BitmapFont::parse("pixel.fnt", source.as_bytes()) {
+            Err(AssetError::Parse { message, .. }) => assert_eq!(message, "page 0 is missing"),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_page_reports_error() {
+        let source = "page id=0 file=\"pixel_0.png\"\nchar id=65 x=0 y=0 page=3\n";
+
+        match BitmapFont::parse("pixel.fnt", source.as_bytes()) {
+            Err(AssetError::Parse { message, .. }) => {
+                assert_eq!(message, "char 'A' uses page 3 which doesn't exist")
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        let source = "page id=0 file=\"pixel_0.png\"\nchar id=65 page=first\n";
+
+        match BitmapFont::parse("pixel.fnt", source.as_bytes()) {
+            Err(AssetError::Parse {
+                line,
+                column,
+                message,
+                ..
+            }) => {
+                assert_eq!((line, column), (2, 1));
+                assert_eq!(message, "invalid value 'first' for 'page'");
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
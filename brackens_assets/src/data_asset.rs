@@ -0,0 +1,180 @@
+//===============================================================
+
+use std::{ops::Deref, path::Path};
+
+use serde::de::DeserializeOwned;
+
+use crate::{Asset, AssetError, AssetFileLoadable};
+
+//===============================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    Ron,
+    Json,
+    Toml,
+}
+
+impl DataFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "ron" => Some(Self::Ron),
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+//===============================================================
+
+/// Deserialize data from a RON, JSON or TOML file. Parse errors report the line and column
+/// they happened at.
+pub fn parse_data<T: DeserializeOwned>(
+    path: &str,
+    format: DataFormat,
+    bytes: &[u8],
+) -> Result<T, AssetError> {
+    let source = std::str::from_utf8(bytes).map_err(|e| AssetError::decode(path, e))?;
+
+    match format {
+        DataFormat::Ron => ron::from_str(source)
+            .map_err(|e| AssetError::parse(path, e.position.line, e.position.col, e.code)),
+
+        DataFormat::Json => serde_json::from_str(source).map_err(|e| {
+            // Json errors include the position in their message
+            let message = e.to_string();
+            let message = match message.rsplit_once(" at line ") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            AssetError::parse(path, e.line(), e.column(), message)
+        }),
+
+        DataFormat::Toml => toml::from_str(source).map_err(|e| {
+            let (line, column) = match e.span() {
+                Some(span) => line_column(source, span.start),
+                None => (0, 0),
+            };
+            AssetError::parse(path, line, column, e.message())
+        }),
+    }
+}
+
+/// One based line and column of a byte offset
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+
+    let line = before.matches('\n').count() + 1;
+    let column = match before.rfind('\n') {
+        Some(line_start) => before[line_start + 1..].chars().count() + 1,
+        None => before.chars().count() + 1,
+    };
+
+    (line, column)
+}
+
+//===============================================================
+
+/// Asset holding data deserialized from a RON, JSON or TOML file depending on its
+/// extension. Used for data such as enemy stats or level definitions.
+///
+/// Load with `AssetStorage::load_from_file::<DataAsset<T>>`. Loads are deduplicated by
+/// path and can be reloaded with `AssetStorage::reload`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataAsset<T> {
+    data: T,
+}
+
+impl<T> DataAsset<T> {
+    pub fn new(data: T) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl<T> Deref for DataAsset<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> Asset for DataAsset<T> {
+    fn asset_name(&self) -> &str {
+        "Data Asset"
+    }
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> AssetFileLoadable for DataAsset<T> {
+    fn load_from_bytes(path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+        let format = DataFormat::from_path(path).ok_or_else(|| {
+            let extension = Path::new(path)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default();
+            AssetError::unsupported_format(path, extension)
+        })?;
+
+        parse_data(path, format, bytes).map(Self::new)
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Stats {
+        health: u32,
+        speed: f32,
+    }
+
+    fn parse_position(path: &str, source: &str) -> (usize, usize) {
+        match DataAsset::<Stats>::load_from_bytes(path, source.as_bytes()) {
+            Err(AssetError::Parse {
+                path: error_path,
+                line,
+                column,
+                ..
+            }) => {
+                assert_eq!(error_path, path);
+                (line, column)
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ron_parse_error_has_line_and_column() {
+        let source = "(\n    health: 10,\n    speed: ,\n)";
+        assert_eq!(parse_position("stats.ron", source), (3, 12));
+    }
+
+    #[test]
+    fn toml_parse_error_has_line_and_column() {
+        let source = "health = 10\nspeed = \n";
+        assert_eq!(parse_position("stats.toml", source), (2, 9));
+    }
+}
@@ -1,11 +1,19 @@
 //===============================================================
 
-use std::{fs::File, io::Read};
-
-use super::{Asset, AssetFileLoadable};
+use super::{Asset, AssetError, AssetFileLoadable, BitmapFont, FileLoader, LoaderRegistry};
 use glyph_brush::ab_glyph::FontArc;
-use image::DynamicImage;
-use log::error;
+use image::{DynamicImage, ImageError, ImageFormat};
+
+//===============================================================
+
+/// Register loaders for the asset types implemented in this module
+pub fn register_default_loaders(registry: &mut LoaderRegistry) {
+    registry.register(FileLoader::<DynamicImage>::new(&[
+        "png", "jpg", "jpeg", "bmp", "gif", "ico", "tga", "tif", "tiff", "webp", "pnm", "hdr",
+    ]));
+    registry.register(FileLoader::<FontArc>::new(&["ttf", "otf"]));
+    registry.register(FileLoader::<BitmapFont>::new(&["fnt"]));
+}
 
 //===============================================================
 
@@ -13,21 +21,29 @@ impl Asset for DynamicImage {
     fn asset_name(&self) -> &str {
         "Dynamic Image"
     }
+
+    fn byte_size(&self) -> usize {
+        self.as_bytes().len()
+    }
 }
 
 impl AssetFileLoadable for DynamicImage {
-    fn load_from_file(path: &str) -> Self {
-        match image::open(path) {
-            Ok(val) => val,
-            Err(e) => {
-                error!("Error: Could not open image - {}", e);
-                Self::load_default()
-            }
-        }
+    fn load_from_bytes(path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+        let result = match ImageFormat::from_path(path) {
+            Ok(format) => image::load_from_memory_with_format(bytes, format),
+            // Unknown extension, try to guess the format from the data instead
+            Err(_) => image::load_from_memory(bytes),
+        };
+
+        result.map_err(|e| match e {
+            ImageError::IoError(e) => AssetError::io(path, e),
+            ImageError::Unsupported(e) => AssetError::unsupported_format(path, e.format_hint()),
+            e => AssetError::decode(path, e),
+        })
     }
 
-    fn load_default() -> Self {
-        DynamicImage::default()
+    fn load_default() -> Option<Self> {
+        Some(DynamicImage::default())
     }
 }
 
@@ -39,32 +55,8 @@ impl Asset for FontArc {
     }
 }
 impl AssetFileLoadable for FontArc {
-    fn load_from_file(path: &str) -> Self {
-        let mut file = match File::open(path) {
-            Ok(file) => file,
-            Err(e) => {
-                error!("Error: Unable to load Font Asset - {}", e);
-                return Self::load_default();
-            }
-        };
-
-        let mut buffer = vec![];
-        if let Err(e) = file.read_to_end(&mut buffer) {
-            error!("Error: Unable to read font file - {}", e);
-            return Self::load_default();
-        }
-
-        match FontArc::try_from_vec(buffer) {
-            Ok(font) => font,
-            Err(e) => {
-                error!("Error: Unable to read font from file - {}", e);
-                Self::load_default()
-            }
-        }
-    }
-
-    fn load_default() -> Self {
-        panic!("Error: Unable to load font");
+    fn load_from_bytes(path: &str, bytes: &[u8]) -> Result<Self, AssetError> {
+        FontArc::try_from_vec(bytes.to_vec()).map_err(|e| AssetError::decode(path, e))
     }
 }
 
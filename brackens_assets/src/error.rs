@@ -0,0 +1,161 @@
+//===============================================================
+
+//===============================================================
+
+#[derive(Debug)]
+pub enum AssetError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    Decode {
+        path: String,
+        source: anyhow::Error,
+    },
+    UnsupportedFormat {
+        path: String,
+        format: String,
+    },
+    Dependency {
+        path: String,
+        source: Box<AssetError>,
+    },
+    MissingLabel {
+        path: String,
+        label: String,
+    },
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl AssetError {
+    //----------------------------------------------
+
+    pub fn io(path: &str, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn decode<E: Into<anyhow::Error>>(path: &str, source: E) -> Self {
+        Self::Decode {
+            path: path.into(),
+            source: source.into(),
+        }
+    }
+
+    pub fn unsupported_format<F: ToString>(path: &str, format: F) -> Self {
+        Self::UnsupportedFormat {
+            path: path.into(),
+            format: format.to_string(),
+        }
+    }
+
+    pub fn dependency(path: &str, source: AssetError) -> Self {
+        Self::Dependency {
+            path: path.into(),
+            source: Box::new(source),
+        }
+    }
+
+    pub fn parse<M: ToString>(path: &str, line: usize, column: usize, message: M) -> Self {
+        Self::Parse {
+            path: path.into(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn missing_label(path: &str, label: &str) -> Self {
+        Self::MissingLabel {
+            path: path.into(),
+            label: label.into(),
+        }
+    }
+
+    //----------------------------------------------
+
+    pub fn path(&self) -> &str {
+        match self {
+            AssetError::Io { path, .. }
+            | AssetError::Decode { path, .. }
+            | AssetError::UnsupportedFormat { path, .. }
+            | AssetError::Dependency { path, .. }
+            | AssetError::MissingLabel { path, .. }
+            | AssetError::Parse { path, .. } => path,
+        }
+    }
+
+    //----------------------------------------------
+}
+
+impl std::fmt::Display for AssetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetError::Io { path, source } => {
+                write!(f, "AssetError: Unable to read '{}' - {}", path, source)
+            }
+            AssetError::Decode { path, source } => {
+                write!(f, "AssetError: Unable to decode '{}' - {}", path, source)
+            }
+            AssetError::UnsupportedFormat { path, format } => write!(
+                f,
+                "AssetError: Unable to load '{}' - unsupported format '{}'",
+                path, format
+            ),
+            AssetError::Dependency { path, source } => write!(
+                f,
+                "AssetError: Unable to load dependency of '{}' - {}",
+                path, source
+            ),
+            AssetError::MissingLabel { path, label } => write!(
+                f,
+                "AssetError: '{}' doesn't contain a sub-asset labelled '{}'",
+                path, label
+            ),
+            AssetError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "AssetError: Unable to parse '{}' at line {}, column {} - {}",
+                path, line, column, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::Io { source, .. } => Some(source),
+            AssetError::Decode { source, .. } => Some(source.as_ref()),
+            AssetError::UnsupportedFormat { .. }
+            | AssetError::MissingLabel { .. }
+            | AssetError::Parse { .. } => None,
+            AssetError::Dependency { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+//===============================================================
+
+/// What an asset storage should do when an asset fails to load from file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FallbackPolicy {
+    /// Return the error to the caller.
+    #[default]
+    Error,
+    /// Log the error and use the asset's placeholder instead, if it has one.
+    Placeholder,
+}
+
+//===============================================================
@@ -0,0 +1,277 @@
+//===============================================================
+
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+};
+
+use crate::{handle::HandleInner, Asset, AssetError, HandleID};
+
+//===============================================================
+
+pub enum AssetEvent<T: Asset> {
+    /// Asset has finished loading and its data is available
+    Added(HandleID<T>),
+    /// Asset data has been replaced, either by a reload or a modification
+    Modified(HandleID<T>),
+    Removed(HandleID<T>),
+    LoadFailed(HandleID<T>, Arc<AssetError>),
+}
+
+impl<T: Asset> AssetEvent<T> {
+    pub fn id(&self) -> HandleID<T> {
+        match self {
+            AssetEvent::Added(id)
+            | AssetEvent::Modified(id)
+            | AssetEvent::Removed(id)
+            | AssetEvent::LoadFailed(id, _) => *id,
+        }
+    }
+}
+
+impl<T: Asset> Clone for AssetEvent<T> {
+    fn clone(&self) -> Self {
+        match self {
+            AssetEvent::Added(id) => AssetEvent::Added(*id),
+            AssetEvent::Modified(id) => AssetEvent::Modified(*id),
+            AssetEvent::Removed(id) => AssetEvent::Removed(*id),
+            AssetEvent::LoadFailed(id, e) => AssetEvent::LoadFailed(*id, e.clone()),
+        }
+    }
+}
+
+impl<T: Asset> std::fmt::Debug for AssetEvent<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetEvent::Added(id) => write!(f, "Added({:?})", id.id()),
+            AssetEvent::Modified(id) => write!(f, "Modified({:?})", id.id()),
+            AssetEvent::Removed(id) => write!(f, "Removed({:?})", id.id()),
+            AssetEvent::LoadFailed(id, e) => write!(f, "LoadFailed({:?}, {})", id.id(), e),
+        }
+    }
+}
+
+//===============================================================
+
+/// Cursor into the events of a single asset type. Each system reading events should keep
+/// its own reader. Events are kept until every reader has read them, so a reader that is
+/// no longer used should be dropped.
+pub struct AssetEventReader<T: Asset> {
+    // Shared with the queue so it knows which events are still unread
+    next_sequence: Arc<AtomicU64>,
+    missed: u64,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T: Asset> AssetEventReader<T> {
+    fn new(next_sequence: Arc<AtomicU64>) -> Self {
+        Self {
+            next_sequence,
+            missed: 0,
+            data: PhantomData,
+        }
+    }
+
+    /// Number of events that were discarded before this reader got to read them. Only
+    /// happens when the retention policy limits how long events are kept.
+    #[inline]
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+//===============================================================
+
+#[derive(Clone, Debug)]
+pub(crate) enum AssetEventKind {
+    Added,
+    Modified,
+    Removed,
+    LoadFailed(Arc<AssetError>),
+}
+
+struct StoredEvent {
+    sequence: u64,
+    tick: u64,
+    id: HandleInner,
+    kind: AssetEventKind,
+}
+
+/// Events of a single asset type
+#[derive(Default)]
+pub(crate) struct EventQueue {
+    events: VecDeque<StoredEvent>,
+    next_sequence: u64,
+    readers: Vec<Weak<AtomicU64>>,
+}
+
+impl EventQueue {
+    pub fn push(&mut self, tick: u64, id: HandleInner, kind: AssetEventKind) {
+        self.events.push_back(StoredEvent {
+            sequence: self.next_sequence,
+            tick,
+            id,
+            kind,
+        });
+        self.next_sequence += 1;
+    }
+
+    /// Reader of the events pushed from now on
+    pub fn reader<T: Asset>(&mut self) -> AssetEventReader<T> {
+        let next_sequence = Arc::new(AtomicU64::new(self.next_sequence));
+        self.readers.push(Arc::downgrade(&next_sequence));
+
+        AssetEventReader::new(next_sequence)
+    }
+
+    /// Discard events every reader has read, along with events older than the maximum age
+    /// in ticks if there is one
+    pub fn prune(&mut self, tick: u64, max_age: Option<u64>) {
+        self.readers.retain(|reader| reader.strong_count() > 0);
+
+        let oldest_unread = self
+            .readers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|reader| reader.load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(self.next_sequence);
+
+        while let Some(event) = self.events.front() {
+            let expired = max_age.is_some_and(|max_age| event.tick + max_age < tick);
+            if event.sequence >= oldest_unread && !expired {
+                break;
+            }
+            self.events.pop_front();
+        }
+    }
+
+    pub fn read<T: Asset>(&self, reader: &mut AssetEventReader<T>) -> Vec<AssetEvent<T>> {
+        let first_sequence = match self.events.front() {
+            Some(event) => event.sequence,
+            None => self.next_sequence,
+        };
+
+        let mut next_sequence = reader.next_sequence.load(Ordering::Relaxed);
+        if next_sequence < first_sequence {
+            reader.missed += first_sequence - next_sequence;
+            next_sequence = first_sequence;
+        }
+
+        let start = (next_sequence - first_sequence) as usize;
+
+        let events = self
+            .events
+            .iter()
+            .skip(start)
+            .map(|event| {
+                let id = HandleID::new(event.id);
+                match &event.kind {
+                    AssetEventKind::Added => AssetEvent::Added(id),
+                    AssetEventKind::Modified => AssetEvent::Modified(id),
+                    AssetEventKind::Removed => AssetEvent::Removed(id),
+                    AssetEventKind::LoadFailed(e) => AssetEvent::LoadFailed(id, e.clone()),
+                }
+            })
+            .collect();
+
+        reader
+            .next_sequence
+            .store(self.next_sequence, Ordering::Relaxed);
+
+        events
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::handle::HandleAllocator;
+
+    use super::*;
+
+    struct Text;
+
+    impl Asset for Text {
+        fn asset_name(&self) -> &str {
+            "Text"
+        }
+    }
+
+    fn ids(events: &[AssetEvent<Text>]) -> Vec<HandleInner> {
+        events.iter().map(|event| event.id().into()).collect()
+    }
+
+    #[test]
+    fn readers_have_their_own_cursor() {
+        let mut allocator = HandleAllocator::default();
+        let (first, second) = (allocator.allocate(), allocator.allocate());
+
+        let mut queue = EventQueue::default();
+        let mut early = queue.reader::<Text>();
+
+        queue.push(0, first, AssetEventKind::Added);
+        let mut late = queue.reader::<Text>();
+        queue.push(0, second, AssetEventKind::Modified);
+
+        assert_eq!(ids(&queue.read(&mut early)), vec![first, second]);
+        assert_eq!(ids(&queue.read(&mut late)), vec![second]);
+
+        // Nothing new to read
+        assert!(queue.read(&mut early).is_empty());
+        assert!(queue.read(&mut late).is_empty());
+    }
+
+    #[test]
+    fn events_kept_until_every_reader_has_read_them() {
+        let mut allocator = HandleAllocator::default();
+        let mut queue = EventQueue::default();
+        let mut every_tick = queue.reader::<Text>();
+        let mut skipping = queue.reader::<Text>();
+
+        let mut pushed = Vec::new();
+        for tick in 1..=20 {
+            let id = allocator.allocate();
+            queue.push(tick, id, AssetEventKind::Added);
+            pushed.push(id);
+
+            assert_eq!(ids(&queue.read(&mut every_tick)), vec![id]);
+            queue.prune(tick, None);
+        }
+
+        assert_eq!(ids(&queue.read(&mut skipping)), pushed);
+        assert_eq!(skipping.missed(), 0);
+
+        // Read by everyone, so nothing is left
+        queue.prune(21, None);
+        assert!(queue.events.is_empty());
+
+        // Dropped readers don't keep events around
+        drop(skipping);
+        queue.push(22, allocator.allocate(), AssetEventKind::Removed);
+        let _ = queue.read(&mut every_tick);
+        queue.prune(22, None);
+        assert!(queue.events.is_empty());
+    }
+
+    #[test]
+    fn max_age_discards_unread_events() {
+        let mut allocator = HandleAllocator::default();
+        let mut queue = EventQueue::default();
+        let mut reader = queue.reader::<Text>();
+
+        let old = allocator.allocate();
+        let new = allocator.allocate();
+        queue.push(1, old, AssetEventKind::Added);
+        queue.push(3, new, AssetEventKind::Added);
+        queue.prune(4, Some(2));
+
+        assert_eq!(ids(&queue.read(&mut reader)), vec![new]);
+        assert_eq!(reader.missed(), 1);
+    }
+}
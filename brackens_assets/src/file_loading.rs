@@ -2,13 +2,27 @@
 
 use anyhow::Result;
 
+use crate::vfs::Vfs;
+
 //===============================================================
 
-pub fn load_string(file_name: &str) -> Result<String> {
-    let path = std::path::Path::new("res/").join(file_name);
-    let txt = std::fs::read_to_string(path)?;
+/// Folder the file loading functions read from, relative to the root of the filesystem
+pub const RESOURCE_FOLDER: &str = "res/";
+
+fn resource_path(file_name: &str) -> String {
+    format!("{}{}", RESOURCE_FOLDER, file_name)
+}
+
+pub fn load_string(vfs: &Vfs, file_name: &str) -> Result<String> {
+    let txt = vfs.read_string(&resource_path(file_name))?;
 
     Ok(txt)
 }
 
+pub fn load_binary(vfs: &Vfs, file_name: &str) -> Result<Vec<u8>> {
+    let data = vfs.read(&resource_path(file_name))?;
+
+    Ok(data)
+}
+
 //===============================================================
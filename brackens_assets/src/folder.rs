@@ -0,0 +1,96 @@
+//===============================================================
+
+use std::sync::Arc;
+
+use crate::{Asset, AssetError, Handle, UntypedHandle};
+
+//===============================================================
+
+/// Which files a folder handle is made of
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FolderSource {
+    Directory { path: String, recursive: bool },
+    Glob(String),
+}
+
+//===============================================================
+
+/// Handle to every asset loaded from a directory or glob pattern. Keeps the assets loaded
+/// for as long as it exists. Use `AssetStorage::rescan` to load files added since.
+pub struct FolderHandle {
+    source: FolderSource,
+    handles: Vec<(String, UntypedHandle)>,
+    failed: Vec<(String, Arc<AssetError>)>,
+}
+
+impl FolderHandle {
+    //----------------------------------------------
+
+    pub(crate) fn new(source: FolderSource) -> Self {
+        Self {
+            source,
+            handles: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    pub(crate) fn contains(&self, path: &str) -> bool {
+        self.handles.iter().any(|(loaded, _)| loaded == path)
+    }
+
+    pub(crate) fn push(&mut self, path: String, handle: UntypedHandle) {
+        self.handles.push((path, handle));
+    }
+
+    pub(crate) fn set_failed(&mut self, failed: Vec<(String, Arc<AssetError>)>) {
+        self.failed = failed;
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn source(&self) -> &FolderSource {
+        &self.source
+    }
+
+    pub fn handles(&self) -> impl Iterator<Item = &UntypedHandle> {
+        self.handles.iter().map(|(_, handle)| handle)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.handles.iter().map(|(path, _)| path.as_str())
+    }
+
+    /// Handles of every asset of type `T` in the folder
+    pub fn typed<T: Asset>(&self) -> Vec<Handle<T>> {
+        self.handles()
+            .filter_map(|handle| handle.downcast())
+            .collect()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&UntypedHandle> {
+        self.handles
+            .iter()
+            .find(|(loaded, _)| loaded == path)
+            .map(|(_, handle)| handle)
+    }
+
+    /// Files that failed to load during the last scan
+    pub fn failed(&self) -> &[(String, Arc<AssetError>)] {
+        &self.failed
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
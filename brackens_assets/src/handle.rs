@@ -1,19 +1,27 @@
 //===============================================================
 
-use std::{hash::Hash, marker::PhantomData, sync::Arc};
+use std::{any::TypeId, hash::Hash, marker::PhantomData, sync::Weak};
 
 use crate::{asset_storage::ReferenceCountSignal, Asset, SenderType};
 
 //===============================================================
 
+/// Slot index of an asset along with the generation of that slot. The generation changes
+/// every time a slot is freed so ids kept after an asset is unloaded never alias the
+/// asset that reuses the slot.
 #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-pub struct HandleInner(u32);
+pub struct HandleInner {
+    index: u32,
+    generation: u32,
+}
 impl HandleInner {
-    pub(crate) fn from_id(id: u32) -> Self {
-        Self(id)
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
     }
-    pub(crate) fn next(&mut self) {
-        self.0 += 1;
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 }
 
@@ -30,6 +38,57 @@ impl<T: Asset> From<&HandleID<T>> for HandleInner {
 
 //===============================================================
 
+/// Hands out generational ids and reuses the slots of freed ones.
+#[derive(Default)]
+pub(crate) struct HandleAllocator {
+    // Stored wider than ids so slots that have run out of generations can be retired
+    // while still reporting old ids as stale.
+    generations: Vec<u64>,
+    free: Vec<u32>,
+}
+impl HandleAllocator {
+    pub fn allocate(&mut self) -> HandleInner {
+        match self.free.pop() {
+            Some(index) => HandleInner {
+                index,
+                generation: self.generations[index as usize] as u32,
+            },
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                HandleInner {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn free(&mut self, id: HandleInner) {
+        if self.is_stale(id) {
+            return;
+        }
+
+        let generation = &mut self.generations[id.index as usize];
+        *generation += 1;
+
+        // Retire slots that have run out of generations rather than wrapping around
+        if *generation <= u32::MAX as u64 {
+            self.free.push(id.index);
+        }
+    }
+
+    /// Check if an id refers to a slot that has since been freed
+    pub fn is_stale(&self, id: HandleInner) -> bool {
+        match self.generations.get(id.index as usize) {
+            Some(generation) => *generation != id.generation as u64,
+            None => false,
+        }
+    }
+}
+
+//===============================================================
+
 pub struct HandleID<T: Asset> {
     id: HandleInner,
     data: PhantomData<T>,
@@ -74,21 +133,21 @@ impl<T: Asset> Eq for HandleID<T> {}
 pub struct Handle<T: Asset> {
     handle_id: HandleID<T>,
     sender: SenderType<ReferenceCountSignal>,
-    asset: Arc<T>,
+    alive: Weak<()>,
 }
 
 impl<T: Asset> Handle<T> {
     pub(crate) fn new(
         id: HandleID<T>,
         sender: SenderType<ReferenceCountSignal>,
-        asset: Arc<T>,
+        alive: Weak<()>,
     ) -> Self {
         sender.send(ReferenceCountSignal::Increase(id.id)).unwrap();
 
         Self {
             handle_id: id,
             sender,
-            asset,
+            alive,
         }
     }
 
@@ -100,11 +159,25 @@ impl<T: Asset> Handle<T> {
     pub fn inner_id(&self) -> HandleInner {
         self.handle_id.id
     }
+
+    /// Create a handle that doesn't keep the asset loaded
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        WeakHandle::new(self.handle_id, self.sender.clone(), self.alive.clone())
+    }
 }
 
 impl<T: Asset> Clone for Handle<T> {
     fn clone(&self) -> Self {
-        Self::new(self.handle_id, self.sender.clone(), self.asset.clone())
+        Self::new(self.handle_id, self.sender.clone(), self.alive.clone())
+    }
+}
+
+impl<T: Asset> Drop for Handle<T> {
+    fn drop(&mut self) {
+        // Storage may have already been dropped
+        self.sender
+            .send(ReferenceCountSignal::Decrease(self.handle_id.id))
+            .ok();
     }
 }
 
@@ -115,3 +188,278 @@ impl<T: Asset> PartialEq for Handle<T> {
 }
 
 //===============================================================
+
+/// Handle that doesn't count towards an asset's reference count. Can be upgraded into a
+/// `Handle<T>` for as long as the asset is still loaded.
+pub struct WeakHandle<T: Asset> {
+    handle_id: HandleID<T>,
+    sender: SenderType<ReferenceCountSignal>,
+    alive: Weak<()>,
+}
+
+impl<T: Asset> WeakHandle<T> {
+    pub(crate) fn new(
+        id: HandleID<T>,
+        sender: SenderType<ReferenceCountSignal>,
+        alive: Weak<()>,
+    ) -> Self {
+        Self {
+            handle_id: id,
+            sender,
+            alive,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> HandleID<T> {
+        self.handle_id
+    }
+    #[inline]
+    pub fn inner_id(&self) -> HandleInner {
+        self.handle_id.id
+    }
+
+    /// Check if the asset is still loaded. The asset may still be unloaded before
+    /// `upgrade` is called.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.alive.strong_count() > 0
+    }
+
+    /// Get a strong handle to the asset, or `None` if the asset has been unloaded
+    pub fn upgrade(&self) -> Option<Handle<T>> {
+        // Hold onto the token so the asset is still tracked while the handle is created
+        let _alive = self.alive.upgrade()?;
+        Some(Handle::new(
+            self.handle_id,
+            self.sender.clone(),
+            self.alive.clone(),
+        ))
+    }
+}
+
+impl<T: Asset> Clone for WeakHandle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.handle_id, self.sender.clone(), self.alive.clone())
+    }
+}
+
+impl<T: Asset> PartialEq for WeakHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle_id.id == other.handle_id.id
+    }
+}
+
+impl<T: Asset> From<&Handle<T>> for WeakHandle<T> {
+    fn from(value: &Handle<T>) -> Self {
+        value.downgrade()
+    }
+}
+
+//===============================================================
+
+/// Handle to an asset whose type is only known at runtime. Can be downcast into a typed
+/// `Handle<T>` once the caller knows what type of asset was loaded.
+pub struct UntypedHandle {
+    id: HandleInner,
+    type_id: TypeId,
+    sender: SenderType<ReferenceCountSignal>,
+    alive: Weak<()>,
+}
+
+impl UntypedHandle {
+    pub(crate) fn new(
+        id: HandleInner,
+        type_id: TypeId,
+        sender: SenderType<ReferenceCountSignal>,
+        alive: Weak<()>,
+    ) -> Self {
+        sender.send(ReferenceCountSignal::Increase(id)).unwrap();
+
+        Self {
+            id,
+            type_id,
+            sender,
+            alive,
+        }
+    }
+
+    #[inline]
+    pub fn inner_id(&self) -> HandleInner {
+        self.id
+    }
+    #[inline]
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+    #[inline]
+    pub fn is<T: Asset>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Get a typed handle to the asset if it is of type `T`
+    pub fn downcast<T: Asset>(&self) -> Option<Handle<T>> {
+        match self.is::<T>() {
+            true => Some(Handle::new(
+                HandleID::new(self.id),
+                self.sender.clone(),
+                self.alive.clone(),
+            )),
+            false => None,
+        }
+    }
+}
+
+impl Clone for UntypedHandle {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.id,
+            self.type_id,
+            self.sender.clone(),
+            self.alive.clone(),
+        )
+    }
+}
+
+impl Drop for UntypedHandle {
+    fn drop(&mut self) {
+        self.sender
+            .send(ReferenceCountSignal::Decrease(self.id))
+            .ok();
+    }
+}
+
+impl PartialEq for UntypedHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T: Asset> From<Handle<T>> for UntypedHandle {
+    fn from(value: Handle<T>) -> Self {
+        Self::new(
+            value.inner_id(),
+            TypeId::of::<T>(),
+            value.sender.clone(),
+            value.alive.clone(),
+        )
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{asset_storage::AssetStorageError, AssetStorageSingle};
+
+    use super::*;
+
+    struct Text(&'static str);
+
+    impl Asset for Text {
+        fn asset_name(&self) -> &str {
+            "Text"
+        }
+    }
+
+    #[test]
+    fn weak_handle_fails_to_upgrade_once_unloaded() {
+        let mut storage = AssetStorageSingle::new();
+        let handle = storage.add_asset(Text("text"));
+        let weak = handle.downgrade();
+        let id = handle.id();
+
+        assert!(weak.is_alive());
+
+        drop(handle);
+        storage.tick();
+
+        assert!(storage.get_data(id).is_err());
+        assert!(!weak.is_alive());
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn upgraded_handle_keeps_asset_loaded() {
+        let mut storage = AssetStorageSingle::new();
+        let handle = storage.add_asset(Text("text"));
+        let weak = handle.downgrade();
+
+        let upgraded = weak.upgrade().unwrap();
+        drop(handle);
+        storage.tick();
+
+        assert_eq!(storage.get_data(upgraded.id()).unwrap().0, "text");
+        assert!(weak.is_alive());
+
+        drop(upgraded);
+        storage.tick();
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn stale_id_reported_after_slot_reused() {
+        let mut storage = AssetStorageSingle::new();
+        let first = storage.add_asset(Text("first"));
+        let old_id = first.id();
+
+        drop(first);
+        storage.tick();
+
+        let second = storage.add_asset(Text("second"));
+        assert_eq!(second.inner_id().index(), old_id.id().index());
+        assert_ne!(second.inner_id().generation(), old_id.id().generation());
+
+        assert!(storage.is_stale(old_id));
+        assert!(matches!(
+            storage.get_data(old_id),
+            Err(AssetStorageError::Stale)
+        ));
+        assert_eq!(storage.get_data(second.id()).unwrap().0, "second");
+    }
+
+    #[test]
+    fn allocator_reuses_freed_slots() {
+        let mut allocator = HandleAllocator::default();
+        let first = allocator.allocate();
+        let second = allocator.allocate();
+
+        allocator.free(first);
+        // Freeing twice doesn't hand the slot out twice
+        allocator.free(first);
+
+        let reused = allocator.allocate();
+        assert_eq!(reused.index(), first.index());
+        assert_eq!(reused.generation(), first.generation() + 1);
+        assert!(allocator.is_stale(first));
+        assert!(!allocator.is_stale(second));
+        assert!(!allocator.is_stale(reused));
+
+        assert_eq!(allocator.allocate().index(), 2);
+    }
+
+    #[test]
+    fn allocator_retires_exhausted_slots() {
+        let mut allocator = HandleAllocator::default();
+        let first = allocator.allocate();
+
+        // Skip ahead to the last generation of the slot
+        allocator.generations[first.index() as usize] = u32::MAX as u64;
+        let last = HandleInner {
+            index: first.index(),
+            generation: u32::MAX,
+        };
+        assert!(!allocator.is_stale(last));
+
+        allocator.free(last);
+        assert!(allocator.is_stale(last));
+        assert!(allocator.is_stale(first));
+
+        // The retired slot is never handed out again
+        for _ in 0..4 {
+            let id = allocator.allocate();
+            assert_ne!(id.index(), first.index());
+            allocator.free(id);
+        }
+    }
+}
@@ -1,18 +1,40 @@
 //===============================================================
 
 use downcast_rs::DowncastSync;
+use handle::HandleInner;
 
 pub mod asset_storage;
 pub mod asset_storage_single;
+pub mod bitmap_font;
+pub mod data_asset;
 pub mod default_implementations;
+pub mod error;
+pub mod events;
 pub mod file_loading;
+pub mod folder;
 pub mod handle;
+pub mod loader;
+pub mod meta;
+pub mod pack;
+pub mod retention;
+pub mod vfs;
+mod worker_pool;
 
 //===============================================================
 
-pub use asset_storage::AssetStorage;
+pub use asset_storage::{AssetStorage, LoadState};
 pub use asset_storage_single::AssetStorageSingle;
-pub use handle::{Handle, HandleID};
+pub use bitmap_font::{BitmapFont, BitmapGlyph};
+pub use data_asset::DataAsset;
+pub use error::{AssetError, FallbackPolicy};
+pub use events::{AssetEvent, AssetEventReader};
+pub use folder::FolderHandle;
+pub use handle::{Handle, HandleID, UntypedHandle, WeakHandle};
+pub use loader::{AssetLoader, FileLoader, LoadContext, LoaderRegistry};
+pub use meta::AssetMeta;
+pub use pack::{PackCompression, PackSource, PackWriter};
+pub use retention::{AssetStats, RetentionPolicy, RetentionTime};
+pub use vfs::{ArchiveSource, AssetSource, DirectorySource, EmbeddedSource, Vfs};
 
 //===============================================================
 
@@ -23,11 +45,35 @@ pub(crate) type ReceiverType<T> = crossbeam::channel::Receiver<T>;
 
 pub trait Asset: Send + Sync + DowncastSync {
     fn asset_name(&self) -> &str;
+
+    /// Approximate amount of memory used by the asset. Used for retention budgets and
+    /// storage statistics. Defaults to the size of the type itself.
+    fn byte_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /// Ids of other assets in the same storage this asset uses. Recorded as dependencies
+    /// whenever the asset is added, so they stay loaded and their reloads reach this asset.
+    fn dependencies(&self) -> Vec<HandleInner> {
+        Vec::new()
+    }
 }
 
 pub trait AssetFileLoadable: Asset {
-    fn load_from_file(path: &str) -> Self;
-    fn load_default() -> Self;
+    /// Create the asset from the contents of a file. The path is the one the file was
+    /// read from and is only used for format detection and error messages.
+    fn load_from_bytes(path: &str, bytes: &[u8]) -> Result<Self, AssetError>
+    where
+        Self: Sized;
+
+    /// Placeholder used in place of an asset that failed to load. Only used when the
+    /// storage's fallback policy is set to `FallbackPolicy::Placeholder`.
+    fn load_default() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 //===============================================================
@@ -0,0 +1,223 @@
+//===============================================================
+
+use std::{collections::HashMap, marker::PhantomData, path::Path, sync::Arc};
+
+use log::warn;
+
+use serde::de::DeserializeOwned;
+
+use crate::{meta::AssetMeta, Asset, AssetError, AssetFileLoadable};
+
+//===============================================================
+
+/// Sub-assets added while loading a file, alongside their labels
+pub(crate) type LabelledAssets = Vec<(String, Arc<dyn Asset>)>;
+
+/// Split a path into the file path and the label of a sub-asset inside that file,
+/// e.g. `"level.obj#mesh/Cube"` into `("level.obj", Some("mesh/Cube"))`
+pub fn split_label(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('#') {
+        Some((file_path, label)) => (file_path, Some(label)),
+        None => (path, None),
+    }
+}
+
+pub fn labelled_path(path: &str, label: &str) -> String {
+    format!("{}#{}", path, label)
+}
+
+//===============================================================
+
+/// Loader used to create an asset from a file without the caller needing to know the
+/// asset type. Loaders are registered against the file extensions they can handle and
+/// are given the contents of the file read through the storage's virtual filesystem.
+pub trait AssetLoader: Send + Sync + 'static {
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, context: &mut LoadContext, bytes: &[u8]) -> Result<Arc<dyn Asset>, AssetError>;
+
+    /// Placeholder used if loading fails and the storage's fallback policy allows it.
+    fn load_default(&self) -> Option<Arc<dyn Asset>> {
+        None
+    }
+}
+
+//===============================================================
+
+/// Information about the file currently being loaded. Loaders use it to read the import
+/// settings of the file, to declare other files the asset depends on, which are loaded
+/// alongside it and kept loaded for as long as the asset is, and to add labelled
+/// sub-assets that can be requested individually.
+pub struct LoadContext {
+    path: String,
+    meta: Option<AssetMeta>,
+    dependencies: Vec<String>,
+    labelled: LabelledAssets,
+}
+
+impl LoadContext {
+    pub(crate) fn new(path: &str, meta: Option<AssetMeta>) -> Self {
+        Self {
+            path: path.into(),
+            meta,
+            dependencies: Vec::new(),
+            labelled: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    #[inline]
+    pub fn meta(&self) -> Option<&AssetMeta> {
+        self.meta.as_ref()
+    }
+
+    /// Import settings of the file, or the default settings if it has none
+    pub fn settings<S: DeserializeOwned + Default>(&self) -> Result<S, AssetError> {
+        match &self.meta {
+            Some(meta) => meta.deserialize(&self.path),
+            None => Ok(S::default()),
+        }
+    }
+
+    pub fn add_dependency(&mut self, path: &str) {
+        if !self
+            .dependencies
+            .iter()
+            .any(|dependency| dependency == path)
+        {
+            self.dependencies.push(path.into());
+        }
+    }
+
+    #[inline]
+    pub fn dependencies(&self) -> &[String] {
+        &self.dependencies
+    }
+
+    /// Add a sub-asset that can be requested on its own using `"path#label"`. Sub-assets
+    /// are kept loaded for as long as the asset loaded from the file is, and the file is kept
+    /// loaded for as long as any of its sub-assets are used.
+    pub fn add_labelled_asset<T: Asset>(&mut self, label: &str, asset: T) {
+        let asset = Arc::new(asset) as Arc<dyn Asset>;
+
+        match self
+            .labelled
+            .iter_mut()
+            .find(|(existing, _)| existing == label)
+        {
+            Some((_, existing)) => {
+                warn!(
+                    "Warning: Replacing sub-asset '{}' of '{}' added twice",
+                    label, self.path
+                );
+                *existing = asset;
+            }
+            None => self.labelled.push((label.into(), asset)),
+        }
+    }
+
+    pub fn has_labelled_asset(&self, label: &str) -> bool {
+        self.labelled.iter().any(|(existing, _)| existing == label)
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<String>, LabelledAssets) {
+        (self.dependencies, self.labelled)
+    }
+}
+
+//===============================================================
+
+/// Loader for any type implementing `AssetFileLoadable`.
+pub struct FileLoader<T: AssetFileLoadable> {
+    extensions: Vec<&'static str>,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T: AssetFileLoadable> FileLoader<T> {
+    pub fn new(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T: AssetFileLoadable> AssetLoader for FileLoader<T> {
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+
+    fn load(&self, context: &mut LoadContext, bytes: &[u8]) -> Result<Arc<dyn Asset>, AssetError> {
+        Ok(Arc::new(T::load_from_bytes(context.path(), bytes)?))
+    }
+
+    fn load_default(&self) -> Option<Arc<dyn Asset>> {
+        T::load_default().map(|asset| Arc::new(asset) as Arc<dyn Asset>)
+    }
+}
+
+//===============================================================
+
+#[derive(Default)]
+pub struct LoaderRegistry {
+    loaders: Vec<Arc<dyn AssetLoader>>,
+    // Lower case extension to index of loader in loaders
+    extensions: HashMap<String, usize>,
+}
+
+impl LoaderRegistry {
+    //----------------------------------------------
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //----------------------------------------------
+
+    /// Register a loader for all of its extensions. Later registrations replace earlier
+    /// ones for the same extension.
+    pub fn register<L: AssetLoader>(&mut self, loader: L) {
+        let index = self.loaders.len();
+
+        for extension in loader.extensions() {
+            let extension = extension.to_lowercase();
+            if self.extensions.insert(extension.clone(), index).is_some() {
+                warn!(
+                    "Warning: Replacing previously registered asset loader for extension '{}'",
+                    extension
+                );
+            }
+        }
+
+        self.loaders.push(Arc::new(loader));
+    }
+
+    pub fn has_loader(&self, extension: &str) -> bool {
+        self.extensions.contains_key(&extension.to_lowercase())
+    }
+
+    pub fn get_loader(&self, extension: &str) -> Option<&Arc<dyn AssetLoader>> {
+        self.extensions
+            .get(&extension.to_lowercase())
+            .map(|index| &self.loaders[*index])
+    }
+
+    /// Find the loader matching the extension of the given path
+    pub fn get_path_loader(&self, path: &str) -> Result<&Arc<dyn AssetLoader>, AssetError> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+
+        self.get_loader(extension)
+            .ok_or_else(|| AssetError::unsupported_format(path, extension))
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
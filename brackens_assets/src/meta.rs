@@ -0,0 +1,172 @@
+//===============================================================
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::AssetError;
+
+//===============================================================
+
+/// Path of the sidecar file holding the import settings of an asset
+pub fn meta_path(path: &str) -> String {
+    format!("{}.meta", path)
+}
+
+//===============================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MetaFormat {
+    Ron,
+    Toml,
+}
+
+/// Import settings of an asset, read from a `.meta` sidecar file next to the asset or
+/// provided in code. Loaders deserialize them into their own settings type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssetMeta {
+    format: MetaFormat,
+    source: String,
+}
+
+impl AssetMeta {
+    //----------------------------------------------
+
+    pub fn new(format: MetaFormat, source: String) -> Self {
+        Self { format, source }
+    }
+
+    /// Detect the format of a `.meta` file. Files are read as RON if they parse as RON and
+    /// as TOML otherwise. If neither parses, files starting with `(` or a RON attribute are
+    /// treated as RON so the error is reported in the format they were most likely written in.
+    pub fn parse(source: String) -> Self {
+        let format = match (
+            ron::from_str::<ron::Value>(&source).is_ok(),
+            toml::from_str::<toml::Table>(&source).is_ok(),
+        ) {
+            (true, _) => MetaFormat::Ron,
+            (false, true) => MetaFormat::Toml,
+            (false, false) => {
+                let trimmed = source.trim_start();
+                match trimmed.starts_with('(') || trimmed.starts_with("#![") {
+                    true => MetaFormat::Ron,
+                    false => MetaFormat::Toml,
+                }
+            }
+        };
+
+        Self::new(format, source)
+    }
+
+    pub fn from_settings<S: Serialize>(settings: &S) -> Result<Self, ron::Error> {
+        Ok(Self::new(MetaFormat::Ron, ron::to_string(settings)?))
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn format(&self) -> MetaFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Deserialize the settings. The path is the asset the settings belong to and is only
+    /// used for error messages.
+    pub fn deserialize<S: DeserializeOwned>(&self, path: &str) -> Result<S, AssetError> {
+        let meta_path = meta_path(path);
+
+        match self.format {
+            MetaFormat::Ron => {
+                ron::from_str(&self.source).map_err(|e| AssetError::decode(&meta_path, e))
+            }
+            MetaFormat::Toml => {
+                toml::from_str(&self.source).map_err(|e| AssetError::decode(&meta_path, e))
+            }
+        }
+    }
+
+    /// Key used to tell apart the same file loaded with different settings
+    pub fn cache_key(&self, path: &str) -> String {
+        let hash = blake3::hash(self.source.as_bytes());
+        format!("{}?{}", path, &hash.to_hex()[..16])
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(default)]
+    struct Settings {
+        filter: String,
+        mips: bool,
+        size: Option<[u32; 2]>,
+    }
+
+    fn expected() -> Settings {
+        Settings {
+            filter: "nearest".into(),
+            mips: true,
+            size: Some([16, 8]),
+        }
+    }
+
+    #[test]
+    fn ron_meta_parsed() {
+        let sources = [
+            r#"(filter: "nearest", mips: true, size: Some((16, 8)))"#,
+            r#"#![enable(implicit_some)]
+            (filter: "nearest", mips: true, size: (16, 8))"#,
+            // Named structs don't start with a parenthesis
+            r#"Settings(filter: "nearest", mips: true, size: Some((16, 8)))"#,
+            "  // Comment\n(filter: \"nearest\", mips: true, size: Some((16, 8)))",
+        ];
+
+        for source in sources {
+            let meta = AssetMeta::parse(source.into());
+            assert_eq!(meta.format(), MetaFormat::Ron, "{}", source);
+            assert_eq!(meta.deserialize::<Settings>("a.png").unwrap(), expected());
+        }
+    }
+
+    #[test]
+    fn toml_meta_parsed() {
+        let sources = [
+            "filter = \"nearest\"\nmips = true\nsize = [16, 8]\n",
+            "# Comment\n\nfilter = 'nearest'\nmips = true\nsize = [16, 8]",
+        ];
+
+        for source in sources {
+            let meta = AssetMeta::parse(source.into());
+            assert_eq!(meta.format(), MetaFormat::Toml, "{}", source);
+            assert_eq!(meta.deserialize::<Settings>("a.png").unwrap(), expected());
+        }
+
+        // An empty file is valid TOML holding the default settings
+        let meta = AssetMeta::parse(String::new());
+        assert_eq!(
+            meta.deserialize::<Settings>("a.png").unwrap(),
+            Settings::default()
+        );
+    }
+
+    #[test]
+    fn invalid_meta_reports_error() {
+        let meta = AssetMeta::parse("(filter: ".into());
+        assert_eq!(meta.format(), MetaFormat::Ron);
+        assert!(meta.deserialize::<Settings>("a.png").is_err());
+
+        let meta = AssetMeta::parse("filter = ".into());
+        assert_eq!(meta.format(), MetaFormat::Toml);
+        assert!(meta.deserialize::<Settings>("a.png").is_err());
+    }
+}
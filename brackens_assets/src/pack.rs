@@ -0,0 +1,683 @@
+//===============================================================
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
+use glob::Pattern;
+use log::info;
+use parking_lot::Mutex;
+
+use crate::{
+    vfs::{filter_listing, normalize_path, AssetSource, ReadSeek},
+    AssetError,
+};
+
+//===============================================================
+
+// Pack layout:
+//  header - magic, version, entry count
+//  index  - path, compression, offset, stored size, size and hash of every entry
+//  data   - entry data, offsets are relative to the start of this section
+const PACK_MAGIC: &[u8; 4] = b"BRPK";
+const PACK_VERSION: u32 = 1;
+
+// Smallest possible index entry, one with an empty path
+const MIN_ENTRY_SIZE: u64 = 2 + 1 + 8 + 8 + 8 + 32;
+// Deflate can't shrink data by more than this, used to catch impossible sizes
+const MAX_DEFLATE_RATIO: u64 = 1032;
+
+//===============================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PackCompression {
+    None,
+    #[default]
+    Deflate,
+}
+
+impl PackCompression {
+    fn to_byte(self) -> u8 {
+        match self {
+            PackCompression::None => 0,
+            PackCompression::Deflate => 1,
+        }
+    }
+
+    fn from_byte(value: u8) -> std::io::Result<Self> {
+        match value {
+            0 => Ok(PackCompression::None),
+            1 => Ok(PackCompression::Deflate),
+            _ => Err(invalid_data(format!(
+                "unknown pack compression type {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackEntry {
+    pub compression: PackCompression,
+    offset: u64,
+    pub stored_size: u64,
+    pub size: u64,
+    /// Blake3 hash of the uncompressed data
+    pub hash: [u8; 32],
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+}
+
+//===============================================================
+
+/// Builds a pack file in memory before writing it out.
+#[derive(Default)]
+pub struct PackWriter {
+    entries: Vec<(String, PackEntry, Vec<u8>)>,
+    data_size: u64,
+}
+
+impl PackWriter {
+    //----------------------------------------------
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    //----------------------------------------------
+
+    /// Add a file to the pack. Data is only stored compressed if compressing makes it
+    /// smaller, so already compressed formats such as png are stored as is.
+    pub fn add_file(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        compression: PackCompression,
+    ) -> std::io::Result<()> {
+        let path = normalize_path(path);
+
+        if self
+            .entries
+            .iter()
+            .any(|(entry_path, ..)| *entry_path == path)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("pack already contains file '{}'", path),
+            ));
+        }
+
+        let (compression, stored) = match compression {
+            PackCompression::None => (PackCompression::None, data.to_vec()),
+            PackCompression::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(data)?;
+                let compressed = encoder.finish()?;
+
+                match compressed.len() < data.len() {
+                    true => (PackCompression::Deflate, compressed),
+                    false => (PackCompression::None, data.to_vec()),
+                }
+            }
+        };
+
+        let entry = PackEntry {
+            compression,
+            offset: self.data_size,
+            stored_size: stored.len() as u64,
+            size: data.len() as u64,
+            hash: *blake3::hash(data).as_bytes(),
+        };
+
+        self.data_size += entry.stored_size;
+        self.entries.push((path, entry, stored));
+
+        Ok(())
+    }
+
+    //----------------------------------------------
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(PACK_MAGIC)?;
+        writer.write_all(&PACK_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        for (path, entry, _) in &self.entries {
+            let path_len: u16 = path
+                .len()
+                .try_into()
+                .map_err(|_| invalid_data(format!("pack path '{}' is too long", path)))?;
+
+            writer.write_all(&path_len.to_le_bytes())?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_all(&[entry.compression.to_byte()])?;
+            writer.write_all(&entry.offset.to_le_bytes())?;
+            writer.write_all(&entry.stored_size.to_le_bytes())?;
+            writer.write_all(&entry.size.to_le_bytes())?;
+            writer.write_all(&entry.hash)?;
+        }
+
+        for (_, _, data) in &self.entries {
+            writer.write_all(data)?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Filter used to pick which files from a directory end up in a pack. Patterns are
+/// matched against paths relative to the directory being packed.
+#[derive(Default)]
+pub struct PackFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PackFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include(mut self, pattern: &str) -> Result<Self, glob::PatternError> {
+        self.include.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, glob::PatternError> {
+        self.exclude.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Check a path against the filter. Everything is included if no include patterns
+    /// are given. Exclude patterns take priority over include patterns.
+    pub fn matches(&self, path: &str) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(path));
+
+        included && !self.exclude.iter().any(|pattern| pattern.matches(path))
+    }
+}
+
+/// Add every file in a directory and its subdirectories that matches the filter
+pub fn pack_directory<P: AsRef<Path>>(
+    directory: P,
+    filter: &PackFilter,
+    compression: PackCompression,
+) -> std::io::Result<PackWriter> {
+    let directory = directory.as_ref();
+
+    let mut files = Vec::new();
+    collect_files(directory, directory, &mut files)?;
+    // Keep pack output the same between runs
+    files.sort();
+
+    let mut writer = PackWriter::new();
+
+    for path in files.into_iter().filter(|path| filter.matches(path)) {
+        let data = std::fs::read(directory.join(&path))?;
+        writer.add_file(&path, &data, compression)?;
+    }
+
+    info!(
+        "Packed {} files from directory {:?}",
+        writer.len(),
+        directory
+    );
+
+    Ok(writer)
+}
+
+fn collect_files(root: &Path, directory: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(normalize_path(&relative.to_string_lossy()));
+        }
+    }
+
+    Ok(())
+}
+
+//===============================================================
+
+/// Source reading files from a pack created by `PackWriter`. Data is checked against
+/// its stored hash when read.
+pub struct PackSource {
+    entries: HashMap<String, PackEntry>,
+    data_start: u64,
+    reader: Mutex<Box<dyn ReadSeek>>,
+}
+
+impl PackSource {
+    //----------------------------------------------
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        let path_str = path.as_ref().to_string_lossy();
+
+        let file = File::open(path.as_ref()).map_err(|e| AssetError::io(&path_str, e))?;
+        Self::from_reader(BufReader::new(file)).map_err(|e| AssetError::decode(&path_str, e))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> std::io::Result<Self> {
+        Self::from_reader(Cursor::new(bytes))
+    }
+
+    fn from_reader<R: Read + Seek + Send + 'static>(mut reader: R) -> std::io::Result<Self> {
+        // Sizes in the header are checked against the file before anything is allocated
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PACK_MAGIC {
+            return Err(invalid_data("file is not an asset pack"));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != PACK_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported pack version {}",
+                version
+            )));
+        }
+
+        let entry_count = read_u32(&mut reader)?;
+        let remaining = file_size - reader.stream_position()?;
+        if entry_count as u64 * MIN_ENTRY_SIZE > remaining {
+            return Err(invalid_data(format!(
+                "pack entry count {} is larger than the file",
+                entry_count
+            )));
+        }
+
+        let mut entries = HashMap::with_capacity(entry_count as usize);
+
+        for _ in 0..entry_count {
+            let path_len = read_u16(&mut reader)? as u64;
+            if path_len > file_size - reader.stream_position()? {
+                return Err(invalid_data("pack entry path is longer than the file"));
+            }
+
+            let mut path = vec![0; path_len as usize];
+            reader.read_exact(&mut path)?;
+            let path = String::from_utf8(path).map_err(invalid_data)?;
+
+            let mut compression = [0; 1];
+            reader.read_exact(&mut compression)?;
+
+            let offset = read_u64(&mut reader)?;
+            let stored_size = read_u64(&mut reader)?;
+            let size = read_u64(&mut reader)?;
+
+            let mut hash = [0; 32];
+            reader.read_exact(&mut hash)?;
+
+            let entry = PackEntry {
+                compression: PackCompression::from_byte(compression[0])?,
+                offset,
+                stored_size,
+                size,
+                hash,
+            };
+
+            entries.insert(path, entry);
+        }
+
+        let data_start = reader.stream_position()?;
+        let data_size = file_size - data_start;
+
+        for (path, entry) in &entries {
+            let in_bounds = entry
+                .offset
+                .checked_add(entry.stored_size)
+                .is_some_and(|end| end <= data_size);
+
+            let valid_size = match entry.compression {
+                PackCompression::None => entry.size == entry.stored_size,
+                PackCompression::Deflate => {
+                    entry.size <= entry.stored_size.saturating_mul(MAX_DEFLATE_RATIO)
+                }
+            };
+
+            if !in_bounds || !valid_size {
+                return Err(invalid_data(format!(
+                    "pack entry '{}' has sizes that don't fit the file",
+                    path
+                )));
+            }
+        }
+
+        Ok(Self {
+            entries,
+            data_start,
+            reader: Mutex::new(Box::new(reader)),
+        })
+    }
+
+    //----------------------------------------------
+
+    pub fn entry(&self, path: &str) -> Option<&PackEntry> {
+        self.entries.get(path)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    //----------------------------------------------
+
+    fn read_entry(&self, path: &str, entry: &PackEntry) -> std::io::Result<Vec<u8>> {
+        let mut stored = vec![0; entry.stored_size as usize];
+        {
+            let mut reader = self.reader.lock();
+            reader.seek(SeekFrom::Start(self.data_start + entry.offset))?;
+            reader.read_exact(&mut stored)?;
+        }
+
+        let data = match entry.compression {
+            PackCompression::None => stored,
+            PackCompression::Deflate => {
+                let mut data = Vec::with_capacity(entry.size as usize);
+                // Never decompress more than the entry claims to hold
+                DeflateDecoder::new(stored.as_slice())
+                    .take(entry.size)
+                    .read_to_end(&mut data)?;
+                data
+            }
+        };
+
+        if blake3::hash(&data).as_bytes() != &entry.hash {
+            return Err(invalid_data(format!(
+                "pack entry '{}' does not match its hash",
+                path
+            )));
+        }
+
+        Ok(data)
+    }
+
+    //----------------------------------------------
+}
+
+impl AssetSource for PackSource {
+    fn read(&self, path: &str) -> Option<std::io::Result<Vec<u8>>> {
+        self.entries
+            .get(path)
+            .map(|entry| self.read_entry(path, entry))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn list(&self, directory: &str, recursive: bool) -> Vec<String> {
+        filter_listing(
+            self.entries.keys().map(String::as_str),
+            directory,
+            recursive,
+        )
+    }
+}
+
+//===============================================================
+
+fn read_u16<R: Read>(reader: &mut R) -> std::io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::{DirectorySource, Vfs};
+
+    fn packed_bytes(writer: &PackWriter) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        writer.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("brackens_pack_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trip_entries() {
+        let text = "hello pack ".repeat(100);
+        let random: Vec<u8> = (0..512u32).map(|i| (i * 7919 % 251) as u8).collect();
+
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("text.txt", text.as_bytes(), PackCompression::Deflate)
+            .unwrap();
+        writer
+            .add_file("nested/data.bin", &random, PackCompression::None)
+            .unwrap();
+        writer
+            .add_file("empty", &[], PackCompression::Deflate)
+            .unwrap();
+
+        let pack = PackSource::from_bytes(packed_bytes(&writer)).unwrap();
+
+        assert_eq!(pack.len(), 3);
+        assert_eq!(pack.read("text.txt").unwrap().unwrap(), text.as_bytes());
+        assert_eq!(pack.read("nested/data.bin").unwrap().unwrap(), random);
+        assert_eq!(pack.read("empty").unwrap().unwrap(), Vec::<u8>::new());
+        assert!(pack.read("missing").is_none());
+
+        let text_entry = pack.entry("text.txt").unwrap();
+        assert_eq!(text_entry.compression, PackCompression::Deflate);
+        assert!(text_entry.stored_size < text_entry.size);
+        assert_eq!(text_entry.hash, *blake3::hash(text.as_bytes()).as_bytes());
+
+        assert_eq!(
+            pack.entry("nested/data.bin").unwrap().compression,
+            PackCompression::None
+        );
+    }
+
+    #[test]
+    fn incompressible_data_stored_uncompressed() {
+        let data = [1, 2, 3];
+
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("small", &data, PackCompression::Deflate)
+            .unwrap();
+
+        let pack = PackSource::from_bytes(packed_bytes(&writer)).unwrap();
+        assert_eq!(
+            pack.entry("small").unwrap().compression,
+            PackCompression::None
+        );
+        assert_eq!(pack.read("small").unwrap().unwrap(), data);
+    }
+
+    #[test]
+    fn duplicate_paths_rejected() {
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("a/b.txt", b"one", PackCompression::None)
+            .unwrap();
+
+        assert!(writer
+            .add_file("./a//b.txt", b"two", PackCompression::None)
+            .is_err());
+    }
+
+    #[test]
+    fn corrupted_data_detected() {
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("data", b"some important data", PackCompression::None)
+            .unwrap();
+
+        let mut bytes = packed_bytes(&writer);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        let pack = PackSource::from_bytes(bytes).unwrap();
+        let error = pack.read("data").unwrap().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn invalid_pack_rejected() {
+        assert!(PackSource::from_bytes(b"not a pack".to_vec()).is_err());
+        assert!(PackSource::from_bytes(Vec::new()).is_err());
+    }
+
+    fn assert_invalid_data(bytes: Vec<u8>) {
+        match PackSource::from_bytes(bytes) {
+            Ok(_) => panic!("pack with invalid header was accepted"),
+            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+        }
+    }
+
+    #[test]
+    fn oversized_header_values_rejected() {
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("data", b"some data", PackCompression::None)
+            .unwrap();
+        let bytes = packed_bytes(&writer);
+
+        // Header is magic, version and entry count. The offset of the only entry follows
+        // its path length, path and compression.
+        let offset = 12 + 2 + "data".len() + 1;
+        let stored_size = offset + 8;
+        let size = stored_size + 8;
+
+        let mut entry_count = bytes.clone();
+        entry_count[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_invalid_data(entry_count);
+
+        let mut path_len = bytes.clone();
+        path_len[12..14].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_invalid_data(path_len);
+
+        let mut offset_bytes = bytes.clone();
+        offset_bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_invalid_data(offset_bytes);
+
+        let mut stored_bytes = bytes.clone();
+        stored_bytes[stored_size..stored_size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_invalid_data(stored_bytes);
+
+        let mut size_bytes = bytes;
+        size_bytes[size..size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_invalid_data(size_bytes);
+    }
+
+    #[test]
+    fn pack_directory_with_filter() {
+        let dir = temp_dir("filter");
+        std::fs::create_dir_all(dir.join("textures/raw")).unwrap();
+        std::fs::write(dir.join("textures/player.png"), b"player").unwrap();
+        std::fs::write(dir.join("textures/raw/player.psd"), b"source").unwrap();
+        std::fs::write(dir.join("textures/raw/enemy.png"), b"enemy").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"readme").unwrap();
+
+        let filter = PackFilter::new()
+            .include("**/*.png")
+            .unwrap()
+            .exclude("textures/raw/*")
+            .unwrap();
+
+        let writer = pack_directory(&dir, &filter, PackCompression::Deflate).unwrap();
+        let pack = PackSource::from_bytes(packed_bytes(&writer)).unwrap();
+
+        let mut paths = pack.paths().cloned().collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["textures/player.png".to_string()]);
+        assert_eq!(
+            pack.read("textures/player.png").unwrap().unwrap(),
+            b"player"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn pack_overrides_directory_in_vfs() {
+        let dir = temp_dir("vfs");
+        std::fs::write(dir.join("config.txt"), b"original").unwrap();
+        std::fs::write(dir.join("other.txt"), b"other").unwrap();
+
+        let mut writer = PackWriter::new();
+        writer
+            .add_file("config.txt", b"patched", PackCompression::Deflate)
+            .unwrap();
+
+        let pack_path = dir.join("patch.pack");
+        writer.write_to_file(&pack_path).unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount("res", DirectorySource::new(&dir));
+        vfs.mount("res", PackSource::open(&pack_path).unwrap());
+
+        assert_eq!(vfs.read("res/config.txt").unwrap(), b"patched");
+        assert_eq!(vfs.read("res/other.txt").unwrap(), b"other");
+        assert!(vfs.read("res/missing.txt").is_err());
+
+        assert!(vfs.unmount("res"));
+        assert_eq!(vfs.read("res/config.txt").unwrap(), b"original");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
+
+//===============================================================
@@ -0,0 +1,111 @@
+//===============================================================
+
+use std::{any::TypeId, time::Instant};
+
+use crate::handle::HandleInner;
+
+//===============================================================
+
+/// How long assets no longer referenced by any handle are kept loaded for
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RetentionTime {
+    /// Unload as soon as the last handle is dropped
+    #[default]
+    Immediate,
+    Seconds(f32),
+    /// Number of calls to `tick`
+    Frames(u64),
+    /// Only unload when needed to stay within the byte budget
+    Forever,
+}
+
+/// Policy deciding when unused assets are unloaded. Keeping unused assets around for a
+/// while avoids reloading them from disk when they're released and requested again
+/// shortly after, such as between scenes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RetentionPolicy {
+    pub time: RetentionTime,
+    /// While the total size of all loaded assets is above the budget, unused assets are
+    /// unloaded starting with the least recently used.
+    pub byte_budget: Option<usize>,
+    /// Discard events older than this many ticks even if a reader hasn't read them yet.
+    /// Without a limit events are kept until every reader has read them.
+    pub event_ticks: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn new(time: RetentionTime) -> Self {
+        Self {
+            time,
+            byte_budget: None,
+            event_ticks: None,
+        }
+    }
+
+    pub fn with_byte_budget(mut self, bytes: usize) -> Self {
+        self.byte_budget = Some(bytes);
+        self
+    }
+
+    pub fn with_event_ticks(mut self, ticks: u64) -> Self {
+        self.event_ticks = Some(ticks);
+        self
+    }
+}
+
+//===============================================================
+
+/// When an asset became unused
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UnusedAsset {
+    pub tick: u64,
+    pub instant: Instant,
+}
+
+impl UnusedAsset {
+    pub fn new(tick: u64) -> Self {
+        Self {
+            tick,
+            instant: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self, time: RetentionTime, tick: u64) -> bool {
+        match time {
+            RetentionTime::Immediate => true,
+            RetentionTime::Seconds(seconds) => self.instant.elapsed().as_secs_f32() >= seconds,
+            RetentionTime::Frames(frames) => tick - self.tick >= frames,
+            RetentionTime::Forever => false,
+        }
+    }
+}
+
+//===============================================================
+
+/// Information about an asset currently held by a storage
+#[derive(Clone, Debug)]
+pub struct ResidentAsset {
+    pub id: HandleInner,
+    pub type_id: TypeId,
+    pub asset_name: String,
+    pub path: Option<String>,
+    pub byte_size: usize,
+    pub handle_count: u32,
+    /// Asset has no handles and is only kept loaded by the retention policy
+    pub unused: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AssetStats {
+    pub assets: Vec<ResidentAsset>,
+    pub total_bytes: usize,
+    pub unused_bytes: usize,
+}
+
+impl AssetStats {
+    pub fn unused_count(&self) -> usize {
+        self.assets.iter().filter(|asset| asset.unused).count()
+    }
+}
+
+//===============================================================
@@ -0,0 +1,531 @@
+//===============================================================
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use glob::{MatchOptions, Pattern, PatternError};
+use parking_lot::Mutex;
+use zip::{result::ZipError, ZipArchive};
+
+use crate::AssetError;
+
+//===============================================================
+
+/// Somewhere files can be read from. Paths given to a source are relative to the point
+/// the source is mounted at and always use `/` as a separator.
+pub trait AssetSource: Send + Sync + 'static {
+    /// Read the whole file. Returns `None` if the source doesn't contain the file, letting
+    /// the filesystem check the layers below.
+    fn read(&self, path: &str) -> Option<std::io::Result<Vec<u8>>>;
+
+    fn exists(&self, path: &str) -> bool;
+
+    /// Paths of the files inside a directory of the source. Sources that can't list their
+    /// files return nothing.
+    fn list(&self, _directory: &str, _recursive: bool) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+//===============================================================
+
+/// Clean up a path so it can be compared against mount points and source entries.
+/// Converts `\` to `/` and removes empty, `.` and `..` segments.
+pub fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Keep the paths inside a directory, including ones in subdirectories if recursive
+pub(crate) fn filter_listing<'a, I: Iterator<Item = &'a str>>(
+    paths: I,
+    directory: &str,
+    recursive: bool,
+) -> Vec<String> {
+    paths
+        .filter(|path| {
+            let relative = match directory.is_empty() {
+                true => Some(*path),
+                false => path
+                    .strip_prefix(directory)
+                    .and_then(|path| path.strip_prefix('/')),
+            };
+
+            match relative {
+                Some(relative) => recursive || !relative.contains('/'),
+                None => false,
+            }
+        })
+        .map(|path| path.to_string())
+        .collect()
+}
+
+//===============================================================
+
+#[derive(Clone)]
+struct Mount {
+    point: String,
+    source: Arc<dyn AssetSource>,
+}
+
+impl Mount {
+    /// Get the path relative to this mount if the mount contains it
+    fn relative_path<'a>(&self, path: &'a str) -> Option<&'a str> {
+        if self.point.is_empty() {
+            return Some(path);
+        }
+
+        match path.strip_prefix(self.point.as_str()) {
+            Some("") => Some(""),
+            Some(relative) => relative.strip_prefix('/'),
+            None => None,
+        }
+    }
+
+    /// Check if the mount point is somewhere inside a directory
+    fn is_inside(&self, directory: &str) -> bool {
+        match directory.is_empty() {
+            true => !self.point.is_empty(),
+            false => self
+                .point
+                .strip_prefix(directory)
+                .is_some_and(|point| point.starts_with('/')),
+        }
+    }
+}
+
+//===============================================================
+
+/// Virtual filesystem made out of stacked sources. Sources mounted later override files
+/// from sources mounted earlier, allowing mods and patches to replace assets.
+#[derive(Clone, Default)]
+pub struct Vfs {
+    mounts: Vec<Mount>,
+}
+
+impl Vfs {
+    //----------------------------------------------
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filesystem reading from the current working directory
+    pub fn with_working_directory() -> Self {
+        let mut vfs = Self::new();
+        vfs.mount("", DirectorySource::new("."));
+        vfs
+    }
+
+    //----------------------------------------------
+
+    /// Mount a source on top of all existing sources. An empty mount point mounts the
+    /// source at the root.
+    pub fn mount<S: AssetSource>(&mut self, mount_point: &str, source: S) {
+        self.mounts.push(Mount {
+            point: normalize_path(mount_point),
+            source: Arc::new(source),
+        });
+    }
+
+    /// Remove the most recently mounted source at the given mount point
+    pub fn unmount(&mut self, mount_point: &str) -> bool {
+        let mount_point = normalize_path(mount_point);
+
+        match self
+            .mounts
+            .iter()
+            .rposition(|mount| mount.point == mount_point)
+        {
+            Some(index) => {
+                self.mounts.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.mounts.clear();
+    }
+
+    //----------------------------------------------
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, AssetError> {
+        let normalized = normalize_path(path);
+
+        for mount in self.mounts.iter().rev() {
+            let relative = match mount.relative_path(&normalized) {
+                Some(relative) => relative,
+                None => continue,
+            };
+
+            if let Some(result) = mount.source.read(relative) {
+                return result.map_err(|e| AssetError::io(path, e));
+            }
+        }
+
+        Err(AssetError::io(
+            path,
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "file not found in any mounted source",
+            ),
+        ))
+    }
+
+    pub fn read_string(&self, path: &str) -> Result<String, AssetError> {
+        String::from_utf8(self.read(path)?).map_err(|e| AssetError::decode(path, e))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        let normalized = normalize_path(path);
+
+        self.mounts.iter().rev().any(|mount| {
+            mount
+                .relative_path(&normalized)
+                .map(|relative| mount.source.exists(relative))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Paths of the files inside a directory across every mounted source, sorted and
+    /// without duplicates
+    pub fn list(&self, directory: &str, recursive: bool) -> Vec<String> {
+        let directory = normalize_path(directory);
+        let mut files = BTreeSet::new();
+
+        for mount in &self.mounts {
+            let listed = match mount.relative_path(&directory) {
+                Some(relative) => mount.source.list(relative, recursive),
+
+                // Sources mounted inside the directory only hold files in subdirectories
+                None if recursive && mount.is_inside(&directory) => mount.source.list("", true),
+
+                None => continue,
+            };
+
+            files.extend(listed.into_iter().map(|path| match mount.point.is_empty() {
+                true => path,
+                false => format!("{}/{}", mount.point, path),
+            }));
+        }
+
+        files.into_iter().collect()
+    }
+
+    /// Paths of the files matching a glob pattern such as `"sprites/**/*.png"`. `*` doesn't
+    /// match across directories while `**` does.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<String>, PatternError> {
+        let pattern = normalize_path(pattern);
+        let matcher = Pattern::new(&pattern)?;
+
+        // Only list the part of the filesystem the pattern can match
+        let directory = pattern
+            .split('/')
+            .take_while(|segment| !segment.contains(['*', '?', '[']))
+            .collect::<Vec<_>>();
+        let directory = match directory.len() == pattern.split('/').count() {
+            // Pattern without wildcards, the last segment is the file itself
+            true => directory[..directory.len() - 1].join("/"),
+            false => directory.join("/"),
+        };
+
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+
+        Ok(self
+            .list(&directory, true)
+            .into_iter()
+            .filter(|path| matcher.matches_with(path, options))
+            .collect())
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Source reading files from a directory on disk
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl AssetSource for DirectorySource {
+    fn read(&self, path: &str) -> Option<std::io::Result<Vec<u8>>> {
+        match std::fs::read(self.root.join(path)) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            result => Some(result),
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.root.join(path).is_file()
+    }
+
+    fn list(&self, directory: &str, recursive: bool) -> Vec<String> {
+        let mut files = Vec::new();
+        let mut to_read = vec![directory.to_string()];
+
+        while let Some(directory) = to_read.pop() {
+            let entries = match std::fs::read_dir(self.root.join(&directory)) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let path = match directory.is_empty() {
+                    true => name,
+                    false => format!("{}/{}", directory, name),
+                };
+
+                match entry.file_type() {
+                    Ok(file_type) if file_type.is_dir() => {
+                        if recursive {
+                            to_read.push(path);
+                        }
+                    }
+                    Ok(_) => files.push(path),
+                    Err(_) => {}
+                }
+            }
+        }
+
+        files
+    }
+}
+
+//===============================================================
+
+/// Source reading files compiled into the binary. See `embed_assets!`.
+#[derive(Default)]
+pub struct EmbeddedSource {
+    files: HashMap<String, Cow<'static, [u8]>>,
+}
+
+impl EmbeddedSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<B: Into<Cow<'static, [u8]>>>(&mut self, path: &str, bytes: B) {
+        self.files.insert(normalize_path(path), bytes.into());
+    }
+
+    pub fn with<B: Into<Cow<'static, [u8]>>>(mut self, path: &str, bytes: B) -> Self {
+        self.insert(path, bytes);
+        self
+    }
+}
+
+impl AssetSource for EmbeddedSource {
+    fn read(&self, path: &str) -> Option<std::io::Result<Vec<u8>>> {
+        self.files.get(path).map(|bytes| Ok(bytes.to_vec()))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn list(&self, directory: &str, recursive: bool) -> Vec<String> {
+        filter_listing(self.files.keys().map(String::as_str), directory, recursive)
+    }
+}
+
+/// Create an `EmbeddedSource` containing the given files. Paths are relative to the
+/// manifest directory of the crate using the macro.
+#[macro_export]
+macro_rules! embed_assets {
+    ($($path:literal),* $(,)?) => {{
+        let mut source = $crate::vfs::EmbeddedSource::new();
+        $(
+            source.insert(
+                $path,
+                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)) as &'static [u8],
+            );
+        )*
+        source
+    }};
+}
+
+//===============================================================
+
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Source reading files from a zip archive
+pub struct ArchiveSource {
+    archive: Mutex<ZipArchive<Box<dyn ReadSeek>>>,
+}
+
+impl ArchiveSource {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        let path_str = path.as_ref().to_string_lossy();
+
+        let file = File::open(path.as_ref()).map_err(|e| AssetError::io(&path_str, e))?;
+        Self::from_reader(BufReader::new(file)).map_err(|e| AssetError::decode(&path_str, e))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, ZipError> {
+        Self::from_reader(Cursor::new(bytes))
+    }
+
+    fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<Self, ZipError> {
+        let archive = ZipArchive::new(Box::new(reader) as Box<dyn ReadSeek>)?;
+        Ok(Self {
+            archive: Mutex::new(archive),
+        })
+    }
+}
+
+impl AssetSource for ArchiveSource {
+    fn read(&self, path: &str) -> Option<std::io::Result<Vec<u8>>> {
+        let mut archive = self.archive.lock();
+
+        let mut file = match archive.by_name(path) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return None,
+            Err(ZipError::Io(e)) => return Some(Err(e)),
+            Err(e) => return Some(Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e))),
+        };
+
+        let mut buffer = Vec::with_capacity(file.size() as usize);
+        Some(file.read_to_end(&mut buffer).map(|_| buffer))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.archive.lock().by_name(path).is_ok()
+    }
+
+    fn list(&self, directory: &str, recursive: bool) -> Vec<String> {
+        let archive = self.archive.lock();
+        let files = archive.file_names().filter(|name| !name.ends_with('/'));
+        filter_listing(files, directory, recursive)
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded(files: &[(&str, &'static str)]) -> EmbeddedSource {
+        files
+            .iter()
+            .fold(EmbeddedSource::new(), |source, (path, contents)| {
+                source.with(path, contents.as_bytes())
+            })
+    }
+
+    #[test]
+    fn later_mounts_override_earlier_ones() {
+        let mut vfs = Vfs::new();
+        vfs.mount("", embedded(&[("a.txt", "base"), ("b.txt", "base")]));
+        vfs.mount("", embedded(&[("a.txt", "patch")]));
+
+        assert_eq!(vfs.read_string("a.txt").unwrap(), "patch");
+        assert_eq!(vfs.read_string("b.txt").unwrap(), "base");
+        assert_eq!(vfs.list("", false), vec!["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn unmount_removes_most_recent_mount() {
+        let mut vfs = Vfs::new();
+        vfs.mount("mods", embedded(&[("a.txt", "first")]));
+        vfs.mount("mods/", embedded(&[("a.txt", "second")]));
+
+        assert!(vfs.unmount("mods"));
+        assert_eq!(vfs.read_string("mods/a.txt").unwrap(), "first");
+
+        assert!(vfs.unmount("mods"));
+        assert!(!vfs.exists("mods/a.txt"));
+        assert!(vfs.read("mods/a.txt").is_err());
+
+        assert!(!vfs.unmount("mods"));
+    }
+
+    #[test]
+    fn normalize_path_cleans_segments() {
+        assert_eq!(normalize_path("a/b/../c.txt"), "a/c.txt");
+        assert_eq!(normalize_path("a\\b\\c.txt"), "a/b/c.txt");
+        assert_eq!(normalize_path("./a//b/./c.txt"), "a/b/c.txt");
+        assert_eq!(normalize_path("../../a.txt"), "a.txt");
+        assert_eq!(normalize_path("a/.."), "");
+
+        let mut vfs = Vfs::new();
+        vfs.mount("", embedded(&[("sprites/player.png", "player")]));
+        assert_eq!(
+            vfs.read_string("sprites\\enemies\\..\\player.png").unwrap(),
+            "player"
+        );
+    }
+
+    #[test]
+    fn list_across_nested_mounts() {
+        let mut vfs = Vfs::new();
+        vfs.mount("", embedded(&[("root.txt", ""), ("data/a.txt", "")]));
+        vfs.mount("data/extra", embedded(&[("b.txt", ""), ("deep/c.txt", "")]));
+        vfs.mount("other", embedded(&[("d.txt", "")]));
+
+        assert_eq!(vfs.list("data", false), vec!["data/a.txt"]);
+        assert_eq!(
+            vfs.list("data", true),
+            vec!["data/a.txt", "data/extra/b.txt", "data/extra/deep/c.txt"]
+        );
+        assert_eq!(vfs.list("data/extra", false), vec!["data/extra/b.txt"]);
+        assert_eq!(
+            vfs.list("", true),
+            vec![
+                "data/a.txt",
+                "data/extra/b.txt",
+                "data/extra/deep/c.txt",
+                "other/d.txt",
+                "root.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn glob_without_wildcards() {
+        let mut vfs = Vfs::new();
+        vfs.mount(
+            "",
+            embedded(&[("a.txt", ""), ("data/b.txt", ""), ("data/c.txt", "")]),
+        );
+
+        assert_eq!(vfs.glob("data/b.txt").unwrap(), vec!["data/b.txt"]);
+        assert_eq!(vfs.glob("a.txt").unwrap(), vec!["a.txt"]);
+        assert!(vfs.glob("data/missing.txt").unwrap().is_empty());
+        assert_eq!(
+            vfs.glob("data/*.txt").unwrap(),
+            vec!["data/b.txt", "data/c.txt"]
+        );
+    }
+}
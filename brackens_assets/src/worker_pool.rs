@@ -0,0 +1,85 @@
+//===============================================================
+
+use std::thread::JoinHandle;
+
+use log::{info, warn};
+
+use crate::{ReceiverType, SenderType};
+
+//===============================================================
+
+pub(crate) type Job = Box<dyn FnOnce() + Send + 'static>;
+
+const MAX_WORKER_COUNT: usize = 4;
+
+//===============================================================
+
+/// Small pool of background threads used to read and decode assets off the main thread.
+pub(crate) struct WorkerPool {
+    job_sender: Option<SenderType<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    //----------------------------------------------
+
+    pub fn new() -> Self {
+        let worker_count = std::thread::available_parallelism()
+            .map(|count| count.get().saturating_sub(1))
+            .unwrap_or(1)
+            .clamp(1, MAX_WORKER_COUNT);
+
+        info!("Creating asset worker pool with {} workers", worker_count);
+
+        let (job_sender, job_receiver) = crossbeam::channel::unbounded::<Job>();
+
+        let workers = (0..worker_count)
+            .filter_map(|index| {
+                let receiver: ReceiverType<Job> = job_receiver.clone();
+
+                std::thread::Builder::new()
+                    .name(format!("Asset Worker {}", index))
+                    .spawn(move || {
+                        // Keep running jobs until the pool is dropped and the channel disconnects
+                        while let Ok(job) = receiver.recv() {
+                            job();
+                        }
+                    })
+                    .map_err(|e| warn!("Warning: Unable to spawn asset worker thread - {}", e))
+                    .ok()
+            })
+            .collect();
+
+        Self {
+            job_sender: Some(job_sender),
+            workers,
+        }
+    }
+
+    //----------------------------------------------
+
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) {
+        match &self.job_sender {
+            // Fall back to running on the calling thread if no workers could be created
+            Some(sender) if !self.workers.is_empty() => sender.send(Box::new(job)).unwrap(),
+            _ => job(),
+        }
+    }
+
+    //----------------------------------------------
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // Dropping the sender disconnects the channel, letting each worker finish its loop
+        self.job_sender.take();
+
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                warn!("Warning: Asset worker thread panicked while shutting down");
+            }
+        }
+    }
+}
+
+//===============================================================
@@ -36,6 +36,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(80., 80.),
                 handle: texture1.clone(),
                 color: [1., 1., 1., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Center,
         ));
@@ -48,6 +51,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(30., 30.),
                 handle: texture2.clone(),
                 color: [1., 0., 0., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Spin(103.),
             Progress(0., 1.),
@@ -62,6 +68,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(28., 28.),
                 handle: texture2.clone(),
                 color: [1., 1., 1., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Spin(150.),
             Progress(0., 1.2),
@@ -76,6 +85,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(50., 50.),
                 handle: texture2.clone(),
                 color: [1., 1., 1., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Spin(250.),
             Progress(0., 0.8),
@@ -90,6 +102,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(30., 30.),
                 handle: texture2.clone(),
                 color: [1., 1., 1., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Spin(80.),
             Progress(0., 2.3),
@@ -2,7 +2,7 @@
 
 use brackens_engine::{
     core_components::KeyManager,
-    prelude::KeyCode,
+    prelude::{BlendMode, KeyCode, TextureRect},
     renderer::{
         components::Visible,
         components_2d::Texture,
@@ -42,6 +42,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(32., 32.),
                 handle: texture.clone(),
                 color: [1., 0., 1., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
             Movable(5.),
         ));
@@ -54,6 +57,9 @@ impl ShipyardGameState for Game {
                 size: Vec2::new(64., 64.),
                 handle: texture,
                 color: [1., 0., 0., 1.],
+                blend_mode: BlendMode::Opaque,
+                rect: TextureRect::FULL,
+                layer: None,
             },
         ));
 
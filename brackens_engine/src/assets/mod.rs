@@ -1,21 +1,67 @@
 //===============================================================
 
-use brackens_assets::{handle::HandleInner, AssetStorageSingle};
+use brackens_assets::{
+    asset_storage::AssetStorageError, handle::HandleInner, AssetFileLoadable, AssetStorageSingle,
+};
 // use log::info;
 use shipyard::{Unique, World};
 
-pub use brackens_assets::{Asset, Handle, HandleID};
+pub use brackens_assets::{
+    ArchiveSource, Asset, AssetError, AssetEvent, AssetEventReader, AssetSource, DirectorySource,
+    EmbeddedSource, Handle, HandleID, WeakHandle,
+};
 
 //===============================================================
 
+/// Filesystem every asset file is read through. Starts with the working directory mounted
+/// at the root.
+#[derive(Unique)]
+pub struct Vfs(pub(crate) brackens_assets::Vfs);
+
+impl Default for Vfs {
+    fn default() -> Self {
+        Self(brackens_assets::Vfs::with_working_directory())
+    }
+}
+
+impl Vfs {
+    /// Mount a source on top of the filesystem. Files in the source override files of the
+    /// same path in sources mounted before it.
+    #[inline]
+    pub fn mount<S: AssetSource>(&mut self, mount_point: &str, source: S) {
+        self.0.mount(mount_point, source)
+    }
+
+    #[inline]
+    pub fn unmount(&mut self, mount_point: &str) -> bool {
+        self.0.unmount(mount_point)
+    }
+
+    #[inline]
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, AssetError> {
+        self.0.read(path)
+    }
+
+    #[inline]
+    pub fn read_string(&self, path: &str) -> Result<String, AssetError> {
+        self.0.read_string(path)
+    }
+
+    #[inline]
+    pub fn exists(&self, path: &str) -> bool {
+        self.0.exists(path)
+    }
+}
+
+//===============================================================
+
+/// Asset that can be loaded by path. Files should be read through the `Vfs` unique.
 pub trait AssetLoadable: Asset {
-    fn load_asset(world: &World, path: &str) -> Result<Self, AssetLoadError>
+    fn load_asset(world: &World, path: &str) -> Result<Self, AssetError>
     where
         Self: Sized;
 }
 
-pub struct AssetLoadError;
-
 #[derive(Unique, Default)]
 pub struct AssetStorage<T: Asset> {
     inner: AssetStorageSingle<T>,
@@ -41,15 +87,42 @@ where
     }
 
     #[inline]
-    pub fn get_handle(&self, id: &HandleID<T>) -> Option<Handle<T>> {
+    pub fn get_handle(&self, id: &HandleID<T>) -> Result<Handle<T>, AssetStorageError> {
         self.inner.get_handle(id)
     }
 
     #[inline]
-    pub fn get_data(&self, id: &HandleID<T>) -> Option<&T> {
+    pub fn get_weak_handle(&self, id: &HandleID<T>) -> Result<WeakHandle<T>, AssetStorageError> {
+        self.inner.get_weak_handle(id)
+    }
+
+    #[inline]
+    pub fn get_data(&self, id: &HandleID<T>) -> Result<&T, AssetStorageError> {
         self.inner.get_data(id)
     }
 
+    #[inline]
+    pub fn get_mut(&mut self, id: &HandleID<T>) -> Result<&mut T, AssetStorageError> {
+        self.inner.get_mut(id)
+    }
+
+    #[inline]
+    pub fn make_mut(&mut self, id: &HandleID<T>) -> Result<&mut T, AssetStorageError>
+    where
+        T: Clone,
+    {
+        self.inner.make_mut(id)
+    }
+
+    #[inline]
+    pub fn modify<R, F: FnOnce(&mut T) -> R>(
+        &mut self,
+        id: &HandleID<T>,
+        modify: F,
+    ) -> Result<R, AssetStorageError> {
+        self.inner.modify(id, modify)
+    }
+
     //----------------------------------------------
 
     #[inline]
@@ -57,11 +130,27 @@ where
         self.inner.get_just_added()
     }
 
+    #[inline]
+    pub fn get_just_modified(&self) -> &Vec<HandleInner> {
+        self.inner.get_just_modified()
+    }
+
     #[inline]
     pub fn get_removed_assets(&self) -> &Vec<HandleInner> {
         self.inner.get_removed_assets()
     }
 
+    /// Reader that only receives events sent after it was created
+    #[inline]
+    pub fn event_reader(&mut self) -> AssetEventReader<T> {
+        self.inner.event_reader()
+    }
+
+    #[inline]
+    pub fn read_events(&self, reader: &mut AssetEventReader<T>) -> Vec<AssetEvent<T>> {
+        self.inner.read_events(reader)
+    }
+
     //----------------------------------------------
 
     #[inline]
@@ -80,7 +169,7 @@ where
         &mut self,
         world: &mut World,
         path: String,
-    ) -> Result<Handle<T>, AssetLoadError> {
+    ) -> Result<Handle<T>, AssetError> {
         // info!("Loading new {} asset from path {}", T::asset_name(), path);
 
         // Check if file is already loaded. If so, we can create a new handle to the existing data
@@ -98,4 +187,21 @@ where
     }
 }
 
+impl<T> AssetStorage<T>
+where
+    T: AssetFileLoadable,
+{
+    /// Load an asset from a file read through the virtual filesystem. Files that are already
+    /// loaded return a new handle to the existing data.
+    pub fn load_from_vfs(&mut self, vfs: &Vfs, path: &str) -> Result<Handle<T>, AssetError> {
+        if let Some(handle) = self.inner.get_loaded_file(path) {
+            return Ok(handle);
+        }
+
+        let bytes = vfs.read(path)?;
+        let data = T::load_from_bytes(path, &bytes)?;
+        Ok(self.inner.add_asset_file(data, path))
+    }
+}
+
 //===============================================================
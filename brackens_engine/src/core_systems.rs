@@ -3,12 +3,19 @@
 use brackens_tools::winit::event::{ElementState, KeyboardInput, MouseButton};
 use shipyard::{UniqueView, UniqueViewMut};
 
-use super::core_components::*;
+use super::{core_components::*, renderer::components::FrameCapture};
 
 //===============================================================
 
-pub fn sys_update_tracker(mut tracker: UniqueViewMut<UpkeepTracker>) {
-    tracker.0.tick()
+pub fn sys_update_tracker(
+    mut tracker: UniqueViewMut<UpkeepTracker>,
+    frame_capture: UniqueView<FrameCapture>,
+) {
+    // Advance by exactly one captured frame while capturing frames
+    match frame_capture.frame_delta() {
+        Some(delta) => tracker.0.tick_fixed(delta),
+        None => tracker.0.tick(),
+    }
 }
 
 //===============================================================
@@ -3,7 +3,7 @@
 use brackens_renderer::{RenderComponents, RenderPrefs, Size};
 
 use brackens_tools::{
-    runner::{Runner, RunnerCore, RunnerLoopEvent},
+    runner::{Runner, RunnerCore, RunnerDataCore, RunnerLoopEvent},
     winit::{
         self,
         event::{DeviceEvent, DeviceId, WindowEvent},
@@ -14,7 +14,7 @@ use brackens_tools::{
 
 use core_components::*;
 use log::{error, info, warn};
-use prelude::ClearColor;
+use prelude::{ClearColor, FrameCapture, Screenshots};
 use shipyard::{AllStoragesViewMut, UniqueView, UniqueViewMut};
 
 #[cfg(feature = "debug")]
@@ -28,7 +28,7 @@ use {
 pub mod prelude {
     pub use crate::{
         core_components::{UpkeepTracker, WindowSize},
-        renderer::components::{Camera, ClearColor, Visible},
+        renderer::components::{Camera, ClearColor, FrameCapture, Screenshots, Visible},
         spatial_components::{
             GlobalTransform, HierarchyBundle, HierarchyBundleTools, Transform, UseParentTransform,
         },
@@ -45,7 +45,16 @@ pub mod prelude {
     };
 
     #[cfg(feature = "2d")]
-    pub use crate::renderer::{components_2d::Texture, tools_2d::load_texture};
+    pub use crate::renderer::{
+        components_2d::{BitmapText, Gizmos, NineSlice, Sprite, SpriteAnimation, Text2D, Texture},
+        tools_2d::{load_bitmap_font, load_font, load_texture},
+    };
+    #[cfg(feature = "2d")]
+    pub use brackens_renderer::renderer_2d::{
+        AnimationClip, AnimationMode, AtlasBuilder, BlendMode, FontArc, NineSliceBorder, SliceMode,
+        SpriteAnimator, SpriteSheet, SpriteSheetLayout, TextAlign, TextSpace, TextVerticalAlign,
+        TextureAtlas, TextureRect,
+    };
 }
 
 //===============================================================
@@ -67,10 +76,17 @@ mod tool_systems;
 #[derive(Default)]
 pub struct ShipyardRunner {
     pub window_builder: WindowBuilder,
+    /// Allow taking screenshots and capturing frames, if the surface supports it
+    pub allow_capture: bool,
 }
 impl ShipyardRunner {
     pub fn run<GS: ShipyardGameState + 'static>(self) {
-        Runner::run::<ShipyardCore<GS>>(self.window_builder);
+        let prefs = RenderPrefs {
+            allow_capture: self.allow_capture,
+            ..ShipyardCore::<GS>::render_prefs()
+        };
+
+        Runner::run_with_data::<RenderPrefs, ShipyardCore<GS>>(self.window_builder, prefs);
     }
 }
 
@@ -89,10 +105,19 @@ pub struct ShipyardCore<GS: ShipyardGameState> {
     proxy: EventLoopProxy<RunnerLoopEvent>,
     world: shipyard::World,
 }
-impl<GS: ShipyardGameState> RunnerCore for ShipyardCore<GS> {
-    fn new(
+impl<GS: ShipyardGameState> ShipyardCore<GS> {
+    fn render_prefs() -> RenderPrefs {
+        RenderPrefs {
+            present_mode: brackens_renderer::wgpu::PresentMode::Mailbox,
+            ..Default::default()
+        }
+    }
+}
+impl<GS: ShipyardGameState> RunnerDataCore<RenderPrefs> for ShipyardCore<GS> {
+    fn new_data(
         window: winit::window::Window,
         event_loop: &EventLoop<brackens_tools::runner::RunnerLoopEvent>,
+        prefs: RenderPrefs,
     ) -> Self {
         info!("Staring Shipyard core");
 
@@ -104,14 +129,7 @@ impl<GS: ShipyardGameState> RunnerCore for ShipyardCore<GS> {
             width: window.inner_size().width,
             height: window.inner_size().height,
         };
-        let render_components = RenderComponents::new(
-            RenderPrefs {
-                present_mode: brackens_renderer::wgpu::PresentMode::Mailbox,
-                ..Default::default()
-            },
-            &window,
-            inner_size,
-        );
+        let render_components = RenderComponents::new(prefs, &window, inner_size);
 
         //--------------------------------------------------
 
@@ -135,6 +153,8 @@ impl<GS: ShipyardGameState> RunnerCore for ShipyardCore<GS> {
         //--------------------------------------------------
 
         world.add_unique(ClearColor([0.5, 0.4, 0.4]));
+        world.add_unique(Screenshots::default());
+        world.add_unique(FrameCapture::default());
 
         //--------------------------------------------------
 
@@ -165,6 +185,14 @@ impl<GS: ShipyardGameState> RunnerCore for ShipyardCore<GS> {
 
         //--------------------------------------------------
     }
+}
+impl<GS: ShipyardGameState> RunnerCore for ShipyardCore<GS> {
+    fn new(
+        window: winit::window::Window,
+        event_loop: &EventLoop<brackens_tools::runner::RunnerLoopEvent>,
+    ) -> Self {
+        Self::new_data(window, event_loop, Self::render_prefs())
+    }
 
     fn input(&mut self, event: WindowEvent) {
         match event {
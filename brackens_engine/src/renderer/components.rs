@@ -1,11 +1,14 @@
 //===============================================================
 
-use brackens_renderer::render_tools;
+use std::path::{Path, PathBuf};
+
+use brackens_renderer::{capture, render_tools};
 
 use brackens_tools::glam::Vec3;
 use shipyard::{Component, Unique};
 
 pub use brackens_renderer::{
+    capture::{CaptureFormat, FrameCaptureSettings},
     renderer_2d::RendererTexture,
     renderer_2d::TextureDrawBuffer as FinalTextureDrawCall,
     tools::{CameraOrthographic, CameraPerspective},
@@ -20,6 +23,64 @@ pub struct RenderPassTools(pub(crate) render_tools::RenderPassTools);
 #[derive(Unique)]
 pub struct ClearColor(pub [f64; 3]);
 
+/// Request screenshots of the window. They are taken at the end of the frame and saved
+/// as PNG files in the background. Requires `ShipyardRunner::allow_capture`.
+#[derive(Unique, Default)]
+pub struct Screenshots(pub(crate) capture::Screenshots);
+impl Screenshots {
+    /// Returns the path the screenshot will be saved to
+    pub fn request(&mut self) -> PathBuf {
+        self.0.request()
+    }
+    pub fn request_path(&mut self, path: impl Into<PathBuf>) {
+        self.0.request_path(path)
+    }
+    pub fn directory(&self) -> &Path {
+        self.0.directory()
+    }
+    pub fn set_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.0.set_directory(directory)
+    }
+    pub fn in_progress(&self) -> usize {
+        self.0.in_progress()
+    }
+}
+
+/// Record a number of seconds of frames to a PNG sequence or Y4M file. While capturing,
+/// the upkeep tracker advances by exactly one captured frame every frame. Requires
+/// `ShipyardRunner::allow_capture`.
+#[derive(Unique, Default)]
+pub struct FrameCapture(pub(crate) Option<capture::FrameCapture>);
+impl FrameCapture {
+    /// Returns false without starting if a capture is already in progress
+    pub fn start(&mut self, settings: FrameCaptureSettings) -> bool {
+        if self.is_capturing() {
+            return false;
+        }
+        self.0 = Some(capture::FrameCapture::new(settings));
+        true
+    }
+    /// Stop capturing early. Frames captured so far are still written.
+    pub fn stop(&mut self) {
+        if let Some(capture) = &mut self.0 {
+            capture.stop();
+        }
+    }
+    pub fn is_capturing(&self) -> bool {
+        self.0.is_some()
+    }
+    /// Captured and total number of frames
+    pub fn progress(&self) -> Option<(u32, u32)> {
+        self.0
+            .as_ref()
+            .map(|capture| (capture.frames_captured(), capture.frame_count()))
+    }
+    /// Time to advance the game by each frame while capturing
+    pub fn frame_delta(&self) -> Option<f32> {
+        self.0.as_ref().map(|capture| capture.frame_delta())
+    }
+}
+
 //===============================================================
 // Shared Rendering Components
 
@@ -1,11 +1,22 @@
 //===============================================================
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
 
-use brackens_assets::Handle;
+use brackens_assets::{Handle, HandleID};
 use brackens_renderer::{
+    gizmos::{GizmoBuffer, GizmoRenderer as GizmoRendererInner},
     render_tools,
-    renderer_2d::{self, tools::TextureProcessor, RawTextureInstance, RendererTexture, TextureID},
+    renderer_2d::{
+        self,
+        tools::{TextureProcessor, TransparentInstance},
+        AnimationClip, AnimationEvent, BlendMode, FontArc, FontId, NineSliceBorder,
+        NineSliceLayout, RawTextureInstance, RendererBitmapFont, RendererTexture, SliceMode,
+        SpriteAnimator, SpriteSheet, SubTexture, TextAlign, TextSection, TextSpace,
+        TextVerticalAlign, TextureID, TextureRect,
+    },
     wgpu, Size,
 };
 use brackens_tools::glam::{self, Vec2};
@@ -21,6 +32,12 @@ pub struct Texture {
     pub size: Vec2,
     pub handle: Handle<RendererTexture>,
     pub color: [f32; 4],
+    pub blend_mode: BlendMode,
+    /// Area of the texture to draw
+    pub rect: TextureRect,
+    /// Depth to draw at instead of the z translation of the transform. Textures with a
+    /// higher layer are drawn behind ones with a lower layer.
+    pub layer: Option<f32>,
 }
 impl Texture {
     pub fn new(handle: Handle<RendererTexture>, width: f32, height: f32) -> Self {
@@ -28,6 +45,9 @@ impl Texture {
             size: Vec2::new(width, height),
             handle,
             color: [1., 1., 1., 1.],
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layer: None,
         }
     }
     pub fn new_color(
@@ -40,8 +60,279 @@ impl Texture {
             size: Vec2::new(width, height),
             handle,
             color,
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layer: None,
+        }
+    }
+
+    /// Draw an image packed into an atlas at its size in pixels
+    pub fn from_sub_texture(sub_texture: &SubTexture) -> Self {
+        Texture {
+            size: Vec2::new(
+                sub_texture.size.width as f32,
+                sub_texture.size.height as f32,
+            ),
+            handle: sub_texture.texture.clone(),
+            color: [1., 1., 1., 1.],
+            blend_mode: BlendMode::default(),
+            rect: sub_texture.rect,
+            layer: None,
+        }
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn with_rect(mut self, rect: TextureRect) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = Some(layer);
+        self
+    }
+}
+
+/// Texture drawn as nine slices so its borders keep their size when the area is scaled.
+/// The borders are in pixels of the texture.
+#[derive(Component, Clone)]
+pub struct NineSlice {
+    pub size: Vec2,
+    pub handle: Handle<RendererTexture>,
+    pub color: [f32; 4],
+    pub blend_mode: BlendMode,
+    /// Area of the texture containing all nine slices
+    pub rect: TextureRect,
+    pub layout: NineSliceLayout,
+}
+impl NineSlice {
+    pub fn new(
+        handle: Handle<RendererTexture>,
+        width: f32,
+        height: f32,
+        border: NineSliceBorder,
+    ) -> Self {
+        NineSlice {
+            size: Vec2::new(width, height),
+            handle,
+            color: [1., 1., 1., 1.],
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layout: NineSliceLayout::new(border),
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_rect(mut self, rect: TextureRect) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    /// Size of each pixel of the borders when drawn
+    pub fn with_border_scale(mut self, border_scale: f32) -> Self {
+        self.layout.border_scale = border_scale;
+        self
+    }
+
+    /// Repeat the edges and center instead of stretching them
+    pub fn with_tiling(mut self, edges: SliceMode, center: SliceMode) -> Self {
+        self.layout.edges = edges;
+        self.layout.center = center;
+        self
+    }
+}
+
+//--------------------------------------------------
+// Sprite Components
+
+/// Draw a single sprite of a sprite sheet. The handle and rect of the `Texture` on the
+/// same entity are kept in sync with the sheet.
+#[derive(Component, Clone)]
+pub struct Sprite {
+    pub sheet: Handle<SpriteSheet>,
+    pub index: usize,
+}
+impl Sprite {
+    pub fn new(sheet: Handle<SpriteSheet>, index: usize) -> Self {
+        Self { sheet, index }
+    }
+}
+
+/// Changes the index of the `Sprite` on the same entity over time
+#[derive(Component, Clone, Default)]
+pub struct SpriteAnimation(pub(crate) SpriteAnimator);
+impl SpriteAnimation {
+    //--------------------------------------------------
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.0.add_clip(name, clip);
+        self
+    }
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip) {
+        self.0.add_clip(name, clip);
+    }
+
+    //--------------------------------------------------
+
+    pub fn play(&mut self, clip: &str) -> bool {
+        self.0.play(clip)
+    }
+    pub fn restart(&mut self, clip: &str) -> bool {
+        self.0.restart(clip)
+    }
+    pub fn pause(&mut self) {
+        self.0.pause()
+    }
+    pub fn resume(&mut self) {
+        self.0.resume()
+    }
+    pub fn stop(&mut self) {
+        self.0.stop()
+    }
+    pub fn set_speed(&mut self, speed: f32) {
+        self.0.set_speed(speed)
+    }
+
+    //--------------------------------------------------
+
+    pub fn is_playing(&self) -> bool {
+        self.0.is_playing()
+    }
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+    pub fn current_clip(&self) -> Option<&str> {
+        self.0.current_clip()
+    }
+    pub fn current_frame(&self) -> usize {
+        self.0.current_frame()
+    }
+    /// Events of the frames reached this frame
+    pub fn events(&self) -> &[AnimationEvent] {
+        self.0.events()
+    }
+
+    //--------------------------------------------------
+}
+
+impl From<SpriteAnimator> for SpriteAnimation {
+    fn from(animator: SpriteAnimator) -> Self {
+        Self(animator)
+    }
+}
+
+//--------------------------------------------------
+// Text Components
+
+/// Text drawn at the global transform of the entity. The alignment is relative to the
+/// translation, so centered text is centered on the entity.
+#[derive(Component, Clone)]
+pub struct Text2D {
+    pub text: String,
+    pub font: Handle<FontArc>,
+    /// Height of the text in pixels
+    pub size: f32,
+    pub color: [f32; 4],
+    pub align: TextAlign,
+    pub vertical_align: TextVerticalAlign,
+    /// Width and height to wrap the text within. Text outside the bounds is clipped.
+    pub bounds: Option<Vec2>,
+    /// Screen space text is positioned in pixels from the bottom left of the window
+    pub space: TextSpace,
+}
+impl Text2D {
+    pub fn new(text: &str, font: Handle<FontArc>, size: f32) -> Self {
+        Text2D {
+            text: text.to_string(),
+            font,
+            size,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            bounds: None,
+            space: TextSpace::default(),
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign, vertical_align: TextVerticalAlign) -> Self {
+        self.align = align;
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_bounds(mut self, width: f32, height: f32) -> Self {
+        self.bounds = Some(Vec2::new(width, height));
+        self
+    }
+
+    pub fn with_space(mut self, space: TextSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+/// Text drawn with a bitmap font at the global transform of the entity. Glyphs are drawn
+/// by the texture renderer, so bitmap text is sorted and batched along with other textures.
+#[derive(Component, Clone)]
+pub struct BitmapText {
+    pub text: String,
+    pub font: Handle<RendererBitmapFont>,
+    /// Size of each pixel of the font
+    pub scale: f32,
+    pub color: [f32; 4],
+    pub align: TextAlign,
+    pub vertical_align: TextVerticalAlign,
+    pub blend_mode: BlendMode,
+}
+impl BitmapText {
+    pub fn new(text: &str, font: Handle<RendererBitmapFont>) -> Self {
+        BitmapText {
+            text: text.to_string(),
+            font,
+            scale: 1.,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            blend_mode: BlendMode::Alpha,
         }
     }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign, vertical_align: TextVerticalAlign) -> Self {
+        self.align = align;
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
 }
 
 //--------------------------------------------------
@@ -101,11 +392,35 @@ impl TextureRenderer {
         self.processor.get_unprocessed_mut()
     }
 
+    #[inline]
+    pub(crate) fn get_unprocessed_transparent_mut(
+        &mut self,
+    ) -> &mut Vec<TransparentInstance<TextureID>> {
+        self.processor.get_unprocessed_transparent_mut()
+    }
+
+    #[inline]
+    pub(crate) fn draw_texture_blended(
+        &mut self,
+        texture_id: TextureID,
+        instance: RawTextureInstance,
+        blend_mode: BlendMode,
+        order: u64,
+    ) {
+        self.processor
+            .draw_texture_blended(texture_id, instance, blend_mode, order);
+    }
+
     #[inline]
     pub(crate) fn process_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
         self.processor.process_texture(device, queue);
     }
 
+    #[inline]
+    pub(crate) fn depth_texture(&self) -> &renderer_2d::Texture {
+        self.renderer.depth_texture()
+    }
+
     //--------------------------------------------------
 
     pub(crate) fn render(
@@ -117,14 +432,194 @@ impl TextureRenderer {
             .processor
             .get_draw_data()
             .iter()
-            .map(|(id, buffer)| {
-                let bind_group = &texture_storage.get_data(id).unwrap().bind_group;
+            // Textures may have been unloaded since their instances were queued
+            .filter_map(|(id, buffer)| {
+                let bind_group = &texture_storage.get_data(id).ok()?.bind_group;
 
-                (bind_group, buffer)
+                Some((bind_group, buffer))
             })
             .collect::<Vec<_>>();
 
         self.renderer.render(render_tools, &draw);
+
+        // Transparent textures are drawn back to front after all opaque ones
+        if let Some(instance_buffer) = self.processor.get_transparent_buffer() {
+            let transparent = self
+                .processor
+                .get_transparent_draw_calls()
+                .iter()
+                .filter_map(|draw_call| {
+                    let bind_group = &texture_storage
+                        .get_data(&draw_call.texture_id)
+                        .ok()?
+                        .bind_group;
+
+                    Some((
+                        bind_group,
+                        draw_call.blend_mode,
+                        draw_call.instances.clone(),
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            self.renderer
+                .render_transparent(render_tools, instance_buffer, &transparent);
+        }
+    }
+
+    //--------------------------------------------------
+}
+
+//--------------------------------------------------
+// Text Rendering
+
+#[derive(Unique)]
+pub struct TextRenderer(renderer_2d::TextRenderer);
+
+impl TextRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        window_size: Size<u32>,
+    ) -> Self {
+        Self(renderer_2d::TextRenderer::new(
+            device,
+            config.format,
+            window_size,
+        ))
+    }
+
+    //--------------------------------------------------
+
+    #[inline]
+    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, new_size: Size<u32>) {
+        self.0.resize(queue, new_size);
+    }
+
+    #[inline]
+    pub(crate) fn resize_projection(&mut self, queue: &wgpu::Queue, matrix: &glam::Mat4) {
+        self.0.set_camera_projection(queue, matrix);
+    }
+
+    //--------------------------------------------------
+
+    #[inline]
+    pub(crate) fn font_id(&mut self, handle_id: HandleID<FontArc>, font: &FontArc) -> FontId {
+        self.0.font_id(handle_id, font)
+    }
+
+    #[inline]
+    pub(crate) fn queue(&mut self, section: &TextSection) {
+        self.0.queue(section);
+    }
+
+    #[inline]
+    pub(crate) fn process(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.0.process(device, queue);
+    }
+
+    #[inline]
+    pub(crate) fn render(&self, render_tools: &mut render_tools::RenderPassTools) {
+        self.0.render(render_tools);
+    }
+
+    //--------------------------------------------------
+}
+
+//===============================================================
+// Gizmo Rendering
+
+/// Lines and shapes queued by any system, drawn on top of the scene for the current frame
+/// only.
+#[derive(Unique, Default)]
+pub struct Gizmos(GizmoBuffer);
+impl Deref for Gizmos {
+    type Target = GizmoBuffer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for Gizmos {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Unique)]
+pub struct GizmoRenderer {
+    renderer: GizmoRendererInner,
+
+    /// Projection of the 2D camera, kept while drawing with the projection of another renderer
+    camera_projection: glam::Mat4,
+    scene_projection: bool,
+}
+
+impl GizmoRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        window_size: Size<u32>,
+    ) -> Self {
+        let renderer = GizmoRendererInner::new(device, config.format, window_size);
+
+        Self {
+            camera_projection: renderer.projection(),
+            renderer,
+            scene_projection: false,
+        }
+    }
+
+    //--------------------------------------------------
+
+    #[inline]
+    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, new_size: Size<u32>) {
+        self.renderer.resize(queue, new_size);
+    }
+
+    pub(crate) fn resize_projection(&mut self, queue: &wgpu::Queue, matrix: &glam::Mat4) {
+        self.camera_projection = *matrix;
+
+        if !self.scene_projection {
+            self.renderer.set_projection(queue, matrix);
+        }
+    }
+
+    /// Draw with the projection of whatever was rendered last this frame. None uses the 2D
+    /// camera.
+    #[cfg(feature = "3d")]
+    pub(crate) fn set_scene_projection(
+        &mut self,
+        queue: &wgpu::Queue,
+        projection: Option<glam::Mat4>,
+    ) {
+        match projection {
+            Some(projection) => {
+                self.renderer.set_projection(queue, &projection);
+                self.scene_projection = true;
+            }
+            None if self.scene_projection => {
+                self.renderer.set_projection(queue, &self.camera_projection);
+                self.scene_projection = false;
+            }
+            None => {}
+        }
+    }
+
+    //--------------------------------------------------
+
+    #[inline]
+    pub(crate) fn process(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, gizmos: &Gizmos) {
+        self.renderer.process(device, queue, gizmos);
+    }
+
+    #[inline]
+    pub(crate) fn render(
+        &self,
+        render_tools: &mut render_tools::RenderPassTools,
+        depth_texture: &renderer_2d::Texture,
+    ) {
+        self.renderer.render(render_tools, depth_texture);
     }
 
     //--------------------------------------------------
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use brackens_assets::Handle;
 use brackens_renderer::{
-    bytemuck, render_tools,
+    bytemuck, render_tools, renderer_2d,
     renderer_3d::{
         self, model_renderer::InstanceData, MaterialID, MeshID, RawMeshInstance, RendererMaterial,
         RendererMesh,
@@ -12,6 +12,7 @@ use brackens_renderer::{
     wgpu::{self, util::DeviceExt},
     Size,
 };
+use brackens_tools::glam;
 use shipyard::{Component, Unique};
 
 use crate::assets::AssetStorage;
@@ -59,6 +60,22 @@ impl ModelRenderer {
         self.renderer.get_material_layout()
     }
 
+    /// Whether any models were processed to be drawn this frame
+    #[inline]
+    pub(crate) fn has_models(&self) -> bool {
+        !self.processed_data.is_empty()
+    }
+
+    #[inline]
+    pub(crate) fn depth_texture(&self) -> &renderer_2d::Texture {
+        self.renderer.depth_texture()
+    }
+
+    #[inline]
+    pub(crate) fn projection(&self) -> glam::Mat4 {
+        self.renderer.projection()
+    }
+
     //--------------------------------------------------
 
     pub(crate) fn resize(
@@ -28,8 +28,13 @@ cfg_if! {
 //===============================================================
 
 pub(crate) fn run_startup_systems(world: &mut World) {
-    #[cfg(feature = "2d")]
-    world.run(systems_2d::sys_setup_texture_renderer);
+    cfg_if! {
+        if #[cfg(feature = "2d")] {
+            world.run(systems_2d::sys_setup_texture_renderer);
+            world.run(systems_2d::sys_setup_text_renderer);
+            world.run(systems_2d::sys_setup_gizmo_renderer);
+        }
+    }
 }
 
 pub(crate) fn run_resize_systems(world: &mut World) {
@@ -38,6 +43,8 @@ pub(crate) fn run_resize_systems(world: &mut World) {
     cfg_if! {
     if #[cfg(feature = "2d")] {
             world.run(systems_2d::sys_resize_pipeline);
+            world.run(systems_2d::sys_resize_text);
+            world.run(systems_2d::sys_resize_gizmos);
         }
     }
 
@@ -47,7 +54,13 @@ pub(crate) fn run_resize_systems(world: &mut World) {
 
 pub(crate) fn workload_post_update_systems() -> Workload {
     // #[cfg(feature = "2d")]
-    (systems_2d::sys_update_camera).into_workload()
+    (
+        systems_2d::sys_update_camera,
+        systems_2d::sys_update_text_camera,
+        systems_2d::sys_update_gizmo_camera,
+        systems_2d::sys_update_sprite_animations,
+    )
+        .into_workload()
 }
 
 //===============================================================
@@ -55,8 +68,13 @@ pub(crate) fn workload_post_update_systems() -> Workload {
 pub(crate) fn run_post_render_systems(world: &mut World) {
     cfg_if! {
         if #[cfg(feature = "2d")] {
+            world.run(systems_2d::sys_update_sprites);
+            world.run(systems_2d::sys_queue_bitmap_text);
+            world.run(systems_2d::sys_queue_nine_slices);
             world.run(systems_2d::sys_process_textures);
+            world.run(systems_2d::sys_process_text);
             world.run(systems_2d::sys_render_textures);
+            world.run(systems_2d::sys_render_text);
         }
     }
     cfg_if! {
@@ -66,6 +84,17 @@ pub(crate) fn run_post_render_systems(world: &mut World) {
         }
     }
 
+    // Gizmos go on top of whatever was drawn last
+    cfg_if! {
+        if #[cfg(feature = "3d")] {
+            world.run(systems_2d::sys_process_gizmos);
+            world.run(systems_3d::sys_render_gizmos);
+        } else if #[cfg(feature = "2d")] {
+            world.run(systems_2d::sys_process_gizmos);
+            world.run(systems_2d::sys_render_gizmos);
+        }
+    }
+
     systems::sys_end_render_pass(world);
 }
 
@@ -41,9 +41,30 @@ pub fn sys_clear_background(
 
 pub fn sys_end_render_pass(world: &mut World) {
     if let Ok(render_pass) = world.remove_unique::<RenderPassTools>() {
-        world.run(|queue: UniqueView<Queue>| {
-            render_tools::end_render_pass(&queue.0, render_pass.0);
-        });
+        world.run(
+            |device: UniqueView<Device>,
+             queue: UniqueView<Queue>,
+             mut screenshots: UniqueViewMut<Screenshots>,
+             mut frame_capture: UniqueViewMut<FrameCapture>| {
+                render_tools::end_render_pass_with_capture(
+                    &device.0,
+                    &queue.0,
+                    render_pass.0,
+                    Some(&mut screenshots.0),
+                    frame_capture.0.as_mut(),
+                );
+
+                if frame_capture
+                    .0
+                    .as_ref()
+                    .is_some_and(|capture| capture.is_finished())
+                {
+                    if let Some(finished) = frame_capture.0.take() {
+                        finished.finish(&device.0);
+                    }
+                }
+            },
+        );
     }
 }
 
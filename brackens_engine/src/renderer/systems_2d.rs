@@ -2,13 +2,22 @@
 
 use std::collections::HashMap;
 
-use brackens_renderer::renderer_2d::{RawTextureInstance, RendererTexture, TextureID};
+use brackens_renderer::{
+    renderer_2d::{
+        tools::TransparentInstance, BitmapTextSection, FontArc, RawTextureInstance,
+        RendererBitmapFont, RendererTexture, SpriteSheet, TextSection, TextureID,
+    },
+    Size,
+};
+use brackens_tools::glam;
 use rayon::prelude::ParallelIterator;
-use shipyard::{AllStoragesView, IntoIter, UniqueView, UniqueViewMut, View};
+use shipyard::{
+    AllStoragesView, Get, IntoIter, IntoWithId, UniqueView, UniqueViewMut, View, ViewMut,
+};
 
 use crate::{
     assets::AssetStorage,
-    core_components::{Device, Queue, SurfaceConfig, WindowSize},
+    core_components::{Device, Queue, SurfaceConfig, UpkeepTracker, WindowSize},
     spatial_components::GlobalTransform,
 };
 
@@ -62,6 +71,45 @@ pub fn sys_update_camera(
 
 //--------------------------------------------------
 
+pub fn sys_update_sprite_animations(
+    upkeep: UniqueView<UpkeepTracker>,
+    mut vm_animation: ViewMut<SpriteAnimation>,
+    mut vm_sprite: ViewMut<Sprite>,
+) {
+    let delta = upkeep.delta();
+
+    for (animation, sprite) in (&mut vm_animation, &mut vm_sprite).iter() {
+        animation.0.update(delta);
+
+        if let Some(index) = animation.0.sprite_index() {
+            sprite.index = index;
+        }
+    }
+}
+
+pub fn sys_update_sprites(
+    sheet_storage: UniqueView<AssetStorage<SpriteSheet>>,
+    v_sprite: View<Sprite>,
+    mut vm_texture: ViewMut<Texture>,
+) {
+    for (sprite, texture) in (&v_sprite, &mut vm_texture).iter() {
+        let sheet = match sheet_storage.get_data(&sprite.sheet.id()) {
+            Ok(sheet) => sheet,
+            Err(_) => continue,
+        };
+
+        if texture.handle.id() != sheet.texture().id() {
+            texture.handle = sheet.texture().clone();
+        }
+
+        if let Some(rect) = sheet.uv(sprite.index) {
+            texture.rect = rect;
+        }
+    }
+}
+
+//--------------------------------------------------
+
 pub fn sys_process_textures(
     device: UniqueView<Device>,
     queue: UniqueView<Queue>,
@@ -78,27 +126,36 @@ pub fn sys_process_textures(
 
     //--------------------------------------------------
 
+    let to_instance = |texture: &Texture, transform: &GlobalTransform| {
+        let mut instance = RawTextureInstance {
+            tex_coord_top_left: texture.rect.top_left,
+            tex_coord_bottom_right: texture.rect.bottom_right,
+            transform: (GlobalTransform::from_scale(texture.size.extend(1.)) + transform).to_raw(),
+            color: texture.color,
+        };
+
+        if let Some(layer) = texture.layer {
+            instance.transform[14] = layer;
+        }
+        instance
+    };
+
     // let result: HashMap<TextureID, Vec<RawTextureInstance>>
-    *renderer.get_unprocessed_mut() = (&v_texture, &v_visible, &v_global_transform)
+    let opaque = (&v_texture, &v_visible, &v_global_transform)
         .par_iter()
         .fold(
-            || HashMap::<TextureID, Vec<RawTextureInstance>>::new(),
-            |mut acc, (texture, visible, transform)| {
-                if visible.visible {
-                    // Add RawTextureInstance to hashmap of values to be renderer
-                    acc.entry(texture.handle.id())
+            HashMap::<TextureID, Vec<RawTextureInstance>>::new,
+            |mut opaque, (texture, visible, transform)| {
+                // Transparent textures are sorted and batched by the renderer below
+                if visible.visible && !texture.blend_mode.is_transparent() {
+                    opaque
+                        .entry(texture.handle.id())
                         // Insert empty vec if no value present
                         .or_insert(vec![])
                         // Add texture instance to new or existing hashmap entry
-                        .push(RawTextureInstance {
-                            transform: (GlobalTransform::from_scale(texture.size.extend(1.))
-                                + transform)
-                                .to_raw(),
-                            color: texture.color,
-                            ..Default::default()
-                        });
+                        .push(to_instance(texture, transform));
                 }
-                acc
+                opaque
             },
         )
         .reduce_with(|mut m1, m2| {
@@ -107,7 +164,32 @@ pub fn sys_process_textures(
             }
             m1
         })
-        .unwrap();
+        .unwrap_or_default();
+
+    // Parallel iterators can't give entity ids, which order transparent textures at the
+    // same depth
+    let transparent = (&v_texture, &v_visible, &v_global_transform)
+        .iter()
+        .with_id()
+        .filter(|(_, (texture, visible, _))| visible.visible && texture.blend_mode.is_transparent())
+        .map(|(id, (texture, _, transform))| {
+            TransparentInstance::new(
+                texture.handle.id(),
+                texture.blend_mode,
+                to_instance(texture, transform),
+            )
+            .with_order(id.inner())
+        })
+        .collect::<Vec<_>>();
+
+    // Keep anything else queued this frame, such as bitmap text and nine slices
+    let unprocessed = renderer.get_unprocessed_mut();
+    for (id, instances) in opaque {
+        unprocessed.entry(id).or_insert(vec![]).extend(instances);
+    }
+    renderer
+        .get_unprocessed_transparent_mut()
+        .extend(transparent);
 
     //--------------------------------------------------
 
@@ -131,6 +213,85 @@ pub fn sys_process_textures(
     );
 }
 
+/// Queue an instance for each slice of the nine slices to be processed with the rest of
+/// the textures
+pub fn sys_queue_nine_slices(
+    texture_storage: UniqueView<AssetStorage<RendererTexture>>,
+
+    mut renderer: UniqueViewMut<TextureRenderer>,
+    v_nine_slice: View<NineSlice>,
+    v_visible: View<Visible>,
+    v_global_transform: View<GlobalTransform>,
+) {
+    for (id, (nine_slice, transform)) in (&v_nine_slice, &v_global_transform).iter().with_id() {
+        // Nine slices without a visible component are always shown
+        if let Ok(visible) = v_visible.get(id) {
+            if !visible.visible {
+                continue;
+            }
+        }
+
+        let size = match texture_storage.get_data(&nine_slice.handle.id()) {
+            Ok(texture) => texture.texture.texture.size(),
+            Err(_) => continue,
+        };
+
+        let instances = nine_slice.layout.instances(
+            nine_slice.rect,
+            Size::new(size.width, size.height),
+            nine_slice.size,
+            transform.to_mat4(),
+            nine_slice.color,
+        );
+
+        for instance in instances {
+            renderer.draw_texture_blended(
+                nine_slice.handle.id(),
+                instance,
+                nine_slice.blend_mode,
+                id.inner(),
+            );
+        }
+    }
+}
+
+/// Queue the glyphs of bitmap text to be processed with the rest of the textures
+pub fn sys_queue_bitmap_text(
+    font_storage: UniqueView<AssetStorage<RendererBitmapFont>>,
+
+    mut renderer: UniqueViewMut<TextureRenderer>,
+    v_text: View<BitmapText>,
+    v_visible: View<Visible>,
+    v_global_transform: View<GlobalTransform>,
+) {
+    for (id, (text, transform)) in (&v_text, &v_global_transform).iter().with_id() {
+        // Text without a visible component is always shown
+        if let Ok(visible) = v_visible.get(id) {
+            if !visible.visible {
+                continue;
+            }
+        }
+
+        let font = match font_storage.get_data(&text.font.id()) {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+
+        let glyphs = font.instances(&BitmapTextSection {
+            text: &text.text,
+            scale: text.scale,
+            color: text.color,
+            align: text.align,
+            vertical_align: text.vertical_align,
+            transform: transform.to_mat4(),
+        });
+
+        for (texture_id, instance) in glyphs {
+            renderer.draw_texture_blended(texture_id, instance, text.blend_mode, id.inner());
+        }
+    }
+}
+
 pub fn sys_render_textures(
     mut renderer: UniqueViewMut<TextureRenderer>,
     mut render_tools: UniqueViewMut<RenderPassTools>,
@@ -140,3 +301,161 @@ pub fn sys_render_textures(
 }
 
 //===============================================================
+// Text Stuff
+
+pub fn sys_setup_text_renderer(
+    all_storages: AllStoragesView,
+    device: UniqueView<Device>,
+    config: UniqueView<SurfaceConfig>,
+    window_size: UniqueView<WindowSize>,
+) {
+    all_storages.add_unique(TextRenderer::new(&device.0, &config.0, window_size.0));
+}
+
+//--------------------------------------------------
+
+pub fn sys_resize_text(
+    queue: UniqueView<Queue>,
+    window_size: UniqueView<WindowSize>,
+    mut renderer: UniqueViewMut<TextRenderer>,
+
+    v_camera_bundle: CameraBundleView,
+) {
+    let size = window_size.size();
+    renderer.resize(&queue.0, size);
+
+    if !v_camera_bundle.has_camera() {
+        renderer.resize_projection(
+            &queue.0,
+            &glam::Mat4::orthographic_lh(0., size.width as f32, 0., size.height as f32, 0., 100.),
+        );
+    }
+}
+
+pub fn sys_update_text_camera(
+    queue: UniqueView<Queue>,
+    mut renderer: UniqueViewMut<TextRenderer>,
+    v_camera_bundle: CameraBundleView,
+) {
+    if v_camera_bundle.camera_changed() {
+        renderer.resize_projection(&queue.0, &v_camera_bundle.get_projection());
+    }
+}
+
+//--------------------------------------------------
+
+pub fn sys_process_text(
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    font_storage: UniqueView<AssetStorage<FontArc>>,
+
+    mut renderer: UniqueViewMut<TextRenderer>,
+    v_text: View<Text2D>,
+    v_visible: View<Visible>,
+    v_global_transform: View<GlobalTransform>,
+) {
+    for (id, (text, transform)) in (&v_text, &v_global_transform).iter().with_id() {
+        // Text without a visible component is always shown
+        if let Ok(visible) = v_visible.get(id) {
+            if !visible.visible {
+                continue;
+            }
+        }
+
+        let font = match font_storage.get_data(&text.font.id()) {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+
+        let font_id = renderer.font_id(text.font.id(), font);
+
+        renderer.queue(&TextSection {
+            text: &text.text,
+            font: font_id,
+            size: text.size,
+            color: text.color,
+            align: text.align,
+            vertical_align: text.vertical_align,
+            bounds: text.bounds.map(|bounds| bounds.to_array()),
+            space: text.space,
+            transform: transform.to_mat4(),
+        });
+    }
+
+    renderer.process(&device.0, &queue.0);
+}
+
+/// Text is drawn on top of all textures
+pub fn sys_render_text(
+    renderer: UniqueView<TextRenderer>,
+    mut render_tools: UniqueViewMut<RenderPassTools>,
+) {
+    renderer.render(&mut render_tools.0);
+}
+
+//===============================================================
+// Gizmo Stuff
+
+pub fn sys_setup_gizmo_renderer(
+    all_storages: AllStoragesView,
+    device: UniqueView<Device>,
+    config: UniqueView<SurfaceConfig>,
+    window_size: UniqueView<WindowSize>,
+) {
+    all_storages.add_unique(GizmoRenderer::new(&device.0, &config.0, window_size.0));
+    all_storages.add_unique(Gizmos::default());
+}
+
+//--------------------------------------------------
+
+pub fn sys_resize_gizmos(
+    queue: UniqueView<Queue>,
+    window_size: UniqueView<WindowSize>,
+    mut renderer: UniqueViewMut<GizmoRenderer>,
+
+    v_camera_bundle: CameraBundleView,
+) {
+    let size = window_size.size();
+    renderer.resize(&queue.0, size);
+
+    if !v_camera_bundle.has_camera() {
+        renderer.resize_projection(
+            &queue.0,
+            &glam::Mat4::orthographic_lh(0., size.width as f32, 0., size.height as f32, 0., 100.),
+        );
+    }
+}
+
+pub fn sys_update_gizmo_camera(
+    queue: UniqueView<Queue>,
+    mut renderer: UniqueViewMut<GizmoRenderer>,
+    v_camera_bundle: CameraBundleView,
+) {
+    if v_camera_bundle.camera_changed() {
+        renderer.resize_projection(&queue.0, &v_camera_bundle.get_projection());
+    }
+}
+
+//--------------------------------------------------
+
+/// Upload the gizmos queued this frame and clear them for the next one
+pub fn sys_process_gizmos(
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    mut renderer: UniqueViewMut<GizmoRenderer>,
+    mut gizmos: UniqueViewMut<Gizmos>,
+) {
+    renderer.process(&device.0, &queue.0, &gizmos);
+    gizmos.clear();
+}
+
+/// Gizmos are drawn after everything else, tested against the depth of the textures
+pub fn sys_render_gizmos(
+    renderer: UniqueView<GizmoRenderer>,
+    texture_renderer: UniqueView<TextureRenderer>,
+    mut render_tools: UniqueViewMut<RenderPassTools>,
+) {
+    renderer.render(&mut render_tools.0, texture_renderer.depth_texture());
+}
+
+//===============================================================
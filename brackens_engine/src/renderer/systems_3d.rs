@@ -11,6 +11,7 @@ use crate::{
 
 use super::{
     components::{RenderPassTools, Visible},
+    components_2d::{GizmoRenderer, TextureRenderer},
     components_3d::{Model, ModelRenderer},
 };
 
@@ -61,6 +62,29 @@ pub fn sys_render_models(
 
 //===============================================================
 
+/// Gizmos are drawn after everything else, tested against the depth and projection of the
+/// models if any were drawn and the textures otherwise
+pub fn sys_render_gizmos(
+    queue: UniqueView<Queue>,
+    mut renderer: UniqueViewMut<GizmoRenderer>,
+    model_renderer: UniqueView<ModelRenderer>,
+    texture_renderer: UniqueView<TextureRenderer>,
+    mut render_tools: UniqueViewMut<RenderPassTools>,
+) {
+    match model_renderer.has_models() {
+        true => {
+            renderer.set_scene_projection(&queue.0, Some(model_renderer.projection()));
+            renderer.render(&mut render_tools.0, model_renderer.depth_texture());
+        }
+        false => {
+            renderer.set_scene_projection(&queue.0, None);
+            renderer.render(&mut render_tools.0, texture_renderer.depth_texture());
+        }
+    }
+}
+
+//===============================================================
+
 // pub fn sys_check_models(meshes: View<Mesh>, materials: View<Material>) {
 //     let mut checked = vec![];
 //     for (id, (mesh, material)) in (meshes.modified(), &materials).iter().with_id() {
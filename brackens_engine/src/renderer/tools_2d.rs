@@ -1,11 +1,17 @@
 //===============================================================
 
-use brackens_assets::Handle;
-use brackens_renderer::{renderer_2d::RendererTexture, wgpu};
+use brackens_assets::{BitmapFont, Handle};
+use brackens_renderer::{
+    renderer_2d::{
+        texture_settings::TextureSettings, FontArc, PackedAtlas, RendererBitmapFont,
+        RendererTexture, SpriteSheet, SpriteSheetLayout, TextureAtlas,
+    },
+    wgpu, Size,
+};
 use shipyard::{Borrow, EntitiesViewMut, EntityId, IntoBorrow, UniqueView, UniqueViewMut, ViewMut};
 
 use crate::{
-    assets::AssetStorage,
+    assets::{AssetError, AssetStorage, Vfs},
     core_components::{Device, Queue},
     prelude::Transform,
     spatial_tools::TransformBundleViewMut,
@@ -109,6 +115,7 @@ pub fn load_texture(
     renderer: UniqueView<TextureRenderer>,
     device: UniqueView<Device>,
     queue: UniqueView<Queue>,
+    vfs: UniqueView<Vfs>,
 ) -> Handle<RendererTexture> {
     let layout = renderer.get_layout();
     let sampler = match data.sampler {
@@ -116,8 +123,9 @@ pub fn load_texture(
         None => wgpu::SamplerDescriptor::default(),
     };
 
+    let bytes = vfs.read(data.path).unwrap();
     let loaded_texture =
-        RendererTexture::from_file(&device.0, &queue.0, data.path, data.label, &sampler, layout)
+        RendererTexture::from_bytes(&device.0, &queue.0, &bytes, data.label, &sampler, layout)
             .unwrap();
     texture_storage.add_asset(loaded_texture)
 }
@@ -127,23 +135,160 @@ pub fn load_texture_custom_layout(
     mut texture_storage: UniqueViewMut<AssetStorage<RendererTexture>>,
     device: UniqueView<Device>,
     queue: UniqueView<Queue>,
+    vfs: UniqueView<Vfs>,
 ) -> Handle<RendererTexture> {
     let sampler = match data.0.sampler {
         Some(val) => val,
         None => wgpu::SamplerDescriptor::default(),
     };
 
-    let loaded_texture = RendererTexture::from_file(
+    let bytes = vfs.read(data.0.path).unwrap();
+    let loaded_texture =
+        RendererTexture::from_bytes(&device.0, &queue.0, &bytes, data.0.label, &sampler, data.1)
+            .unwrap();
+
+    texture_storage.add_asset(loaded_texture)
+}
+
+/// Run using world.run_with_data where data is a tuple with a LoadTextureDescriptor struct
+/// and how the texture should be split into sprites
+pub fn load_sprite_sheet(
+    data: (LoadTextureDescriptor, SpriteSheetLayout),
+    mut texture_storage: UniqueViewMut<AssetStorage<RendererTexture>>,
+    mut sheet_storage: UniqueViewMut<AssetStorage<SpriteSheet>>,
+    renderer: UniqueView<TextureRenderer>,
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    vfs: UniqueView<Vfs>,
+) -> Result<Handle<SpriteSheet>, AssetError> {
+    let (data, layout) = data;
+    let sampler = data.sampler.unwrap_or_default();
+
+    let bytes = vfs.read(data.path)?;
+    let loaded_texture = RendererTexture::from_bytes(
         &device.0,
         &queue.0,
-        data.0.path,
-        data.0.label,
+        &bytes,
+        data.label,
         &sampler,
-        data.1,
+        renderer.get_layout(),
     )
-    .unwrap();
+    .map_err(|e| AssetError::decode(data.path, e))?;
 
-    texture_storage.add_asset(loaded_texture)
+    let size = loaded_texture.texture.texture.size();
+    let texture = texture_storage.add_asset(loaded_texture);
+
+    Ok(sheet_storage.add_asset(SpriteSheet::new(
+        texture,
+        Size::new(size.width, size.height),
+        &layout,
+    )))
+}
+
+/// Run using world.run_with_data where data is a tuple with a label, a packed atlas and
+/// the settings of the page textures
+pub fn create_atlas(
+    data: (&str, &PackedAtlas, &TextureSettings),
+    mut texture_storage: UniqueViewMut<AssetStorage<RendererTexture>>,
+    mut atlas_storage: UniqueViewMut<AssetStorage<TextureAtlas>>,
+    renderer: UniqueView<TextureRenderer>,
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+) -> anyhow::Result<Handle<TextureAtlas>> {
+    let (label, atlas, settings) = data;
+
+    let pages = atlas
+        .create_textures(&device.0, &queue.0, label, settings, renderer.get_layout())?
+        .into_iter()
+        .map(|page| texture_storage.add_asset(page))
+        .collect();
+
+    Ok(atlas_storage.add_asset(TextureAtlas::new(pages, atlas)))
+}
+
+/// Run using world.run_with_data where data is a tuple with a label, the path of an atlas
+/// index saved with `PackedAtlas::save` and the settings of the page textures
+pub fn load_atlas(
+    data: (&str, &str, &TextureSettings),
+    texture_storage: UniqueViewMut<AssetStorage<RendererTexture>>,
+    atlas_storage: UniqueViewMut<AssetStorage<TextureAtlas>>,
+    renderer: UniqueView<TextureRenderer>,
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    vfs: UniqueView<Vfs>,
+) -> anyhow::Result<Handle<TextureAtlas>> {
+    let (label, index_path, settings) = data;
+    let atlas = PackedAtlas::load_from_vfs(&vfs.0, index_path)?;
+
+    create_atlas(
+        (label, &atlas, settings),
+        texture_storage,
+        atlas_storage,
+        renderer,
+        device,
+        queue,
+    )
+}
+
+/// Run using world.run_with_data where data is the path of a ttf or otf font
+pub fn load_font(
+    path: &str,
+    mut font_storage: UniqueViewMut<AssetStorage<FontArc>>,
+    vfs: UniqueView<Vfs>,
+) -> Result<Handle<FontArc>, AssetError> {
+    font_storage.load_from_vfs(&vfs, path)
+}
+
+/// Run using world.run_with_data where data is a LoadTextureDescriptor struct with the path
+/// of a BMFont `.fnt` file. The sampler and label are used for every page of the font.
+#[allow(clippy::too_many_arguments)]
+pub fn load_bitmap_font(
+    data: LoadTextureDescriptor,
+    mut texture_storage: UniqueViewMut<AssetStorage<RendererTexture>>,
+    mut bitmap_storage: UniqueViewMut<AssetStorage<BitmapFont>>,
+    mut font_storage: UniqueViewMut<AssetStorage<RendererBitmapFont>>,
+    renderer: UniqueView<TextureRenderer>,
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    vfs: UniqueView<Vfs>,
+) -> anyhow::Result<Handle<RendererBitmapFont>> {
+    let sampler = data.sampler.unwrap_or_default();
+
+    let font_handle = bitmap_storage.load_from_vfs(&vfs, data.path)?;
+    let font = bitmap_storage.get_data(&font_handle.id())?.clone();
+
+    let pages = font
+        .pages
+        .iter()
+        .map(|page| {
+            let texture = read_texture(
+                &vfs,
+                &device,
+                &queue,
+                page,
+                data.label,
+                &sampler,
+                renderer.get_layout(),
+            )?;
+            Ok(texture_storage.add_asset(texture))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(font_storage.add_asset(RendererBitmapFont::new(font, pages)))
+}
+
+/// Read an image file through the virtual filesystem and upload it as a texture
+fn read_texture(
+    vfs: &Vfs,
+    device: &Device,
+    queue: &Queue,
+    path: &str,
+    label: &str,
+    sampler: &wgpu::SamplerDescriptor,
+    layout: &wgpu::BindGroupLayout,
+) -> anyhow::Result<RendererTexture> {
+    let bytes = vfs.read(path)?;
+    RendererTexture::from_bytes(&device.0, &queue.0, &bytes, label, sampler, layout)
 }
 
 //===============================================================
@@ -58,7 +58,7 @@ pub fn load_texture_bytes<T: AsRef<str>>(
 //===============================================================
 
 impl AssetLoadable for RendererMaterial {
-    fn load_asset(_world: &World, _path: &str) -> Result<Self, crate::assets::AssetLoadError>
+    fn load_asset(_world: &World, _path: &str) -> Result<Self, crate::assets::AssetError>
     where
         Self: Sized,
     {
@@ -67,7 +67,7 @@ impl AssetLoadable for RendererMaterial {
 }
 
 impl AssetLoadable for RendererMesh {
-    fn load_asset(_world: &World, _path: &str) -> Result<Self, crate::assets::AssetLoadError>
+    fn load_asset(_world: &World, _path: &str) -> Result<Self, crate::assets::AssetError>
     where
         Self: Sized,
     {
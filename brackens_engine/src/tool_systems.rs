@@ -1,13 +1,16 @@
 //===============================================================
 
-use brackens_renderer::{image::DynamicImage, renderer_2d::RendererTexture};
+use brackens_renderer::{
+    image::DynamicImage,
+    renderer_2d::{FontArc, RendererBitmapFont, RendererTexture, SpriteSheet, TextureAtlas},
+};
 
-use brackens_assets::Asset;
+use brackens_assets::{Asset, BitmapFont};
 use shipyard::{
     AllStoragesView, IntoIter, IntoWorkload, UniqueView, UniqueViewMut, ViewMut, Workload,
 };
 
-use crate::assets::AssetStorage;
+use crate::assets::{AssetStorage, Vfs};
 
 use super::{core_components::UpkeepTracker, tool_components::*};
 
@@ -45,8 +48,15 @@ pub fn sys_add_time(
 //===============================================================
 
 pub fn sys_setup_asset_storage(all_storages: AllStoragesView) {
+    all_storages.add_unique(Vfs::default());
+
     register_asset_storage::<DynamicImage>(&all_storages);
     register_asset_storage::<RendererTexture>(&all_storages);
+    register_asset_storage::<SpriteSheet>(&all_storages);
+    register_asset_storage::<TextureAtlas>(&all_storages);
+    register_asset_storage::<FontArc>(&all_storages);
+    register_asset_storage::<BitmapFont>(&all_storages);
+    register_asset_storage::<RendererBitmapFont>(&all_storages);
 }
 
 pub fn register_asset_storage<T: Asset>(all_storages: &AllStoragesView) {
@@ -57,6 +67,11 @@ pub fn wl_reset_asset_storage() -> Workload {
     (
         sys_reset_asset_storage::<DynamicImage>,
         sys_reset_asset_storage::<RendererTexture>,
+        sys_reset_asset_storage::<SpriteSheet>,
+        sys_reset_asset_storage::<TextureAtlas>,
+        sys_reset_asset_storage::<FontArc>,
+        sys_reset_asset_storage::<BitmapFont>,
+        sys_reset_asset_storage::<RendererBitmapFont>,
     )
         .into_workload()
 }
@@ -0,0 +1,553 @@
+//===============================================================
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread::JoinHandle,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use image::RgbaImage;
+use log::{info, warn};
+
+use crate::offscreen::{ReadbackState, TextureReadback};
+
+//===============================================================
+
+/// Save an image as a PNG on a separate thread, creating parent directories as needed
+pub fn save_png_async(image: RgbaImage, path: PathBuf) {
+    std::thread::spawn(move || {
+        if let Some(parent) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                warn!(
+                    "Warning: Failed to create directory '{}': {}",
+                    parent.display(),
+                    e
+                );
+                return;
+            }
+        }
+
+        match image.save_with_format(&path, image::ImageFormat::Png) {
+            Ok(_) => info!("Saved image to '{}'", path.display()),
+            Err(e) => warn!(
+                "Warning: Failed to save image to '{}': {}",
+                path.display(),
+                e
+            ),
+        }
+    });
+}
+
+//===============================================================
+
+/// Requested screenshots of the surface. Requests are captured when the current frame
+/// ends and written as PNG files without blocking rendering.
+///
+/// The surface needs the `COPY_SRC` usage, see `RenderPrefs::allow_capture`.
+pub struct Screenshots {
+    directory: PathBuf,
+    taken: u32,
+
+    requested: Vec<PathBuf>,
+    copied: Vec<(PathBuf, TextureReadback)>,
+    mapping: Vec<(PathBuf, TextureReadback)>,
+}
+
+impl Default for Screenshots {
+    fn default() -> Self {
+        Self::new("screenshots")
+    }
+}
+
+impl Screenshots {
+    //----------------------------------------------
+
+    /// Directory screenshots requested without a path are saved to
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            taken: 0,
+            requested: Vec::new(),
+            copied: Vec::new(),
+            mapping: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn set_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.directory = directory.into();
+    }
+
+    //----------------------------------------------
+
+    /// Take a screenshot at the end of the frame. Returns the path it will be saved to.
+    pub fn request(&mut self) -> PathBuf {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        let path = self
+            .directory
+            .join(format!("screenshot_{}_{}.png", seconds, self.taken));
+        self.taken += 1;
+
+        self.request_path(path.clone());
+        path
+    }
+
+    /// Take a screenshot at the end of the frame and save it to the given path
+    pub fn request_path(&mut self, path: impl Into<PathBuf>) {
+        self.requested.push(path.into());
+    }
+
+    #[inline]
+    pub fn is_requested(&self) -> bool {
+        !self.requested.is_empty()
+    }
+
+    /// Number of screenshots captured but not yet saved
+    #[inline]
+    pub fn in_progress(&self) -> usize {
+        self.copied.len() + self.mapping.len()
+    }
+
+    //----------------------------------------------
+
+    /// Record copies of the texture for all requested screenshots
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        for path in self.requested.drain(..) {
+            match TextureReadback::new(device, encoder, texture) {
+                Some(readback) => self.copied.push((path, readback)),
+                None => warn!(
+                    "Warning: Unable to take screenshot '{}'. Frame cannot be copied.",
+                    path.display()
+                ),
+            }
+        }
+    }
+
+    /// Start reading back captured screenshots. Call after submitting the encoder
+    /// passed to `capture`.
+    pub fn submitted(&mut self) {
+        self.copied
+            .iter_mut()
+            .for_each(|(_, readback)| readback.map());
+        self.mapping.append(&mut self.copied);
+    }
+
+    /// Save screenshots that have finished reading back
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if self.mapping.is_empty() {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        self.mapping
+            .retain_mut(|(path, readback)| match readback.try_read() {
+                ReadbackState::Pending => true,
+                ReadbackState::Ready(image) => {
+                    save_png_async(image, std::mem::take(path));
+                    false
+                }
+                ReadbackState::Failed => false,
+            });
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Number of captured frames that can wait to be read back before rendering blocks
+const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureFormat {
+    /// Numbered PNG files in a directory
+    PngSequence,
+    /// Single uncompressed YUV4MPEG2 video file
+    Y4m,
+}
+
+#[derive(Clone, Debug)]
+pub struct FrameCaptureSettings {
+    /// Directory for PNG sequences or file for Y4M video
+    pub path: PathBuf,
+    pub format: CaptureFormat,
+    pub fps: u32,
+    pub seconds: f32,
+}
+
+impl FrameCaptureSettings {
+    pub fn png_sequence(directory: impl Into<PathBuf>, fps: u32, seconds: f32) -> Self {
+        Self {
+            path: directory.into(),
+            format: CaptureFormat::PngSequence,
+            fps,
+            seconds,
+        }
+    }
+
+    pub fn y4m(path: impl Into<PathBuf>, fps: u32, seconds: f32) -> Self {
+        Self {
+            path: path.into(),
+            format: CaptureFormat::Y4m,
+            fps,
+            seconds,
+        }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        (self.fps as f32 * self.seconds).round() as u32
+    }
+
+    /// Time between captured frames. The game should be advanced by exactly this much
+    /// every frame while capturing.
+    pub fn frame_delta(&self) -> f32 {
+        1. / self.fps.max(1) as f32
+    }
+}
+
+//===============================================================
+
+/// Records a fixed number of frames at a fixed frame rate. Frames are read back in order
+/// and written on a separate thread, blocking rendering only if the writer falls behind.
+pub struct FrameCapture {
+    settings: FrameCaptureSettings,
+    frame_count: u32,
+    frames_captured: u32,
+
+    copied: Vec<TextureReadback>,
+    mapping: VecDeque<TextureReadback>,
+
+    sender: Option<Sender<RgbaImage>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl FrameCapture {
+    //----------------------------------------------
+
+    pub fn new(settings: FrameCaptureSettings) -> Self {
+        info!(
+            "Capturing {} frames at {} fps to '{}'",
+            settings.frame_count(),
+            settings.fps,
+            settings.path.display()
+        );
+
+        let (sender, receiver) = mpsc::channel::<RgbaImage>();
+        let writer_settings = settings.clone();
+
+        let writer = std::thread::spawn(move || {
+            let mut writer = FrameWriter::new(writer_settings);
+            receiver.iter().for_each(|frame| writer.write(frame));
+            writer.finish();
+        });
+
+        Self {
+            frame_count: settings.frame_count(),
+            settings,
+            frames_captured: 0,
+            copied: Vec::new(),
+            mapping: VecDeque::new(),
+            sender: Some(sender),
+            writer: Some(writer),
+        }
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn settings(&self) -> &FrameCaptureSettings {
+        &self.settings
+    }
+
+    #[inline]
+    pub fn frame_delta(&self) -> f32 {
+        self.settings.frame_delta()
+    }
+
+    #[inline]
+    pub fn frames_captured(&self) -> u32 {
+        self.frames_captured
+    }
+
+    #[inline]
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// All frames have been captured. They may still be being written.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.frames_captured >= self.frame_count
+    }
+
+    /// Stop capturing early. Frames captured so far are still written.
+    pub fn stop(&mut self) {
+        self.frame_count = self.frames_captured;
+    }
+
+    //----------------------------------------------
+
+    /// Record a copy of the texture as the next frame
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        if self.is_finished() {
+            return;
+        }
+
+        match TextureReadback::new(device, encoder, texture) {
+            Some(readback) => self.copied.push(readback),
+            None => warn!(
+                "Warning: Unable to capture frame {}. Frame cannot be copied.",
+                self.frames_captured
+            ),
+        }
+
+        self.frames_captured += 1;
+    }
+
+    /// Start reading back captured frames. Call after submitting the encoder passed
+    /// to `capture`.
+    pub fn submitted(&mut self) {
+        self.copied.iter_mut().for_each(|readback| readback.map());
+        self.mapping.extend(self.copied.drain(..));
+    }
+
+    /// Send frames that have finished reading back to the writer, in order
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if self.mapping.is_empty() {
+            return;
+        }
+
+        let maintain = match self.mapping.len() > MAX_FRAMES_IN_FLIGHT {
+            true => wgpu::Maintain::Wait,
+            false => wgpu::Maintain::Poll,
+        };
+        device.poll(maintain);
+
+        while let Some(readback) = self.mapping.front_mut() {
+            match readback.try_read() {
+                ReadbackState::Pending => break,
+                ReadbackState::Ready(frame) => {
+                    if let Some(sender) = &self.sender {
+                        sender.send(frame).ok();
+                    }
+                }
+                ReadbackState::Failed => {}
+            }
+            self.mapping.pop_front();
+        }
+    }
+
+    /// Wait for all captured frames to be read back and written
+    pub fn finish(mut self, device: &wgpu::Device) {
+        self.submitted();
+        while !self.mapping.is_empty() {
+            device.poll(wgpu::Maintain::Wait);
+            self.poll(device);
+        }
+
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            writer.join().ok();
+        }
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Writes captured frames to disk. Runs on the capture's writer thread.
+struct FrameWriter {
+    settings: FrameCaptureSettings,
+    frames_written: u32,
+    size: Option<(u32, u32)>,
+    y4m: Option<BufWriter<File>>,
+    failed: bool,
+}
+
+impl FrameWriter {
+    fn new(settings: FrameCaptureSettings) -> Self {
+        Self {
+            settings,
+            frames_written: 0,
+            size: None,
+            y4m: None,
+            failed: false,
+        }
+    }
+
+    fn write(&mut self, frame: RgbaImage) {
+        if self.failed {
+            return;
+        }
+
+        // Every frame of a capture has to be the same size
+        let size = *self.size.get_or_insert(frame.dimensions());
+        if size != frame.dimensions() {
+            warn!(
+                "Warning: Skipping captured frame {}. Size changed from {:?} to {:?}.",
+                self.frames_written,
+                size,
+                frame.dimensions()
+            );
+            return;
+        }
+
+        let result = match self.settings.format {
+            CaptureFormat::PngSequence => self.write_png(&frame),
+            CaptureFormat::Y4m => self.write_y4m(&frame),
+        };
+
+        match result {
+            Ok(()) => self.frames_written += 1,
+            Err(e) => {
+                warn!(
+                    "Warning: Failed to write captured frames to '{}': {}",
+                    self.settings.path.display(),
+                    e
+                );
+                self.failed = true;
+            }
+        }
+    }
+
+    fn write_png(&mut self, frame: &RgbaImage) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.settings.path)?;
+
+        let path = self
+            .settings
+            .path
+            .join(format!("frame_{:05}.png", self.frames_written));
+        frame.save_with_format(path, image::ImageFormat::Png)?;
+
+        Ok(())
+    }
+
+    fn write_y4m(&mut self, frame: &RgbaImage) -> anyhow::Result<()> {
+        let file = match &mut self.y4m {
+            Some(file) => file,
+            None => {
+                if let Some(parent) = self.settings.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+
+                let mut file = BufWriter::new(File::create(&self.settings.path)?);
+                file.write_all(
+                    y4m_header(frame.width(), frame.height(), self.settings.fps).as_bytes(),
+                )?;
+                self.y4m.insert(file)
+            }
+        };
+
+        writeln!(file, "FRAME")?;
+        file.write_all(&y4m_planes(frame))?;
+
+        Ok(())
+    }
+
+    fn finish(mut self) {
+        if let Some(file) = &mut self.y4m {
+            if let Err(e) = file.flush() {
+                warn!(
+                    "Warning: Failed to write captured frames to '{}': {}",
+                    self.settings.path.display(),
+                    e
+                );
+            }
+        }
+
+        info!(
+            "Finished writing {} captured frames to '{}'",
+            self.frames_written,
+            self.settings.path.display()
+        );
+    }
+}
+
+//===============================================================
+
+/// Stream header of a Y4M file. Uses full resolution chroma so no subsampling is needed.
+fn y4m_header(width: u32, height: u32, fps: u32) -> String {
+    format!("YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444\n", width, height, fps)
+}
+
+/// Convert a pixel to BT.601 studio range YCbCr, which is what Y4M readers assume
+fn rgb_to_ycbcr(rgb: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = rgb.map(|channel| channel as f32);
+
+    [
+        16. + 0.257 * r + 0.504 * g + 0.098 * b,
+        128. - 0.148 * r - 0.291 * g + 0.439 * b,
+        128. + 0.439 * r - 0.368 * g - 0.071 * b,
+    ]
+    .map(|value| value.round() as u8)
+}
+
+/// Data of a frame as separate Y, Cb and Cr planes, ignoring alpha
+fn y4m_planes(frame: &RgbaImage) -> Vec<u8> {
+    let plane_size = (frame.width() * frame.height()) as usize;
+    let mut planes = vec![0; plane_size * 3];
+
+    frame.pixels().enumerate().for_each(|(index, pixel)| {
+        let [y, u, v] = rgb_to_ycbcr([pixel[0], pixel[1], pixel[2]]);
+        planes[index] = y;
+        planes[plane_size + index] = u;
+        planes[plane_size * 2 + index] = v;
+    });
+
+    planes
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn y4m_header_describes_stream() {
+        assert_eq!(
+            y4m_header(640, 360, 30),
+            "YUV4MPEG2 W640 H360 F30:1 Ip A1:1 C444\n"
+        );
+    }
+
+    #[test]
+    fn rgb_to_ycbcr_matches_bt601_studio_range() {
+        assert_eq!(rgb_to_ycbcr([0, 0, 0]), [16, 128, 128]);
+        assert_eq!(rgb_to_ycbcr([255, 255, 255]), [235, 128, 128]);
+        assert_eq!(rgb_to_ycbcr([255, 0, 0]), [82, 90, 240]);
+        assert_eq!(rgb_to_ycbcr([0, 255, 0]), [145, 54, 34]);
+        assert_eq!(rgb_to_ycbcr([0, 0, 255]), [41, 240, 110]);
+    }
+
+    #[test]
+    fn y4m_planes_are_split_and_ignore_alpha() {
+        let frame = RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 255, 255, 255, 0]).unwrap();
+
+        assert_eq!(y4m_planes(&frame), vec![82, 235, 90, 128, 240, 128]);
+    }
+}
@@ -0,0 +1,495 @@
+//===============================================================
+
+use wgpu::util::DeviceExt;
+
+use crate::{
+    pipelines::{
+        bind_group_templates::{BindGroupEntry, BindGroupEntryLayout, BindGroupTemplate},
+        instance_pipeline::RawInstancePipeline,
+        PipelineBuilderDescriptor, Vertex,
+    },
+    render_tools::RenderPassTools,
+    renderer_2d::{
+        renderer_components::{RawTextureVertex, TEXTURE_INDICES, TEXTURE_VERTICES},
+        Texture, TextureDrawBuffer,
+    },
+    Size,
+};
+
+//===============================================================
+
+const GIZMO_SHADER: &str = include_str!("shaders/gizmo_shader.wgsl");
+
+/// Number of lines used to draw circles and spheres
+pub const CIRCLE_SEGMENTS: usize = 32;
+
+//===============================================================
+
+/// Line segment in world space with a thickness in pixels
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawGizmoInstance {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    pub color: [f32; 4],
+    pub thickness: f32,
+}
+impl Vertex for RawGizmoInstance {
+    fn buffer_layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<RawGizmoInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                // Start f32x3
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x3,
+                    offset: 0,
+                    shader_location: 1,
+                },
+                // End f32x3
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x3,
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                },
+                // Color f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                },
+                // Thickness f32
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32,
+                    offset: std::mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                },
+            ],
+        }
+    }
+}
+
+//===============================================================
+
+/// Shapes to draw for a single frame, made of lines in world space. Thickness is in pixels
+/// so lines stay visible at any distance from the camera.
+#[derive(Default)]
+pub struct GizmoBuffer {
+    lines: Vec<RawGizmoInstance>,
+    lines_on_top: Vec<RawGizmoInstance>,
+    on_top: bool,
+}
+
+impl GizmoBuffer {
+    //----------------------------------------------
+
+    /// Shapes drawn inside the closure ignore depth and are drawn on top of the scene
+    pub fn on_top<F: FnOnce(&mut Self)>(&mut self, draw: F) {
+        let previous = self.on_top;
+        self.on_top = true;
+        draw(self);
+        self.on_top = previous;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lines.len() + self.lines_on_top.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.lines_on_top.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.lines_on_top.clear();
+    }
+
+    //----------------------------------------------
+
+    pub fn line(&mut self, start: glam::Vec3, end: glam::Vec3, color: [f32; 4], thickness: f32) {
+        let instance = RawGizmoInstance {
+            start: start.to_array(),
+            end: end.to_array(),
+            color,
+            thickness,
+        };
+
+        match self.on_top {
+            true => self.lines_on_top.push(instance),
+            false => self.lines.push(instance),
+        }
+    }
+
+    /// Lines joining each point to the next
+    pub fn polyline(&mut self, points: &[glam::Vec3], color: [f32; 4], thickness: f32) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color, thickness);
+        }
+    }
+
+    /// Lines joining each point to the next and the last point back to the first
+    pub fn polygon(&mut self, points: &[glam::Vec3], color: [f32; 4], thickness: f32) {
+        self.polyline(points, color, thickness);
+
+        if let (Some(first), Some(last), true) = (points.first(), points.last(), points.len() > 2) {
+            self.line(*last, *first, color, thickness);
+        }
+    }
+
+    /// Rectangle facing along the z axis of the rotation
+    pub fn rect(
+        &mut self,
+        center: glam::Vec3,
+        size: glam::Vec2,
+        rotation: glam::Quat,
+        color: [f32; 4],
+        thickness: f32,
+    ) {
+        let half = size / 2.;
+        let corners = [
+            glam::Vec3::new(-half.x, -half.y, 0.),
+            glam::Vec3::new(half.x, -half.y, 0.),
+            glam::Vec3::new(half.x, half.y, 0.),
+            glam::Vec3::new(-half.x, half.y, 0.),
+        ]
+        .map(|corner| center + rotation * corner);
+
+        self.polygon(&corners, color, thickness);
+    }
+
+    /// Circle facing along the z axis of the rotation
+    pub fn circle(
+        &mut self,
+        center: glam::Vec3,
+        radius: f32,
+        rotation: glam::Quat,
+        color: [f32; 4],
+        thickness: f32,
+    ) {
+        let points = (0..CIRCLE_SEGMENTS)
+            .map(|segment| {
+                let angle = segment as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + rotation * glam::Vec3::new(angle.cos(), angle.sin(), 0.) * radius
+            })
+            .collect::<Vec<_>>();
+
+        self.polygon(&points, color, thickness);
+    }
+
+    /// Line with a head at the end. The head lies in the xy plane unless the arrow points
+    /// along the z axis.
+    pub fn arrow(&mut self, start: glam::Vec3, end: glam::Vec3, color: [f32; 4], thickness: f32) {
+        self.line(start, end, color, thickness);
+
+        let direction = end - start;
+        let length = direction.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let direction = direction / length;
+
+        let side = match direction.z.abs() > 0.99 {
+            true => glam::Vec3::X,
+            false => glam::Vec3::Z.cross(direction).normalize(),
+        };
+
+        let head = length * 0.2;
+        let base = end - direction * head;
+
+        self.line(end, base + side * head / 2., color, thickness);
+        self.line(end, base - side * head / 2., color, thickness);
+    }
+
+    /// Box aligned to the world axes
+    pub fn aabb(&mut self, min: glam::Vec3, max: glam::Vec3, color: [f32; 4], thickness: f32) {
+        let corner = |x: bool, y: bool, z: bool| {
+            glam::Vec3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+
+        for a in [false, true] {
+            for b in [false, true] {
+                self.line(corner(false, a, b), corner(true, a, b), color, thickness);
+                self.line(corner(a, false, b), corner(a, true, b), color, thickness);
+                self.line(corner(a, b, false), corner(a, b, true), color, thickness);
+            }
+        }
+    }
+
+    /// Circles around each axis
+    pub fn sphere(&mut self, center: glam::Vec3, radius: f32, color: [f32; 4], thickness: f32) {
+        let quarter = std::f32::consts::FRAC_PI_2;
+
+        self.circle(center, radius, glam::Quat::IDENTITY, color, thickness);
+        self.circle(
+            center,
+            radius,
+            glam::Quat::from_rotation_x(quarter),
+            color,
+            thickness,
+        );
+        self.circle(
+            center,
+            radius,
+            glam::Quat::from_rotation_y(quarter),
+            color,
+            thickness,
+        );
+    }
+
+    /// Red, green and blue arrows along the x, y and z axes of the transform
+    pub fn axes(&mut self, transform: &glam::Mat4, length: f32, thickness: f32) {
+        let origin = transform.transform_point3(glam::Vec3::ZERO);
+
+        let axes = [
+            (glam::Vec3::X, [1., 0., 0., 1.]),
+            (glam::Vec3::Y, [0., 1., 0., 1.]),
+            (glam::Vec3::Z, [0., 0., 1., 1.]),
+        ];
+
+        for (axis, color) in axes {
+            let end = transform.transform_point3(axis * length);
+            self.arrow(origin, end, color, thickness);
+        }
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GizmoGlobals {
+    projection: [f32; 16],
+    viewport: [f32; 2],
+    _padding: [f32; 2],
+}
+
+//===============================================================
+
+/// Draws the lines of a `GizmoBuffer` after the rest of the scene. Lines are tested against
+/// the depth texture of the scene unless they were drawn on top.
+pub struct GizmoRenderer {
+    pipeline: RawInstancePipeline,
+    pipeline_on_top: RawInstancePipeline,
+
+    globals: GizmoGlobals,
+    global_bind_group: wgpu::BindGroup,
+    global_buffer: wgpu::Buffer,
+
+    instance_buffer: Option<TextureDrawBuffer>,
+    line_count: u32,
+    line_on_top_count: u32,
+}
+
+impl GizmoRenderer {
+    //----------------------------------------------
+
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, window_size: Size<u32>) -> Self {
+        //----------------------------------------------
+
+        let global_template = BindGroupTemplate::new(
+            device,
+            "Gizmo Renderer",
+            vec![BindGroupEntryLayout::buffer(
+                "Gizmo Renderer",
+                wgpu::ShaderStages::VERTEX,
+            )],
+        );
+
+        let globals = GizmoGlobals {
+            projection: screen_projection(window_size),
+            viewport: [window_size.width as f32, window_size.height as f32],
+            _padding: [0., 0.],
+        };
+
+        let (global_bind_group, global_buffer) =
+            global_template.create_bind_group(device, &[BindGroupEntry::Buffer(globals)]);
+
+        //----------------------------------------------
+
+        let mut builder = PipelineBuilderDescriptor {
+            name: "Gizmo Renderer".into(),
+            bind_group_layouts: Some(vec![global_template.get_layout()]),
+            shader: device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Gizmo Renderer - Shader"),
+                source: wgpu::ShaderSource::Wgsl(GIZMO_SHADER.into()),
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            // Gizmos are tested against the depth of the scene without changing it
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment_targets: vec![Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            multiview: None,
+        };
+
+        let pipeline = RawInstancePipeline::from_descriptor::<RawTextureVertex, RawGizmoInstance>(
+            device,
+            &builder,
+            &TEXTURE_VERTICES,
+            &TEXTURE_INDICES,
+        );
+
+        builder.name = "Gizmo Renderer On Top".into();
+        if let Some(depth_stencil) = &mut builder.depth_stencil {
+            depth_stencil.depth_compare = wgpu::CompareFunction::Always;
+        }
+
+        let pipeline_on_top = RawInstancePipeline::new::<RawTextureVertex, RawGizmoInstance>(
+            device,
+            builder,
+            &TEXTURE_VERTICES,
+            &TEXTURE_INDICES,
+        );
+
+        //----------------------------------------------
+
+        Self {
+            pipeline,
+            pipeline_on_top,
+            globals,
+            global_bind_group,
+            global_buffer: global_buffer.unwrap(),
+            instance_buffer: None,
+            line_count: 0,
+            line_on_top_count: 0,
+        }
+
+        //----------------------------------------------
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn projection(&self) -> glam::Mat4 {
+        glam::Mat4::from_cols_array(&self.globals.projection)
+    }
+
+    pub fn set_projection(&mut self, queue: &wgpu::Queue, matrix: &glam::Mat4) {
+        self.globals.projection = matrix.to_cols_array();
+        self.write_globals(queue);
+    }
+
+    /// Update the size of the window used to convert thickness into pixels
+    pub fn resize(&mut self, queue: &wgpu::Queue, new_size: Size<u32>) {
+        self.globals.viewport = [new_size.width as f32, new_size.height as f32];
+        self.write_globals(queue);
+    }
+
+    fn write_globals(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(
+            &self.global_buffer,
+            0,
+            bytemuck::cast_slice(&[self.globals]),
+        );
+    }
+
+    //----------------------------------------------
+
+    /// Upload the lines of the buffer to be drawn by the next call to `render`
+    pub fn process(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, gizmos: &GizmoBuffer) {
+        self.line_count = gizmos.lines.len() as u32;
+        self.line_on_top_count = gizmos.lines_on_top.len() as u32;
+
+        if gizmos.is_empty() {
+            return;
+        }
+
+        // Lines on top are drawn after the depth tested ones from the same buffer
+        let instances = gizmos
+            .lines
+            .iter()
+            .chain(gizmos.lines_on_top.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        let instance_count = instances.len() as u32;
+
+        match &self.instance_buffer {
+            Some(buffer) if buffer.instance_count >= instance_count => {
+                queue.write_buffer(&buffer.instance_buffer, 0, bytemuck::cast_slice(&instances));
+            }
+            _ => {
+                let instance_buffer =
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Gizmo Renderer Instance Buffer"),
+                        contents: bytemuck::cast_slice(&instances),
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    });
+
+                self.instance_buffer = Some(TextureDrawBuffer {
+                    instance_buffer,
+                    instance_count,
+                });
+            }
+        }
+    }
+
+    //----------------------------------------------
+
+    /// Draw the processed lines, tested against the given depth texture of the scene
+    pub fn render(&self, render_tools: &mut RenderPassTools, depth_texture: &Texture) {
+        let buffer = match (
+            &self.instance_buffer,
+            self.line_count + self.line_on_top_count,
+        ) {
+            (Some(buffer), count) if count > 0 => buffer,
+            _ => return,
+        };
+
+        let mut render_pass = self.pipeline.start_render_pass(
+            render_tools,
+            Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        );
+
+        render_pass.set_bind_group(0, &self.global_bind_group);
+
+        if self.line_count > 0 {
+            render_pass.draw_instance_range(&buffer.instance_buffer, 0..self.line_count);
+        }
+
+        if self.line_on_top_count > 0 {
+            render_pass.set_pipeline(&self.pipeline_on_top);
+            render_pass.draw_instance_range(
+                &buffer.instance_buffer,
+                self.line_count..self.line_count + self.line_on_top_count,
+            );
+        }
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+fn screen_projection(size: Size<u32>) -> [f32; 16] {
+    glam::Mat4::orthographic_lh(0., size.width as f32, 0., size.height as f32, 0., 100.)
+        .to_cols_array()
+}
+
+//===============================================================
@@ -1,7 +1,10 @@
 //===============================================================
 
-use log::info;
+use log::{info, warn};
 
+pub mod capture;
+pub mod gizmos;
+pub mod offscreen;
 pub mod pipelines;
 pub mod renderer_2d;
 pub mod renderer_3d;
@@ -53,6 +56,9 @@ pub struct RenderPrefs {
     pub features: wgpu::Features,
     pub limits: wgpu::Limits,
     pub present_mode: wgpu::PresentMode,
+    /// Add the `COPY_SRC` usage to the surface so frames can be captured. Ignored with a
+    /// warning if the surface doesn't support it.
+    pub allow_capture: bool,
 }
 impl Default for RenderPrefs {
     fn default() -> Self {
@@ -63,6 +69,7 @@ impl Default for RenderPrefs {
             features: wgpu::Features::default(),
             limits: wgpu::Limits::default(),
             present_mode: wgpu::PresentMode::default(),
+            allow_capture: false,
         }
     }
 }
@@ -96,7 +103,7 @@ impl RenderComponents {
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: prefs.backends,
-            dx12_shader_compiler: prefs.dx12_compiler,
+            dx12_shader_compiler: prefs.dx12_compiler.clone(),
         });
 
         let surface = unsafe { instance.create_surface(window).unwrap() };
@@ -114,23 +121,31 @@ impl RenderComponents {
 
         //----------------------------------------------
 
-        let (device, queue) = match pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label: Some("Default Device"),
-                features: prefs.features,
-                limits: prefs.limits,
-            },
-            None,
-        )) {
-            Ok(val) => val,
-            Err(e) => panic!("Error creating wgpu device and queue: {}", e),
-        };
+        let (device, queue) = request_device(&adapter, &prefs);
 
         let capabilities = surface.get_capabilities(&adapter);
 
+        let format = capabilities.formats[0];
+
+        // Surface capabilities don't list supported usages in this version of wgpu, so
+        // check the usages the adapter allows for the surface format instead.
+        let capture_supported = adapter
+            .get_texture_format_features(format)
+            .allowed_usages
+            .contains(wgpu::TextureUsages::COPY_SRC);
+
+        if prefs.allow_capture && !capture_supported {
+            warn!("Warning: Surface doesn't support the COPY_SRC usage. Frames can't be captured.");
+        }
+
+        let usage = match prefs.allow_capture && capture_supported {
+            true => wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            false => wgpu::TextureUsages::RENDER_ATTACHMENT,
+        };
+
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: capabilities.formats[0],
+            usage,
+            format,
             width: size.width,
             height: size.height,
             present_mode: prefs.present_mode,
@@ -154,17 +169,115 @@ impl RenderComponents {
     }
 }
 
+fn request_device(adapter: &wgpu::Adapter, prefs: &RenderPrefs) -> (wgpu::Device, wgpu::Queue) {
+    match pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("Default Device"),
+            features: prefs.features,
+            limits: prefs.limits.clone(),
+        },
+        None,
+    )) {
+        Ok(val) => val,
+        Err(e) => panic!("Error creating wgpu device and queue: {}", e),
+    }
+}
+
+//===============================================================
+
+/// Render components without a window or surface, for rendering to textures only.
+/// `config` describes the offscreen target so renderers can be created the same way as
+/// with a surface.
+pub struct HeadlessRenderComponents {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+}
+
+impl HeadlessRenderComponents {
+    /// Requests a fallback (software) adapter first so it works on machines without a GPU,
+    /// then any adapter. Returns None if no adapter is available at all.
+    pub fn new(prefs: RenderPrefs, size: Size<u32>) -> Option<Self> {
+        info!("Creating new headless wgpu components");
+
+        //----------------------------------------------
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: prefs.backends,
+            dx12_shader_compiler: prefs.dx12_compiler.clone(),
+        });
+
+        let request = |force_fallback_adapter| {
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: prefs.power_preferences,
+                force_fallback_adapter,
+                compatible_surface: None,
+            }))
+        };
+
+        let adapter = match request(true).or_else(|| request(false)) {
+            Some(adapter) => adapter,
+            None => {
+                warn!("Warning: No wgpu adapter available for headless rendering.");
+                return None;
+            }
+        };
+
+        info!(
+            "Using adapter '{}' for headless rendering",
+            adapter.get_info().name
+        );
+
+        //----------------------------------------------
+
+        let (device, queue) = request_device(&adapter, &prefs);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: offscreen::OffscreenTarget::USAGES,
+            format: offscreen::OffscreenTarget::DEFAULT_FORMAT,
+            width: size.width,
+            height: size.height,
+            present_mode: prefs.present_mode,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        //----------------------------------------------
+
+        info!("Successfully created headless wgpu components");
+
+        Some(Self {
+            device,
+            queue,
+            config,
+        })
+    }
+
+    /// Create a render target matching the size and format of the config
+    pub fn create_target(&self) -> offscreen::OffscreenTarget {
+        offscreen::OffscreenTarget::new(
+            &self.device,
+            Size::new(self.config.width, self.config.height),
+            self.config.format,
+        )
+    }
+}
+
 //===============================================================
 
 pub mod render_tools {
     use log::warn;
 
-    use crate::Size;
+    use crate::{
+        capture::{FrameCapture, Screenshots},
+        Size,
+    };
 
     //===============================================================
 
     pub struct RenderPassTools {
-        pub surface_texture: wgpu::SurfaceTexture,
+        /// None when rendering to a texture instead of a surface
+        pub surface_texture: Option<wgpu::SurfaceTexture>,
         pub surface_view: wgpu::TextureView,
         pub encoder: wgpu::CommandEncoder,
     }
@@ -191,7 +304,7 @@ pub mod render_tools {
 
         match surface_data {
             Ok(surface_data) => Ok(RenderPassTools {
-                surface_texture: surface_data.0,
+                surface_texture: Some(surface_data.0),
                 surface_view: surface_data.1,
                 encoder,
             }),
@@ -199,9 +312,81 @@ pub mod render_tools {
         }
     }
 
+    /// Start rendering to a texture view, such as an `OffscreenTarget`, instead of a surface
+    pub fn start_texture_render_pass(
+        device: &wgpu::Device,
+        view: wgpu::TextureView,
+    ) -> RenderPassTools {
+        let encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Texture Command Encoder"),
+        });
+
+        RenderPassTools {
+            surface_texture: None,
+            surface_view: view,
+            encoder,
+        }
+    }
+
     pub fn end_render_pass(queue: &wgpu::Queue, render_pass: RenderPassTools) {
         queue.submit(Some(render_pass.encoder.finish()));
-        render_pass.surface_texture.present();
+        if let Some(surface_texture) = render_pass.surface_texture {
+            surface_texture.present();
+        }
+    }
+
+    /// End the render pass, capturing the surface for any requested screenshots first
+    pub fn end_render_pass_with_screenshots(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        render_pass: RenderPassTools,
+        screenshots: &mut Screenshots,
+    ) {
+        end_render_pass_with_capture(device, queue, render_pass, Some(screenshots), None);
+    }
+
+    /// End the render pass, capturing the surface for requested screenshots and the frame
+    /// capture if there is one
+    pub fn end_render_pass_with_capture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        mut render_pass: RenderPassTools,
+        mut screenshots: Option<&mut Screenshots>,
+        mut frame_capture: Option<&mut FrameCapture>,
+    ) {
+        let capturing = screenshots
+            .as_ref()
+            .is_some_and(|screenshots| screenshots.is_requested())
+            || frame_capture
+                .as_ref()
+                .is_some_and(|frame_capture| !frame_capture.is_finished());
+
+        if capturing {
+            match &render_pass.surface_texture {
+                Some(surface_texture) => {
+                    let texture = &surface_texture.texture;
+
+                    if let Some(screenshots) = screenshots.as_mut() {
+                        screenshots.capture(device, &mut render_pass.encoder, texture);
+                    }
+                    if let Some(frame_capture) = frame_capture.as_mut() {
+                        frame_capture.capture(device, &mut render_pass.encoder, texture);
+                    }
+                }
+                None => warn!("Warning: Frames can only be captured when rendering to a surface."),
+            }
+        }
+
+        end_render_pass(queue, render_pass);
+
+        if let Some(screenshots) = screenshots {
+            screenshots.submitted();
+            screenshots.poll(device);
+        }
+        if let Some(frame_capture) = frame_capture {
+            frame_capture.submitted();
+            frame_capture.poll(device);
+        }
     }
 
     //===============================================================
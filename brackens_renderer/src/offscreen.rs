@@ -0,0 +1,379 @@
+//===============================================================
+
+use std::sync::{Arc, Mutex};
+
+use image::RgbaImage;
+use log::warn;
+
+use crate::{
+    render_tools::{self, RenderPassTools},
+    Size,
+};
+
+//===============================================================
+
+/// Texture that can be rendered to instead of a surface and read back to the CPU
+pub struct OffscreenTarget {
+    texture: wgpu::Texture,
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+}
+
+impl OffscreenTarget {
+    //----------------------------------------------
+
+    pub const DEFAULT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    pub const USAGES: wgpu::TextureUsages = wgpu::TextureUsages::RENDER_ATTACHMENT
+        .union(wgpu::TextureUsages::COPY_SRC)
+        .union(wgpu::TextureUsages::TEXTURE_BINDING);
+
+    pub fn new(device: &wgpu::Device, size: Size<u32>, format: wgpu::TextureFormat) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: Self::USAGES,
+            view_formats: &[],
+        });
+
+        Self {
+            texture,
+            size,
+            format,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, new_size: Size<u32>) {
+        *self = Self::new(device, new_size, self.format);
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    //----------------------------------------------
+
+    /// Start a render pass drawing to this target. Finish it with
+    /// `render_tools::end_render_pass` as usual.
+    pub fn start_render_pass(&self, device: &wgpu::Device) -> RenderPassTools {
+        render_tools::start_texture_render_pass(device, self.create_view())
+    }
+
+    /// Copy the current contents of the target to the CPU. Blocks until the GPU is done.
+    pub fn read_frame(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Option<RgbaImage> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Readback Encoder"),
+        });
+
+        let readback = TextureReadback::new(device, &mut encoder, &self.texture)?;
+        queue.submit(Some(encoder.finish()));
+
+        readback.read(device)
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Set by the `map_async` callback once mapping has finished
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+pub enum ReadbackState {
+    /// Buffer hasn't been mapped yet or the GPU hasn't finished with it
+    Pending,
+    Ready(RgbaImage),
+    Failed,
+}
+
+/// Buffer a texture has been copied into, waiting to be read on the CPU.
+/// Supports 8 bit RGBA and BGRA textures, both linear and sRGB. sRGB data is kept encoded
+/// as it is what image files expect.
+pub struct TextureReadback {
+    buffer: wgpu::Buffer,
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+    padded_bytes_per_row: u32,
+    mapped: Option<MapResult>,
+}
+
+impl TextureReadback {
+    //----------------------------------------------
+
+    /// Record a copy of the texture into a new buffer. The copy happens once the encoder is
+    /// submitted. Returns None if the texture format isn't supported.
+    pub fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Option<Self> {
+        let format = texture.format();
+
+        if !Self::is_supported(format) {
+            warn!(
+                "Warning: Cannot read back texture with format {:?}. Only 8 bit RGBA and BGRA formats are supported.",
+                format
+            );
+            return None;
+        }
+
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            warn!("Warning: Cannot read back texture without the COPY_SRC usage.");
+            return None;
+        }
+
+        let size = Size::new(texture.width(), texture.height());
+        let padded_bytes_per_row = Self::padded_bytes_per_row(size.width);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Readback Buffer"),
+            size: padded_bytes_per_row as u64 * size.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            texture.size(),
+        );
+
+        Some(Self {
+            buffer,
+            size,
+            format,
+            padded_bytes_per_row,
+            mapped: None,
+        })
+    }
+
+    pub fn is_supported(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        )
+    }
+
+    /// Rows copied out of a texture have to be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+    fn padded_bytes_per_row(width: u32) -> u32 {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        (width * 4).div_ceil(align) * align
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    /// Start mapping the buffer without waiting. Must only be called once the encoder the
+    /// copy was recorded in has been submitted.
+    pub fn map(&mut self) {
+        if self.mapped.is_some() {
+            return;
+        }
+
+        let mapped = Arc::new(Mutex::new(None));
+        let callback_mapped = mapped.clone();
+
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *callback_mapped.lock().unwrap() = Some(result);
+            });
+
+        self.mapped = Some(mapped);
+    }
+
+    /// Check if mapping has finished. `wgpu::Device::poll` needs to be called for mapping
+    /// to make progress.
+    pub fn try_read(&mut self) -> ReadbackState {
+        let result = match &self.mapped {
+            Some(mapped) => match mapped.lock().unwrap().take() {
+                Some(result) => result,
+                None => return ReadbackState::Pending,
+            },
+            None => return ReadbackState::Pending,
+        };
+
+        if let Err(e) = result {
+            warn!("Warning: Failed to map texture readback buffer: {}", e);
+            return ReadbackState::Failed;
+        }
+
+        let image = self.to_image(&self.buffer.slice(..).get_mapped_range());
+        self.buffer.unmap();
+
+        match image {
+            Some(image) => ReadbackState::Ready(image),
+            None => ReadbackState::Failed,
+        }
+    }
+
+    /// Wait for the copy to finish and convert the data to an image
+    pub fn read(mut self, device: &wgpu::Device) -> Option<RgbaImage> {
+        self.map();
+        device.poll(wgpu::Maintain::Wait);
+
+        match self.try_read() {
+            ReadbackState::Ready(image) => Some(image),
+            ReadbackState::Pending | ReadbackState::Failed => None,
+        }
+    }
+
+    fn to_image(&self, data: &[u8]) -> Option<RgbaImage> {
+        unpad_image(data, self.size, self.padded_bytes_per_row, self.format)
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Remove the row padding from data copied out of a texture and swap BGRA data to RGBA
+fn unpad_image(
+    data: &[u8],
+    size: Size<u32>,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+) -> Option<RgbaImage> {
+    let row_bytes = size.width as usize * 4;
+
+    let mut pixels = Vec::with_capacity(row_bytes * size.height as usize);
+    data.chunks(padded_bytes_per_row as usize)
+        .take(size.height as usize)
+        .for_each(|row| pixels.extend_from_slice(&row[..row_bytes]));
+
+    if matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+
+    RgbaImage::from_raw(size.width, size.height, pixels)
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two rows of three pixels with each row padded to the copy alignment
+    fn padded_data(padded_bytes_per_row: u32) -> Vec<u8> {
+        let mut data = vec![0xAA; padded_bytes_per_row as usize * 2];
+        for (row, start) in [0, padded_bytes_per_row as usize].into_iter().enumerate() {
+            for pixel in 0..3 {
+                let value = (row * 3 + pixel) as u8 * 10;
+                data[start + pixel * 4..start + pixel * 4 + 4].copy_from_slice(&[
+                    value,
+                    value + 1,
+                    value + 2,
+                    255,
+                ]);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn padded_bytes_per_row_is_aligned() {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        assert_eq!(TextureReadback::padded_bytes_per_row(3), align);
+        assert_eq!(TextureReadback::padded_bytes_per_row(align / 4), align);
+        assert_eq!(
+            TextureReadback::padded_bytes_per_row(align / 4 + 1),
+            align * 2
+        );
+    }
+
+    #[test]
+    fn unpad_removes_row_padding() {
+        let padded = TextureReadback::padded_bytes_per_row(3);
+        let data = padded_data(padded);
+
+        let image = unpad_image(
+            &data,
+            Size::new(3, 2),
+            padded,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        )
+        .unwrap();
+
+        assert_eq!(image.as_raw().len(), 3 * 2 * 4);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 1, 2, 255]);
+        assert_eq!(image.get_pixel(2, 0).0, [20, 21, 22, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [30, 31, 32, 255]);
+        assert_eq!(image.get_pixel(2, 1).0, [50, 51, 52, 255]);
+    }
+
+    #[test]
+    fn unpad_swizzles_bgra() {
+        let padded = TextureReadback::padded_bytes_per_row(3);
+        let data = padded_data(padded);
+
+        let image = unpad_image(
+            &data,
+            Size::new(3, 2),
+            padded,
+            wgpu::TextureFormat::Bgra8Unorm,
+        )
+        .unwrap();
+
+        assert_eq!(image.get_pixel(0, 0).0, [2, 1, 0, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [42, 41, 40, 255]);
+    }
+
+    #[test]
+    fn unpad_fails_on_short_data() {
+        let padded = TextureReadback::padded_bytes_per_row(3);
+        let data = padded_data(padded);
+
+        assert!(unpad_image(
+            &data[..padded as usize],
+            Size::new(3, 2),
+            padded,
+            wgpu::TextureFormat::Rgba8Unorm
+        )
+        .is_none());
+    }
+}
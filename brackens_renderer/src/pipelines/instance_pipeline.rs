@@ -1,5 +1,7 @@
 //===============================================================
 
+use std::ops::Range;
+
 use log::info;
 use wgpu::util::DeviceExt;
 
@@ -23,6 +25,17 @@ impl RawInstancePipeline {
         builder: PipelineBuilderDescriptor,
         vertex_data: &[VB],
         index_data: &[u16],
+    ) -> Self {
+        Self::from_descriptor::<VB, IB>(device, &builder, vertex_data, index_data)
+    }
+
+    /// Create a pipeline without consuming the descriptor so it can be used to create
+    /// variations of the same pipeline
+    pub fn from_descriptor<VB: Vertex, IB: Vertex>(
+        device: &wgpu::Device,
+        builder: &PipelineBuilderDescriptor,
+        vertex_data: &[VB],
+        index_data: &[u16],
     ) -> Self {
         info!("Creating new instance pipeline '{}'", &builder.name);
 
@@ -44,8 +57,11 @@ impl RawInstancePipeline {
 
         //----------------------------------------------
 
-        let pipeline =
-            RawPipeline::new(device, &[VB::buffer_layout(), IB::buffer_layout()], builder);
+        let pipeline = RawPipeline::from_descriptor(
+            device,
+            &[VB::buffer_layout(), IB::buffer_layout()],
+            builder,
+        );
 
         //----------------------------------------------
 
@@ -131,6 +147,11 @@ impl<'a> InstancePipelineRenderPass<'a> {
         }
     }
 
+    /// Switch to another pipeline sharing the same vertex and index layout
+    pub fn set_pipeline(&mut self, pipeline: &'a RawInstancePipeline) {
+        self.render_pass.set_pipeline(&pipeline.pipeline);
+    }
+
     pub fn set_bind_group(&mut self, index: u32, bind_group: &'a wgpu::BindGroup) {
         self.render_pass.set_bind_group(index, bind_group);
     }
@@ -144,6 +165,17 @@ impl<'a> InstancePipelineRenderPass<'a> {
         self.render_pass
             .draw_index(0..self.index_count, 0..instance_count);
     }
+
+    /// Draw a range of the instances in a buffer
+    pub fn draw_instance_range(
+        &mut self,
+        instance_buffer: &'a wgpu::Buffer,
+        instances: Range<u32>,
+    ) {
+        self.render_pass
+            .set_vertex_buffers(Some(instance_buffer), 1);
+        self.render_pass.draw_index(0..self.index_count, instances);
+    }
 }
 
 //===============================================================
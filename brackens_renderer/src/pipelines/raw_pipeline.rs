@@ -19,13 +19,23 @@ impl RawPipeline {
         device: &wgpu::Device,
         buffers: &[wgpu::VertexBufferLayout],
         builder: PipelineBuilderDescriptor,
+    ) -> Self {
+        Self::from_descriptor(device, buffers, &builder)
+    }
+
+    /// Create a pipeline without consuming the descriptor so it can be used to create
+    /// variations of the same pipeline
+    pub fn from_descriptor(
+        device: &wgpu::Device,
+        buffers: &[wgpu::VertexBufferLayout],
+        builder: &PipelineBuilderDescriptor,
     ) -> Self {
         info!("Creating new pipeline '{}'", &builder.name);
 
         //----------------------------------------------
 
-        let bind_group_layouts = match builder.bind_group_layouts {
-            Some(val) => val,
+        let bind_group_layouts = match &builder.bind_group_layouts {
+            Some(val) => val.clone(),
             None => vec![],
         };
 
@@ -44,7 +54,7 @@ impl RawPipeline {
                 buffers,
             },
             primitive: builder.primitive,
-            depth_stencil: builder.depth_stencil,
+            depth_stencil: builder.depth_stencil.clone(),
             multisample: builder.multisample,
             fragment: Some(wgpu::FragmentState {
                 module: &builder.shader,
@@ -61,7 +71,7 @@ impl RawPipeline {
         //----------------------------------------------
 
         Self {
-            name: builder.name,
+            name: builder.name.clone(),
             pipeline,
         }
     }
@@ -70,6 +80,11 @@ impl RawPipeline {
         &self.name
     }
 
+    #[inline]
+    pub fn pipeline(&self) -> &wgpu::RenderPipeline {
+        &self.pipeline
+    }
+
     pub fn start_render_pass<'a: 'b, 'b>(
         &'a self,
         render_tools: &'a mut RenderPassTools,
@@ -104,6 +119,10 @@ pub struct PipelineRenderPass<'a> {
 impl<'a> PipelineRenderPass<'a> {
     //----------------------------------------------
 
+    pub fn set_pipeline(&mut self, pipeline: &'a RawPipeline) {
+        self.render_pass.set_pipeline(&pipeline.pipeline);
+    }
+
     pub fn set_bind_group(&mut self, index: u32, bind_group: &'a wgpu::BindGroup) {
         self.render_pass.set_bind_group(index, bind_group, &[]);
     }
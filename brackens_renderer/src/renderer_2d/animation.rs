@@ -0,0 +1,444 @@
+//===============================================================
+
+use std::{collections::HashMap, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+//===============================================================
+
+/// What happens once the last frame of a clip has played
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationMode {
+    /// Stop on the last frame
+    Once,
+    /// Start again from the first frame
+    #[default]
+    Loop,
+    /// Play backwards to the first frame, then forwards again
+    PingPong,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnimationFrame {
+    /// Index of the sprite in the sprite sheet
+    pub index: usize,
+    /// Seconds the frame is shown for
+    pub duration: f32,
+    /// Event sent every time the frame is reached
+    pub event: Option<String>,
+}
+
+impl AnimationFrame {
+    pub fn new(index: usize, duration: f32) -> Self {
+        Self {
+            index,
+            duration,
+            event: None,
+        }
+    }
+}
+
+//===============================================================
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationClip {
+    pub frames: Vec<AnimationFrame>,
+    pub mode: AnimationMode,
+}
+
+impl AnimationClip {
+    pub fn new(frames: Vec<AnimationFrame>, mode: AnimationMode) -> Self {
+        Self { frames, mode }
+    }
+
+    /// Clip showing each sprite in the range for the same amount of time
+    pub fn from_range(sprites: Range<usize>, frame_duration: f32, mode: AnimationMode) -> Self {
+        Self {
+            frames: sprites
+                .map(|index| AnimationFrame::new(index, frame_duration))
+                .collect(),
+            mode,
+        }
+    }
+
+    /// Send an event every time the frame at the given position in the clip is reached
+    pub fn with_event(mut self, frame: usize, event: &str) -> Self {
+        if let Some(frame) = self.frames.get_mut(frame) {
+            frame.event = Some(event.to_string());
+        }
+        self
+    }
+
+    /// Length of one play through the frames
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration.max(0.)).sum()
+    }
+}
+
+//===============================================================
+
+/// Event sent by a frame of a playing clip
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnimationEvent {
+    pub clip: String,
+    /// Position of the frame in the clip
+    pub frame: usize,
+    pub name: String,
+}
+
+//===============================================================
+
+/// Plays named clips, giving the sprite index to show and the events of the frames passed
+/// during the last update.
+#[derive(Clone, Debug)]
+pub struct SpriteAnimator {
+    clips: HashMap<String, AnimationClip>,
+    current: Option<String>,
+    frame: usize,
+    elapsed: f32,
+    reversed: bool,
+    playing: bool,
+    finished: bool,
+    speed: f32,
+    events: Vec<AnimationEvent>,
+    /// Events of clips started since the last update
+    queued_events: Vec<AnimationEvent>,
+}
+
+impl Default for SpriteAnimator {
+    fn default() -> Self {
+        Self {
+            clips: HashMap::new(),
+            current: None,
+            frame: 0,
+            elapsed: 0.,
+            reversed: false,
+            playing: false,
+            finished: false,
+            speed: 1.,
+            events: vec![],
+            queued_events: vec![],
+        }
+    }
+}
+
+impl SpriteAnimator {
+    //----------------------------------------------
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.add_clip(name, clip);
+        self
+    }
+
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip) {
+        self.clips.insert(name.to_string(), clip);
+    }
+
+    pub fn remove_clip(&mut self, name: &str) -> Option<AnimationClip> {
+        if self.current.as_deref() == Some(name) {
+            self.stop();
+        }
+        self.clips.remove(name)
+    }
+
+    #[inline]
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+
+    //----------------------------------------------
+
+    /// Play a clip from its first frame. Does nothing if the clip is already playing.
+    /// Returns false if there is no clip with the name.
+    pub fn play(&mut self, name: &str) -> bool {
+        if self.playing && self.current.as_deref() == Some(name) {
+            return true;
+        }
+        self.restart(name)
+    }
+
+    /// Play a clip from its first frame, even if it is already playing.
+    /// Returns false if there is no clip with the name.
+    pub fn restart(&mut self, name: &str) -> bool {
+        if !self.clips.contains_key(name) {
+            return false;
+        }
+
+        self.current = Some(name.to_string());
+        self.frame = 0;
+        self.elapsed = 0.;
+        self.reversed = false;
+        self.playing = true;
+        self.finished = false;
+
+        if let Some(event) = self.frame_event() {
+            self.queued_events.push(event);
+        }
+
+        true
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        if self.current.is_some() && !self.finished {
+            self.playing = true;
+        }
+    }
+
+    /// Stop playing and clear the current clip
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.frame = 0;
+        self.elapsed = 0.;
+        self.reversed = false;
+        self.playing = false;
+        self.finished = false;
+    }
+
+    /// Multiplier applied to the time passed into `update`
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.);
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// If a clip played with `AnimationMode::Once` has reached its last frame
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    #[inline]
+    pub fn current_clip(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Position of the current frame in the current clip
+    #[inline]
+    pub fn current_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Index in the sprite sheet of the sprite to show
+    pub fn sprite_index(&self) -> Option<usize> {
+        let clip = self.clips.get(self.current.as_ref()?)?;
+        clip.frames.get(self.frame).map(|frame| frame.index)
+    }
+
+    /// Events of the frames reached during the last update, including the first frame of
+    /// clips started before it
+    #[inline]
+    pub fn events(&self) -> &[AnimationEvent] {
+        &self.events
+    }
+
+    //----------------------------------------------
+
+    /// Advance the current clip by the given seconds. Returns true if the frame changed.
+    pub fn update(&mut self, delta: f32) -> bool {
+        self.events = std::mem::take(&mut self.queued_events);
+
+        if !self.playing {
+            return false;
+        }
+
+        let clip = match self.current.as_ref().and_then(|name| self.clips.get(name)) {
+            Some(clip) => clip,
+            None => return false,
+        };
+
+        // Clips without any length would never advance
+        if clip.duration() <= 0. {
+            return false;
+        }
+
+        let mode = clip.mode;
+        let frame_count = clip.frames.len();
+        let start_frame = self.frame;
+
+        self.elapsed += delta * self.speed;
+
+        loop {
+            let duration = self.frame_duration();
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+
+            match self.next_frame(mode, frame_count) {
+                Some(next) => self.frame = next,
+                None => {
+                    self.elapsed = 0.;
+                    self.playing = false;
+                    self.finished = true;
+                    break;
+                }
+            }
+
+            if let Some(event) = self.frame_event() {
+                self.events.push(event);
+            }
+        }
+
+        self.frame != start_frame
+    }
+
+    /// Position of the frame after the current one. None when a clip played once is over.
+    fn next_frame(&mut self, mode: AnimationMode, frame_count: usize) -> Option<usize> {
+        let last = frame_count.saturating_sub(1);
+
+        match mode {
+            AnimationMode::Once => match self.frame < last {
+                true => Some(self.frame + 1),
+                false => None,
+            },
+            AnimationMode::Loop => Some((self.frame + 1) % frame_count.max(1)),
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return Some(0);
+                }
+
+                if self.reversed && self.frame == 0 {
+                    self.reversed = false;
+                } else if !self.reversed && self.frame >= last {
+                    self.reversed = true;
+                }
+
+                match self.reversed {
+                    true => Some(self.frame - 1),
+                    false => Some(self.frame + 1),
+                }
+            }
+        }
+    }
+
+    fn frame_duration(&self) -> f32 {
+        self.current
+            .as_ref()
+            .and_then(|name| self.clips.get(name))
+            .and_then(|clip| clip.frames.get(self.frame))
+            .map(|frame| frame.duration.max(0.))
+            .unwrap_or(0.)
+    }
+
+    /// Event of the current frame, if it has one
+    fn frame_event(&self) -> Option<AnimationEvent> {
+        let name = self.current.as_ref()?;
+        let event = self
+            .clips
+            .get(name)?
+            .frames
+            .get(self.frame)?
+            .event
+            .as_ref()?;
+
+        Some(AnimationEvent {
+            clip: name.clone(),
+            frame: self.frame,
+            name: event.clone(),
+        })
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animator(clip: AnimationClip) -> SpriteAnimator {
+        let mut animator = SpriteAnimator::new().with_clip("clip", clip);
+        assert!(animator.play("clip"));
+        animator
+    }
+
+    fn frames_after_updates(animator: &mut SpriteAnimator, updates: usize) -> Vec<usize> {
+        (0..updates)
+            .map(|_| {
+                animator.update(1.);
+                animator.current_frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        let mut animator = animator(AnimationClip::from_range(4..7, 1., AnimationMode::PingPong));
+
+        assert_eq!(
+            frames_after_updates(&mut animator, 6),
+            vec![1, 2, 1, 0, 1, 2]
+        );
+        assert_eq!(animator.sprite_index(), Some(6));
+        assert!(animator.is_playing());
+    }
+
+    #[test]
+    fn ping_pong_with_single_frame_stays() {
+        let mut animator = animator(AnimationClip::from_range(3..4, 1., AnimationMode::PingPong));
+
+        assert_eq!(frames_after_updates(&mut animator, 3), vec![0, 0, 0]);
+        assert_eq!(animator.sprite_index(), Some(3));
+        assert!(animator.is_playing());
+    }
+
+    #[test]
+    fn once_finishes_on_last_frame() {
+        let mut animator = animator(AnimationClip::from_range(0..3, 1., AnimationMode::Once));
+
+        animator.update(2.5);
+        assert_eq!(animator.current_frame(), 2);
+        assert!(!animator.is_finished());
+
+        animator.update(1.);
+        assert_eq!(animator.current_frame(), 2);
+        assert!(animator.is_finished());
+        assert!(!animator.is_playing());
+
+        // Finished clips can't be resumed, only played again
+        animator.resume();
+        assert!(!animator.is_playing());
+    }
+
+    #[test]
+    fn large_delta_sends_events_of_skipped_frames() {
+        let clip = AnimationClip::from_range(0..4, 1., AnimationMode::Loop)
+            .with_event(0, "start")
+            .with_event(1, "step")
+            .with_event(2, "hit");
+        let mut animator = animator(clip);
+
+        animator.update(3.5);
+        assert_eq!(animator.current_frame(), 3);
+
+        let names = animator
+            .events()
+            .iter()
+            .map(|event| (event.frame, event.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![(0, "start"), (1, "step"), (2, "hit")]);
+
+        // Events only last for one update
+        animator.update(0.1);
+        assert!(animator.events().is_empty());
+    }
+}
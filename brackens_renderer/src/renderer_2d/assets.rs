@@ -1,11 +1,15 @@
 //===============================================================
 
+use std::borrow::Cow;
+
 use anyhow::Result;
 use brackens_assets::Asset;
 use image::GenericImageView;
 
 use crate::Size;
 
+use super::texture_settings::TextureSettings;
+
 //===============================================================
 
 pub struct Texture {
@@ -18,6 +22,17 @@ impl Asset for Texture {
     fn asset_name(&self) -> &str {
         "Wgpu Texture"
     }
+
+    fn byte_size(&self) -> usize {
+        let size = self.texture.size();
+        let block_size = self.texture.format().block_size(None).unwrap_or(4);
+        let (block_width, block_height) = self.texture.format().block_dimensions();
+
+        ((size.width / block_width)
+            * (size.height / block_height)
+            * size.depth_or_array_layers
+            * block_size) as usize
+    }
     // fn asset_name() -> &'static str {
     //     "Wgpu Texture"
     // }
@@ -52,11 +67,27 @@ impl Texture {
         img: &image::DynamicImage,
         label: Option<&str>,
         sampler: &wgpu::SamplerDescriptor,
+    ) -> Result<Self> {
+        let settings = TextureSettings::default();
+        Self::from_image_with_settings(device, queue, img, label, &settings, Some(sampler))
+    }
+
+    /// Create a texture using the given import settings. The label of the settings is used
+    /// over the given label, and the sampler described by the settings is replaced by the
+    /// given sampler if there is one.
+    pub fn from_image_with_settings(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        label: Option<&str>,
+        settings: &TextureSettings,
+        sampler: Option<&wgpu::SamplerDescriptor>,
     ) -> Result<Self> {
         //----------------------------------------------
 
         let rgba = img.to_rgba8();
         let dimensions = img.dimensions();
+        let mip_level_count = settings.mip_level_count(dimensions.0, dimensions.1);
 
         let size = wgpu::Extent3d {
             width: dimensions.0,
@@ -67,39 +98,28 @@ impl Texture {
         //----------------------------------------------
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label,
+            label: settings.label.as_deref().or(label),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format: settings.format(),
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         });
 
         //----------------------------------------------
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: None,
-            },
-            size,
-        );
+        Self::write_mip_levels(queue, &texture, &rgba);
 
         //----------------------------------------------
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let sampler = device.create_sampler(sampler);
+        let sampler = match sampler {
+            Some(sampler) => device.create_sampler(sampler),
+            None => device.create_sampler(&settings.sampler_descriptor()),
+        };
 
         //----------------------------------------------
 
@@ -112,6 +132,65 @@ impl Texture {
         //----------------------------------------------
     }
 
+    /// Upload new pixels to the texture, regenerating its mip levels. The image must be
+    /// the same size as the texture.
+    pub fn write_image(&self, queue: &wgpu::Queue, img: &image::DynamicImage) -> Result<()> {
+        let size = self.texture.size();
+        let dimensions = img.dimensions();
+
+        if dimensions != (size.width, size.height) {
+            anyhow::bail!(
+                "image is {}x{} but the texture is {}x{}",
+                dimensions.0,
+                dimensions.1,
+                size.width,
+                size.height
+            );
+        }
+
+        Self::write_mip_levels(queue, &self.texture, &img.to_rgba8());
+        Ok(())
+    }
+
+    fn write_mip_levels(queue: &wgpu::Queue, texture: &wgpu::Texture, rgba: &image::RgbaImage) {
+        let dimensions = rgba.dimensions();
+
+        for mip_level in 0..texture.mip_level_count() {
+            let width = (dimensions.0 >> mip_level).max(1);
+            let height = (dimensions.1 >> mip_level).max(1);
+
+            let level = match mip_level {
+                0 => Cow::Borrowed(rgba),
+                _ => Cow::Owned(image::imageops::resize(
+                    rgba,
+                    width,
+                    height,
+                    image::imageops::FilterType::Triangle,
+                )),
+            };
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    aspect: wgpu::TextureAspect::All,
+                    texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                &level,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * width),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
     pub fn create_depth_texture(
@@ -167,6 +246,10 @@ impl Asset for RendererTexture {
     fn asset_name(&self) -> &str {
         "Renderer Texture"
     }
+
+    fn byte_size(&self) -> usize {
+        self.texture.byte_size()
+    }
 }
 
 impl RendererTexture {
@@ -246,6 +329,40 @@ impl RendererTexture {
         }
     }
 
+    pub fn from_bytes_with_settings(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        label: &str,
+        settings: &TextureSettings,
+        sampler: Option<&wgpu::SamplerDescriptor>,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        let img = image::load_from_memory(bytes)?;
+        Self::from_image_with_settings(device, queue, &img, Some(label), settings, sampler, layout)
+    }
+
+    pub fn from_image_with_settings(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        label: Option<&str>,
+        settings: &TextureSettings,
+        sampler: Option<&wgpu::SamplerDescriptor>,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        match Texture::from_image_with_settings(device, queue, img, label, settings, sampler) {
+            Ok(texture) => Ok(Self::from_texture(device, texture, layout)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Upload new pixels to the texture. See `Texture::write_image`.
+    #[inline]
+    pub fn write_image(&self, queue: &wgpu::Queue, img: &image::DynamicImage) -> Result<()> {
+        self.texture.write_image(queue, img)
+    }
+
     pub fn from_texture(
         device: &wgpu::Device,
         texture: Texture,
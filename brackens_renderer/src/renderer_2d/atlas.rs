@@ -0,0 +1,673 @@
+//===============================================================
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use brackens_assets::{handle::HandleInner, Asset, Handle, Vfs};
+use image::{DynamicImage, RgbaImage};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::Size;
+
+use super::{sprite_sheet::TextureRect, texture_settings::TextureSettings, RendererTexture};
+
+//===============================================================
+
+/// Packs many small images into a few large pages so they can be drawn with the same
+/// texture, and so in the same draw call.
+pub struct AtlasBuilder {
+    max_size: u32,
+    padding: u32,
+    extrusion: u32,
+    images: Vec<(String, RgbaImage)>,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new(2048)
+    }
+}
+
+impl AtlasBuilder {
+    //----------------------------------------------
+
+    /// Pages are never wider or taller than `max_size`
+    pub fn new(max_size: u32) -> Self {
+        Self {
+            max_size,
+            padding: 2,
+            extrusion: 1,
+            images: vec![],
+        }
+    }
+
+    /// Empty pixels between images and around the edge of each page
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Pixels the edges of each image are repeated outwards by, so filtering near the edge
+    /// of an image doesn't sample its neighbours
+    pub fn with_extrusion(mut self, extrusion: u32) -> Self {
+        self.extrusion = extrusion;
+        self
+    }
+
+    /// Add an image to be packed. Replaces any image already added with the same name.
+    pub fn add(&mut self, name: &str, image: &DynamicImage) {
+        self.add_rgba(name, image.to_rgba8());
+    }
+
+    pub fn add_rgba(&mut self, name: &str, image: RgbaImage) {
+        match self
+            .images
+            .iter_mut()
+            .find(|(existing, _)| existing == name)
+        {
+            Some((_, existing)) => {
+                warn!("Warning: Replacing image '{}' already added to atlas", name);
+                *existing = image;
+            }
+            None => self.images.push((name.to_string(), image)),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    //----------------------------------------------
+
+    /// Pack all added images. Fails if an image is too large to fit on a page.
+    pub fn build(&self) -> Result<PackedAtlas> {
+        let border = self.extrusion * 2;
+        let mut packer = ShelfPacker::new(self.max_size, self.padding);
+
+        // Packing tallest images first keeps shelves tight
+        let mut order = (0..self.images.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let (_, a) = &self.images[*a];
+            let (_, b) = &self.images[*b];
+            b.height().cmp(&a.height()).then(b.width().cmp(&a.width()))
+        });
+
+        let mut placements = vec![(0, 0, 0); self.images.len()];
+        for index in order {
+            let (name, image) = &self.images[index];
+
+            placements[index] = match packer.insert(image.width() + border, image.height() + border)
+            {
+                Some(placement) => placement,
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "image '{}' ({}x{}) does not fit on an atlas page of size {}",
+                        name,
+                        image.width(),
+                        image.height(),
+                        self.max_size
+                    ))
+                }
+            };
+        }
+
+        //----------------------------------------------
+
+        let mut pages = packer
+            .page_sizes()
+            .into_iter()
+            .map(|(width, height)| RgbaImage::new(width, height))
+            .collect::<Vec<_>>();
+
+        let entries = self
+            .images
+            .iter()
+            .zip(placements)
+            .map(|((name, image), (page, x, y))| {
+                blit_extruded(&mut pages[page], image, x, y, self.extrusion);
+
+                AtlasEntry {
+                    name: name.clone(),
+                    page,
+                    rect: [
+                        x + self.extrusion,
+                        y + self.extrusion,
+                        image.width(),
+                        image.height(),
+                    ],
+                }
+            })
+            .collect();
+
+        Ok(PackedAtlas { pages, entries })
+    }
+
+    //----------------------------------------------
+}
+
+/// Copy an image onto a page, repeating its edge pixels outwards
+fn blit_extruded(page: &mut RgbaImage, image: &RgbaImage, x: u32, y: u32, extrusion: u32) {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    for page_y in 0..height + extrusion * 2 {
+        let source_y = page_y.saturating_sub(extrusion).min(height - 1);
+
+        for page_x in 0..width + extrusion * 2 {
+            let source_x = page_x.saturating_sub(extrusion).min(width - 1);
+            page.put_pixel(x + page_x, y + page_y, *image.get_pixel(source_x, source_y));
+        }
+    }
+}
+
+//===============================================================
+
+/// Places rects in rows of similar height, starting a new page once one is full
+struct ShelfPacker {
+    max_size: u32,
+    padding: u32,
+    pages: Vec<PackerPage>,
+}
+
+#[derive(Default)]
+struct PackerPage {
+    shelves: Vec<Shelf>,
+    next_shelf_y: u32,
+    used_width: u32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+impl ShelfPacker {
+    fn new(max_size: u32, padding: u32) -> Self {
+        Self {
+            max_size,
+            padding,
+            pages: vec![],
+        }
+    }
+
+    /// Returns the page and position of the rect, or None if it can never fit
+    fn insert(&mut self, width: u32, height: u32) -> Option<(usize, u32, u32)> {
+        let limit = self.max_size.saturating_sub(self.padding);
+        if self.padding + width > limit || self.padding + height > limit {
+            return None;
+        }
+
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some((x, y)) = Self::insert_in_page(page, width, height, limit, self.padding) {
+                return Some((index, x, y));
+            }
+        }
+
+        let mut page = PackerPage {
+            next_shelf_y: self.padding,
+            ..Default::default()
+        };
+        let (x, y) = Self::insert_in_page(&mut page, width, height, limit, self.padding)?;
+        self.pages.push(page);
+
+        Some((self.pages.len() - 1, x, y))
+    }
+
+    fn insert_in_page(
+        page: &mut PackerPage,
+        width: u32,
+        height: u32,
+        limit: u32,
+        padding: u32,
+    ) -> Option<(u32, u32)> {
+        // Use the shortest existing shelf the rect fits in
+        let shelf = page
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && shelf.next_x + width <= limit)
+            .min_by_key(|shelf| shelf.height);
+
+        let shelf = match shelf {
+            Some(shelf) => shelf,
+            None => {
+                if page.next_shelf_y + height > limit {
+                    return None;
+                }
+
+                page.shelves.push(Shelf {
+                    y: page.next_shelf_y,
+                    height,
+                    next_x: padding,
+                });
+                page.next_shelf_y += height + padding;
+                page.shelves.last_mut().unwrap()
+            }
+        };
+
+        let position = (shelf.next_x, shelf.y);
+        shelf.next_x += width + padding;
+        page.used_width = page.used_width.max(shelf.next_x);
+
+        Some(position)
+    }
+
+    /// Smallest size each page can be while holding all of its rects
+    fn page_sizes(&self) -> Vec<(u32, u32)> {
+        self.pages
+            .iter()
+            .map(|page| (page.used_width.max(1), page.next_shelf_y.max(1)))
+            .collect()
+    }
+}
+
+//===============================================================
+
+/// Where an image was placed in an atlas
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasEntry {
+    pub name: String,
+    pub page: usize,
+    /// Pixel rect (x, y, width, height) of the image in its page, without extrusion
+    pub rect: [u32; 4],
+}
+
+/// Saved next to the pages of an atlas to describe where each image is
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasIndex {
+    /// File names of the pages, relative to the index
+    pub pages: Vec<String>,
+    pub entries: Vec<AtlasEntry>,
+}
+
+//===============================================================
+
+/// Pages of packed images still on the CPU, ready to be uploaded or saved
+pub struct PackedAtlas {
+    pub pages: Vec<RgbaImage>,
+    pub entries: Vec<AtlasEntry>,
+}
+
+impl PackedAtlas {
+    //----------------------------------------------
+
+    pub fn page_size(&self, page: usize) -> Option<Size<u32>> {
+        self.pages
+            .get(page)
+            .map(|page| Size::new(page.width(), page.height()))
+    }
+
+    /// Texture coordinates of an entry in its page
+    pub fn uv(&self, entry: &AtlasEntry) -> TextureRect {
+        match self.page_size(entry.page) {
+            Some(size) => TextureRect::from_pixels(entry.rect, size),
+            None => TextureRect::FULL,
+        }
+    }
+
+    /// Create a texture for each page. Mipmaps can still bleed between images once they
+    /// are smaller than the padding.
+    pub fn create_textures(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        settings: &TextureSettings,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Vec<RendererTexture>> {
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                RendererTexture::from_image_with_settings(
+                    device,
+                    queue,
+                    &DynamicImage::ImageRgba8(page.clone()),
+                    Some(&format!("{} - Page {}", label, index)),
+                    settings,
+                    None,
+                    layout,
+                )
+            })
+            .collect()
+    }
+
+    //----------------------------------------------
+
+    /// Save each page as `<name>_<page>.png` and the index as `<name>.atlas.ron` in the
+    /// directory. Returns the path of the index.
+    pub fn save(&self, directory: &Path, name: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(directory)?;
+
+        let mut index = AtlasIndex {
+            pages: vec![],
+            entries: self.entries.clone(),
+        };
+
+        for (page_index, page) in self.pages.iter().enumerate() {
+            let file_name = format!("{}_{}.png", name, page_index);
+            page.save_with_format(directory.join(&file_name), image::ImageFormat::Png)?;
+            index.pages.push(file_name);
+        }
+
+        let index_path = directory.join(format!("{}.atlas.ron", name));
+        let data = ron::ser::to_string_pretty(&index, ron::ser::PrettyConfig::default())?;
+        std::fs::write(&index_path, data)?;
+
+        info!("Saved atlas to '{}'", index_path.display());
+
+        Ok(index_path)
+    }
+
+    /// Load an atlas saved with `save` from disk, reading the pages next to the index
+    pub fn load(index_path: &Path) -> Result<Self> {
+        let directory = index_path.parent().unwrap_or(Path::new(""));
+
+        Self::from_bytes(&std::fs::read(index_path)?, |file_name| {
+            Ok(std::fs::read(directory.join(file_name))?)
+        })
+    }
+
+    /// Load an atlas saved with `save` through a virtual filesystem, reading the pages next
+    /// to the index
+    pub fn load_from_vfs(vfs: &Vfs, index_path: &str) -> Result<Self> {
+        let directory = match index_path.rfind('/') {
+            Some(index) => &index_path[..=index],
+            None => "",
+        };
+
+        Self::from_bytes(&vfs.read(index_path)?, |file_name| {
+            Ok(vfs.read(&format!("{}{}", directory, file_name))?)
+        })
+    }
+
+    /// Load an atlas from the bytes of its index. The bytes of each page are read using its
+    /// file name from the index.
+    pub fn from_bytes(
+        index: &[u8],
+        mut read_page: impl FnMut(&str) -> Result<Vec<u8>>,
+    ) -> Result<Self> {
+        let index: AtlasIndex = ron::de::from_bytes(index)?;
+
+        let pages = index
+            .pages
+            .iter()
+            .map(|file_name| Ok(image::load_from_memory(&read_page(file_name)?)?.to_rgba8()))
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(entry) = index.entries.iter().find(|entry| entry.page >= pages.len()) {
+            return Err(anyhow::anyhow!(
+                "atlas entry '{}' is on missing page {}",
+                entry.name,
+                entry.page
+            ));
+        }
+
+        Ok(Self {
+            pages,
+            entries: index.entries,
+        })
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Area of an atlas page holding a single image. Draw it by using the texture and rect on
+/// a textured component. Everything on the same page is drawn in a single batch.
+#[derive(Clone)]
+pub struct SubTexture {
+    pub texture: Handle<RendererTexture>,
+    pub rect: TextureRect,
+    /// Size of the image in pixels
+    pub size: Size<u32>,
+}
+
+/// Atlas uploaded to the GPU, with a sub texture for every packed image
+pub struct TextureAtlas {
+    pages: Vec<Handle<RendererTexture>>,
+    sub_textures: Vec<SubTexture>,
+    names: HashMap<String, usize>,
+}
+
+impl Asset for TextureAtlas {
+    fn asset_name(&self) -> &str {
+        "Texture Atlas"
+    }
+
+    fn byte_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.sub_textures.len() * std::mem::size_of::<SubTexture>()
+    }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        self.pages.iter().map(|page| page.inner_id()).collect()
+    }
+}
+
+impl TextureAtlas {
+    //----------------------------------------------
+
+    /// Create from the handles of the textures created from each page of the packed atlas
+    pub fn new(pages: Vec<Handle<RendererTexture>>, atlas: &PackedAtlas) -> Self {
+        let mut names = HashMap::new();
+        let mut sub_textures = Vec::with_capacity(atlas.entries.len());
+
+        for entry in &atlas.entries {
+            let texture = match pages.get(entry.page) {
+                Some(texture) => texture.clone(),
+                None => {
+                    warn!(
+                        "Warning: No texture given for page {} of atlas entry '{}'",
+                        entry.page, entry.name
+                    );
+                    continue;
+                }
+            };
+
+            names.insert(entry.name.clone(), sub_textures.len());
+            sub_textures.push(SubTexture {
+                texture,
+                rect: atlas.uv(entry),
+                size: Size::new(entry.rect[2], entry.rect[3]),
+            });
+        }
+
+        Self {
+            pages,
+            sub_textures,
+            names,
+        }
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn pages(&self) -> &[Handle<RendererTexture>] {
+        &self.pages
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sub_textures.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sub_textures.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&SubTexture> {
+        self.sub_textures.get(*self.names.get(name)?)
+    }
+
+    /// Sub textures are in the same order the images were added to the builder
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&SubTexture> {
+        self.sub_textures.get(index)
+    }
+
+    #[inline]
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.keys().map(|name| name.as_str())
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use brackens_assets::DirectorySource;
+    use image::Rgba;
+
+    use super::*;
+
+    // Image filled with a color made from its index so images can be told apart
+    fn image(index: u32, width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([index as u8, 100, 200, 255]))
+    }
+
+    fn builder(max_size: u32, sizes: &[(u32, u32)]) -> AtlasBuilder {
+        let mut builder = AtlasBuilder::new(max_size);
+        for (index, (width, height)) in sizes.iter().enumerate() {
+            builder.add_rgba(&index.to_string(), image(index as u32, *width, *height));
+        }
+        builder
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("brackens_atlas_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn packed_images_fit_without_overlap() {
+        let sizes = [
+            (10, 12),
+            (30, 5),
+            (7, 7),
+            (16, 16),
+            (3, 20),
+            (25, 9),
+            (12, 12),
+            (1, 1),
+            (40, 8),
+            (9, 30),
+        ];
+        let atlas = builder(64, &sizes).build().unwrap();
+        let extrusion = 1;
+        let padding = 2;
+
+        assert_eq!(atlas.entries.len(), sizes.len());
+
+        for page in &atlas.pages {
+            assert!(page.width() <= 64 && page.height() <= 64);
+        }
+
+        for (index, entry) in atlas.entries.iter().enumerate() {
+            let [x, y, width, height] = entry.rect;
+            let page = &atlas.pages[entry.page];
+
+            assert_eq!((width, height), sizes[index]);
+            assert!(x >= padding + extrusion && y >= padding + extrusion);
+            assert!(x + width + extrusion <= page.width());
+            assert!(y + height + extrusion <= page.height());
+
+            // Image and its extruded border hold the image's pixels
+            for page_y in y - extrusion..y + height + extrusion {
+                for page_x in x - extrusion..x + width + extrusion {
+                    assert_eq!(page.get_pixel(page_x, page_y)[0], index as u8);
+                }
+            }
+
+            // Extruded rects on the same page are separated by the padding
+            for other in &atlas.entries[index + 1..] {
+                if other.page != entry.page {
+                    continue;
+                }
+                let [other_x, other_y, other_width, other_height] = other.rect;
+                let apart = x + width + extrusion + padding <= other_x - extrusion
+                    || other_x + other_width + extrusion + padding <= x - extrusion
+                    || y + height + extrusion + padding <= other_y - extrusion
+                    || other_y + other_height + extrusion + padding <= y - extrusion;
+                assert!(apart, "'{}' overlaps '{}'", entry.name, other.name);
+            }
+        }
+    }
+
+    #[test]
+    fn full_pages_start_new_pages() {
+        // Each image takes up most of a page with its border and padding
+        let atlas = builder(32, &[(20, 20), (20, 20), (20, 20)])
+            .build()
+            .unwrap();
+
+        assert_eq!(atlas.pages.len(), 3);
+        let mut pages = atlas
+            .entries
+            .iter()
+            .map(|entry| entry.page)
+            .collect::<Vec<_>>();
+        pages.sort();
+        assert_eq!(pages, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn oversized_image_rejected() {
+        // Padding on both sides and the extrusion leave 26 pixels for the image
+        assert!(builder(32, &[(26, 26)]).build().is_ok());
+        assert!(builder(32, &[(8, 8), (27, 4)]).build().is_err());
+        assert!(builder(32, &[(4, 27)]).build().is_err());
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let atlas = builder(32, &[(20, 20), (6, 4), (20, 10)]).build().unwrap();
+        assert!(atlas.pages.len() > 1);
+
+        let dir = temp_dir("round_trip");
+        let index_path = atlas.save(&dir.join("atlases"), "sprites").unwrap();
+
+        let loaded = PackedAtlas::load(&index_path).unwrap();
+        assert_eq!(loaded.entries, atlas.entries);
+        assert_eq!(loaded.pages, atlas.pages);
+
+        let mut vfs = Vfs::new();
+        vfs.mount("res", DirectorySource::new(&dir));
+        let loaded = PackedAtlas::load_from_vfs(&vfs, "res/atlases/sprites.atlas.ron").unwrap();
+        assert_eq!(loaded.entries, atlas.entries);
+        assert_eq!(loaded.pages, atlas.pages);
+
+        // Pages the entries refer to must exist
+        std::fs::write(
+            dir.join("atlases/sprites.atlas.ron"),
+            "(pages: [], entries: [(name: \"a\", page: 0, rect: (0, 0, 1, 1))])",
+        )
+        .unwrap();
+        assert!(PackedAtlas::load(&index_path).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,210 @@
+//===============================================================
+
+use brackens_assets::{handle::HandleInner, Asset, BitmapFont, Handle};
+use log::warn;
+
+use crate::Size;
+
+use super::{
+    text::{TextAlign, TextVerticalAlign},
+    RawTextureInstance, RendererTexture, TextureID, TextureRect,
+};
+
+//===============================================================
+
+/// Text to be drawn with a `RendererBitmapFont`
+#[derive(Clone, Debug)]
+pub struct BitmapTextSection<'a> {
+    pub text: &'a str,
+    /// Size of each pixel of the font. Whole numbers keep pixel art fonts crisp.
+    pub scale: f32,
+    pub color: [f32; 4],
+    /// Horizontal alignment of the lines around the origin of the transform
+    pub align: TextAlign,
+    /// Vertical alignment of the lines around the origin of the transform
+    pub vertical_align: TextVerticalAlign,
+    pub transform: glam::Mat4,
+}
+
+impl<'a> BitmapTextSection<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            scale: 1.,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            transform: glam::Mat4::IDENTITY,
+        }
+    }
+}
+
+//===============================================================
+
+/// Bitmap font with the textures of its pages. Glyphs are drawn as texture instances by
+/// the texture renderer, batched with other textures using the same page.
+pub struct RendererBitmapFont {
+    font: BitmapFont,
+    pages: Vec<Handle<RendererTexture>>,
+}
+
+impl Asset for RendererBitmapFont {
+    fn asset_name(&self) -> &str {
+        "Renderer Bitmap Font"
+    }
+
+    fn byte_size(&self) -> usize {
+        self.font.byte_size() + self.pages.len() * std::mem::size_of::<Handle<RendererTexture>>()
+    }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        self.pages.iter().map(|page| page.inner_id()).collect()
+    }
+}
+
+impl RendererBitmapFont {
+    //----------------------------------------------
+
+    /// Pages must be in the same order as the pages of the font
+    pub fn new(font: BitmapFont, pages: Vec<Handle<RendererTexture>>) -> Self {
+        if pages.len() != font.pages.len() {
+            warn!(
+                "Warning: Bitmap font '{}' has {} pages but was given {} textures",
+                font.face,
+                font.pages.len(),
+                pages.len()
+            );
+        }
+
+        Self { font, pages }
+    }
+
+    #[inline]
+    pub fn font(&self) -> &BitmapFont {
+        &self.font
+    }
+
+    #[inline]
+    pub fn pages(&self) -> &[Handle<RendererTexture>] {
+        &self.pages
+    }
+
+    //----------------------------------------------
+
+    /// Width of a line in font pixels, including kerning
+    fn line_width(&self, line: &str) -> i32 {
+        let mut width = 0;
+        let mut previous = None;
+
+        for character in line.chars() {
+            let glyph = match self.font.glyph(character) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if let Some(previous) = previous {
+                width += self.font.kerning(previous, character);
+            }
+            width += glyph.x_advance;
+            previous = Some(character);
+        }
+
+        width
+    }
+
+    /// Width and height of the text in pixels, before scaling
+    pub fn measure(&self, text: &str) -> Size<f32> {
+        let (width, lines) = text.lines().fold((0, 0), |(width, lines), line| {
+            (width.max(self.line_width(line)), lines + 1)
+        });
+
+        Size::new(width as f32, (lines.max(1) * self.font.line_height) as f32)
+    }
+
+    //----------------------------------------------
+
+    /// Texture instances of every visible glyph in the text, along with the page texture
+    /// they are drawn from. Characters missing from the font are skipped.
+    pub fn instances(&self, section: &BitmapTextSection) -> Vec<(TextureID, RawTextureInstance)> {
+        let line_height = self.font.line_height as f32;
+        let page_size = Size::new(self.font.scale_w, self.font.scale_h);
+
+        let height = self.measure(section.text).height;
+        let mut line_top = match section.vertical_align {
+            TextVerticalAlign::Top => 0.,
+            TextVerticalAlign::Center => -height / 2.,
+            TextVerticalAlign::Bottom => -height,
+        };
+
+        let mut instances = Vec::with_capacity(section.text.len());
+
+        for line in section.text.lines() {
+            let width = self.line_width(line) as f32;
+            let mut pen = match section.align {
+                TextAlign::Left => 0.,
+                TextAlign::Center => -width / 2.,
+                TextAlign::Right => -width,
+            };
+
+            let mut previous = None;
+
+            for character in line.chars() {
+                let glyph = match self.font.glyph(character) {
+                    Some(glyph) => glyph,
+                    None => continue,
+                };
+
+                if let Some(previous) = previous {
+                    pen += self.font.kerning(previous, character) as f32;
+                }
+                previous = Some(character);
+
+                let texture = self.pages.get(glyph.page);
+
+                if let (Some(texture), true) = (texture, glyph.width > 0 && glyph.height > 0) {
+                    let width = glyph.width as f32;
+                    let height = glyph.height as f32;
+
+                    // Glyphs are laid out with y going down from the top of the first line
+                    let center = glam::Vec3::new(
+                        pen + glyph.x_offset as f32 + width / 2.,
+                        -(line_top + glyph.y_offset as f32 + height / 2.),
+                        0.,
+                    ) * section.scale;
+
+                    let transform = section.transform
+                        * glam::Mat4::from_scale_rotation_translation(
+                            glam::Vec3::new(width * section.scale, height * section.scale, 1.),
+                            glam::Quat::IDENTITY,
+                            center,
+                        );
+
+                    let rect = TextureRect::from_pixels(
+                        [glyph.x, glyph.y, glyph.width, glyph.height],
+                        page_size,
+                    );
+
+                    instances.push((
+                        texture.id(),
+                        RawTextureInstance {
+                            tex_coord_top_left: rect.top_left,
+                            tex_coord_bottom_right: rect.bottom_right,
+                            transform: transform.to_cols_array(),
+                            color: section.color,
+                        },
+                    ));
+                }
+
+                pen += glyph.x_advance as f32;
+            }
+
+            line_top += line_height;
+        }
+
+        instances
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
@@ -0,0 +1,71 @@
+//===============================================================
+
+use serde::{Deserialize, Serialize};
+
+//===============================================================
+
+/// How a texture is combined with what has already been drawn. Everything other than
+/// `Opaque` is drawn after opaque textures, sorted back to front, without writing depth.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Replace what is behind. Transparent pixels are still written.
+    #[default]
+    Opaque,
+    /// Blend using the alpha channel
+    Alpha,
+    /// Blend using the alpha channel with colors already multiplied by alpha
+    Premultiplied,
+    /// Add colors to what is behind, weighted by alpha. Used for glows and particles.
+    Additive,
+    /// Multiply colors with what is behind, so white leaves it unchanged. Used for shadows
+    /// and tinting.
+    Multiply,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Opaque,
+        BlendMode::Alpha,
+        BlendMode::Premultiplied,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+    ];
+
+    #[inline]
+    pub fn is_transparent(&self) -> bool {
+        *self != BlendMode::Opaque
+    }
+
+    pub fn blend_state(&self) -> wgpu::BlendState {
+        // Keep the alpha already in the target for modes that only change color
+        let keep_alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        match self {
+            BlendMode::Opaque => wgpu::BlendState::REPLACE,
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            BlendMode::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+}
+
+//===============================================================
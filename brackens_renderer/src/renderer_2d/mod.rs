@@ -3,14 +3,30 @@
 use brackens_assets::HandleID;
 
 pub use {
+    animation::{AnimationClip, AnimationEvent, AnimationFrame, AnimationMode, SpriteAnimator},
     assets::{RendererTexture, Texture},
+    atlas::{AtlasBuilder, PackedAtlas, SubTexture, TextureAtlas},
+    bitmap_font::{BitmapTextSection, RendererBitmapFont},
+    blend::BlendMode,
+    nine_slice::{NineSliceBorder, NineSliceLayout, SliceMode},
     renderer::{Renderer2D, TextureRenderer},
     renderer_components::{RawTextureInstance, RendererDescriptor2D, TextureDrawBuffer},
+    sprite_sheet::{SpriteSheet, SpriteSheetLayout, TextureRect},
+    text::{FontArc, FontId, TextAlign, TextRenderer, TextSection, TextSpace, TextVerticalAlign},
+    texture_settings::TextureSettings,
 };
 
+pub mod animation;
 pub mod assets;
+pub mod atlas;
+pub mod bitmap_font;
+pub mod blend;
+pub mod nine_slice;
 pub mod renderer;
 pub mod renderer_components;
+pub mod sprite_sheet;
+pub mod text;
+pub mod texture_settings;
 pub mod tools;
 
 //===============================================================
@@ -0,0 +1,410 @@
+//===============================================================
+
+use serde::{Deserialize, Serialize};
+
+use crate::Size;
+
+use super::{RawTextureInstance, TextureRect};
+
+//===============================================================
+
+/// Width in pixels of each border of a nine slice texture
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NineSliceBorder {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl NineSliceBorder {
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    pub fn uniform(border: u32) -> Self {
+        Self::new(border, border, border, border)
+    }
+}
+
+//--------------------------------------------------
+
+/// How the edges and center of a nine slice fill their area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SliceMode {
+    #[default]
+    Stretch,
+    /// Repeat the slice at its original size, cutting off the last repeat to fit
+    Tile,
+}
+
+//===============================================================
+
+/// Splits a texture into four corners that keep their size, four edges and a center that
+/// fill the rest of the area.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NineSliceLayout {
+    pub border: NineSliceBorder,
+    /// Size of each pixel of the borders when drawn
+    pub border_scale: f32,
+    pub edges: SliceMode,
+    pub center: SliceMode,
+}
+
+impl NineSliceLayout {
+    pub fn new(border: NineSliceBorder) -> Self {
+        Self {
+            border,
+            border_scale: 1.,
+            edges: SliceMode::Stretch,
+            center: SliceMode::Stretch,
+        }
+    }
+
+    //----------------------------------------------
+
+    /// Instances filling an area of the given size centered on the transform. `rect` is the
+    /// area of the texture containing all nine slices and `texture_size` the size of the
+    /// whole texture in pixels. Borders are scaled down to fit if the area is too small.
+    pub fn instances(
+        &self,
+        rect: TextureRect,
+        texture_size: Size<u32>,
+        size: glam::Vec2,
+        transform: glam::Mat4,
+        color: [f32; 4],
+    ) -> Vec<RawTextureInstance> {
+        let source = glam::Vec2::new(
+            (rect.bottom_right[0] - rect.top_left[0]).abs() * texture_size.width as f32,
+            (rect.bottom_right[1] - rect.top_left[1]).abs() * texture_size.height as f32,
+        );
+
+        if source.x <= 0. || source.y <= 0. || size.x <= 0. || size.y <= 0. {
+            return Vec::new();
+        }
+
+        let columns = self.segments(
+            size.x,
+            source.x,
+            self.border.left as f32,
+            self.border.right as f32,
+        );
+        let rows = self.segments(
+            size.y,
+            source.y,
+            self.border.top as f32,
+            self.border.bottom as f32,
+        );
+
+        let mut instances = Vec::new();
+
+        for (row_index, row) in rows.iter().enumerate() {
+            for (column_index, column) in columns.iter().enumerate() {
+                // Slices with no border or no room left aren't drawn
+                if column.length <= 0. || row.length <= 0. {
+                    continue;
+                }
+
+                let mode = match (column_index == 1, row_index == 1) {
+                    (true, true) => self.center,
+                    (false, false) => SliceMode::Stretch,
+                    _ => self.edges,
+                };
+
+                let mut column_tiles = match (mode, column_index == 1) {
+                    (SliceMode::Tile, true) => column.tiles(),
+                    _ => vec![*column],
+                };
+                let mut row_tiles = match (mode, row_index == 1) {
+                    (SliceMode::Tile, true) => row.tiles(),
+                    _ => vec![*row],
+                };
+
+                // A center tiled on both axes multiplies the repeats of each
+                if column_tiles.len() * row_tiles.len() > MAX_TILES {
+                    column_tiles = vec![*column];
+                    row_tiles = vec![*row];
+                }
+
+                for row_tile in &row_tiles {
+                    for column_tile in &column_tiles {
+                        instances.push(Self::instance(
+                            rect,
+                            size,
+                            transform,
+                            color,
+                            column_tile,
+                            row_tile,
+                        ));
+                    }
+                }
+            }
+        }
+
+        instances
+    }
+
+    /// The three slices along one axis, with the borders shrunk to fit if needed
+    fn segments(&self, length: f32, source: f32, start: f32, end: f32) -> [Segment; 3] {
+        // Borders can't take up more than the whole source
+        let start = start.min(source);
+        let end = end.min(source - start);
+
+        let mut start_length = start * self.border_scale;
+        let mut end_length = end * self.border_scale;
+
+        let borders = start_length + end_length;
+        if borders > length {
+            start_length *= length / borders;
+            end_length *= length / borders;
+        }
+
+        let middle_source = source - start - end;
+
+        [
+            Segment {
+                position: 0.,
+                length: start_length,
+                uv: [0., start / source],
+                tile_length: start_length,
+            },
+            Segment {
+                position: start_length,
+                length: length - start_length - end_length,
+                uv: [start / source, (source - end) / source],
+                tile_length: middle_source * self.border_scale,
+            },
+            Segment {
+                position: length - end_length,
+                length: end_length,
+                uv: [(source - end) / source, 1.],
+                tile_length: end_length,
+            },
+        ]
+    }
+
+    fn instance(
+        rect: TextureRect,
+        size: glam::Vec2,
+        transform: glam::Mat4,
+        color: [f32; 4],
+        column: &Segment,
+        row: &Segment,
+    ) -> RawTextureInstance {
+        let lerp = |axis: usize, amount: f32| {
+            rect.top_left[axis] + (rect.bottom_right[axis] - rect.top_left[axis]) * amount
+        };
+
+        // Rows are positioned down from the top of the area
+        let center = glam::Vec3::new(
+            column.position + column.length / 2. - size.x / 2.,
+            size.y / 2. - (row.position + row.length / 2.),
+            0.,
+        );
+
+        let transform = transform
+            * glam::Mat4::from_scale_rotation_translation(
+                glam::Vec3::new(column.length, row.length, 1.),
+                glam::Quat::IDENTITY,
+                center,
+            );
+
+        RawTextureInstance {
+            tex_coord_top_left: [lerp(0, column.uv[0]), lerp(1, row.uv[0])],
+            tex_coord_bottom_right: [lerp(0, column.uv[1]), lerp(1, row.uv[1])],
+            transform: transform.to_cols_array(),
+            color,
+        }
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+/// Part of the area along one axis along with the part of the source it shows
+#[derive(Clone, Copy)]
+struct Segment {
+    position: f32,
+    length: f32,
+    uv: [f32; 2],
+    /// Length of the source when drawn at its original size
+    tile_length: f32,
+}
+
+/// Most repeats a single slice is tiled with
+const MAX_TILES: usize = 4096;
+
+impl Segment {
+    /// Repeats of the source filling the segment, with the last one cut off to fit. Sources
+    /// smaller than a pixel or needing more than `MAX_TILES` repeats are stretched instead.
+    fn tiles(&self) -> Vec<Segment> {
+        if self.length <= 0. || self.tile_length < 1. {
+            return vec![*self];
+        }
+
+        let count = (self.length / self.tile_length).ceil() as usize;
+        if count > MAX_TILES {
+            return vec![*self];
+        }
+
+        let uv_length = self.uv[1] - self.uv[0];
+
+        (0..count)
+            .map(|index| {
+                let position = index as f32 * self.tile_length;
+                let length = self.tile_length.min(self.length - position);
+
+                Segment {
+                    position: self.position + position,
+                    length,
+                    uv: [
+                        self.uv[0],
+                        self.uv[0] + uv_length * length / self.tile_length,
+                    ],
+                    tile_length: self.tile_length,
+                }
+            })
+            .collect()
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECT: TextureRect = TextureRect {
+        top_left: [0., 0.],
+        bottom_right: [1., 1.],
+    };
+    const TEXTURE_SIZE: Size<u32> = Size {
+        width: 30,
+        height: 30,
+    };
+
+    fn assert_close(value: [f32; 2], expected: [f32; 2]) {
+        assert!(
+            (value[0] - expected[0]).abs() < 1e-5 && (value[1] - expected[1]).abs() < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            value
+        );
+    }
+
+    fn instances(layout: &NineSliceLayout, width: f32, height: f32) -> Vec<RawTextureInstance> {
+        layout.instances(
+            RECT,
+            TEXTURE_SIZE,
+            glam::Vec2::new(width, height),
+            glam::Mat4::IDENTITY,
+            [1.; 4],
+        )
+    }
+
+    #[test]
+    fn slices_cover_their_part_of_the_texture() {
+        let layout = NineSliceLayout::new(NineSliceBorder::uniform(10));
+        let instances = instances(&layout, 90., 60.);
+
+        assert_eq!(instances.len(), 9);
+
+        let third = 1. / 3.;
+        let cuts = [0., third, 2. * third, 1.];
+
+        for (index, instance) in instances.iter().enumerate() {
+            let (row, column) = (index / 3, index % 3);
+
+            assert_close(instance.tex_coord_top_left, [cuts[column], cuts[row]]);
+            assert_close(
+                instance.tex_coord_bottom_right,
+                [cuts[column + 1], cuts[row + 1]],
+            );
+        }
+
+        // Corners keep their size while the center fills the rest
+        assert_eq!(instances[0].transform[0], 10.);
+        assert_eq!(instances[0].transform[5], 10.);
+        assert_eq!(instances[4].transform[0], 70.);
+        assert_eq!(instances[4].transform[5], 40.);
+    }
+
+    #[test]
+    fn borders_shrink_to_fit() {
+        let mut layout = NineSliceLayout::new(NineSliceBorder::new(10, 30, 10, 10));
+        layout.border_scale = 2.;
+
+        let columns = layout.segments(40., 60., 10., 30.);
+        assert_eq!(columns[0].length, 10.);
+        assert_eq!(columns[1].length, 0.);
+        assert_eq!(columns[2].length, 30.);
+        assert_eq!(columns[2].position, 10.);
+
+        // Borders wider than the source are limited to it
+        let columns = layout.segments(100., 30., 40., 10.);
+        assert_close(columns[0].uv, [0., 1.]);
+        assert_eq!(columns[2].length, 0.);
+
+        // Slices left without room aren't drawn
+        let layout = NineSliceLayout::new(NineSliceBorder::uniform(10));
+        assert_eq!(instances(&layout, 10., 10.).len(), 4);
+    }
+
+    #[test]
+    fn last_tile_is_cut_off() {
+        let layout = NineSliceLayout::new(NineSliceBorder::uniform(10));
+        let tiles = layout.segments(55., 30., 10., 10.)[1].tiles();
+
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[0].position, 10.);
+        assert_close(tiles[0].uv, [1. / 3., 2. / 3.]);
+
+        assert_eq!(tiles[3].position, 40.);
+        assert_eq!(tiles[3].length, 5.);
+        assert_close(tiles[3].uv, [1. / 3., 0.5]);
+    }
+
+    #[test]
+    fn center_tiles_on_both_axes() {
+        let mut layout = NineSliceLayout::new(NineSliceBorder::uniform(10));
+        layout.center = SliceMode::Tile;
+
+        // 3 by 3 tiles in the center along with the 8 other slices
+        assert_eq!(instances(&layout, 50., 50.).len(), 17);
+
+        // Each axis stays under the limit, but not the tiles of the whole center
+        let repeats = (MAX_TILES as f32).sqrt() + 1.;
+        let size = 20. + repeats * 10.;
+        assert_eq!(instances(&layout, size, size).len(), 9);
+    }
+
+    #[test]
+    fn tiny_or_numerous_tiles_stretch() {
+        let segment = Segment {
+            position: 0.,
+            length: 100.,
+            uv: [0., 1.],
+            tile_length: 0.5,
+        };
+        assert_eq!(segment.tiles().len(), 1);
+
+        let segment = Segment {
+            length: (MAX_TILES + 1) as f32,
+            tile_length: 1.,
+            ..segment
+        };
+        assert_eq!(segment.tiles().len(), 1);
+
+        let segment = Segment {
+            length: MAX_TILES as f32,
+            ..segment
+        };
+        assert_eq!(segment.tiles().len(), MAX_TILES);
+    }
+}
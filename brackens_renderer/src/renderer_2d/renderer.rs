@@ -1,6 +1,6 @@
 //===============================================================
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
 use wgpu::util::DeviceExt;
 
@@ -23,7 +23,7 @@ use super::{
         TEXTURE_INDICES, TEXTURE_VERTICES,
     },
     tools::TEXTURE_SHADER,
-    RendererTexture, Texture,
+    BlendMode, RendererTexture, Texture,
 };
 
 //===============================================================
@@ -34,6 +34,8 @@ where
 {
     // Underlying pipeline
     pipeline: RawInstancePipeline,
+    // Variations of the pipeline for each transparent blend mode
+    blend_pipelines: Vec<(BlendMode, RawInstancePipeline)>,
 
     // Bind Group Layout textures (and texture instance stuff) must adhere to
     texture_bind_group_layout: wgpu::BindGroupLayout,
@@ -157,7 +159,7 @@ where
             ..
         } = builder;
 
-        let new_builder = PipelineBuilderDescriptor {
+        let mut new_builder = PipelineBuilderDescriptor {
             name,
             bind_group_layouts: Some(vec![layout, &texture_bind_group_layout]),
             shader,
@@ -168,15 +170,51 @@ where
             multiview,
         };
 
-        let pipeline = RawInstancePipeline::new::<RawTextureVertex, RawTextureInstance>(
+        let pipeline = RawInstancePipeline::from_descriptor::<RawTextureVertex, RawTextureInstance>(
             device,
-            new_builder,
+            &new_builder,
             &TEXTURE_VERTICES,
             &TEXTURE_INDICES,
         );
 
+        //----------------------------------------------
+
+        // Transparent textures are tested against depth but don't write to it so those
+        // behind can still be seen
+        if let Some(depth_stencil) = &mut new_builder.depth_stencil {
+            depth_stencil.depth_write_enabled = false;
+        }
+
+        let opaque_targets = new_builder.fragment_targets.clone();
+
+        let blend_pipelines = BlendMode::ALL
+            .into_iter()
+            .filter(|blend_mode| blend_mode.is_transparent())
+            .map(|blend_mode| {
+                new_builder.fragment_targets = opaque_targets
+                    .iter()
+                    .map(|target| {
+                        target.clone().map(|target| wgpu::ColorTargetState {
+                            blend: Some(blend_mode.blend_state()),
+                            ..target
+                        })
+                    })
+                    .collect();
+
+                let pipeline = RawInstancePipeline::from_descriptor::<
+                    RawTextureVertex,
+                    RawTextureInstance,
+                >(
+                    device, &new_builder, &TEXTURE_VERTICES, &TEXTURE_INDICES
+                );
+
+                (blend_mode, pipeline)
+            })
+            .collect();
+
         Self {
             pipeline,
+            blend_pipelines,
             texture_bind_group_layout,
             phantom_data: PhantomData,
             global_bind_group,
@@ -252,6 +290,42 @@ where
             );
         }
     }
+
+    fn blend_pipeline(&self, blend_mode: BlendMode) -> &RawInstancePipeline {
+        self.blend_pipelines
+            .iter()
+            .find(|(mode, _)| *mode == blend_mode)
+            .map(|(_, pipeline)| pipeline)
+            .unwrap_or(&self.pipeline)
+    }
+
+    /// Draw ranges of a single instance buffer in order, switching blend mode as needed.
+    /// Should be drawn after opaque textures.
+    pub fn render_transparent(
+        &self,
+        render_tools: &mut RenderPassTools,
+        instance_buffer: &wgpu::Buffer,
+        draw_calls: &[(&wgpu::BindGroup, BlendMode, Range<u32>)],
+        depth_texture: Option<wgpu::RenderPassDepthStencilAttachment>,
+    ) {
+        if draw_calls.is_empty() {
+            return;
+        }
+
+        let mut render_pass = self.pipeline.start_render_pass(render_tools, depth_texture);
+        render_pass.set_bind_group(0, &self.global_bind_group);
+
+        let mut current_mode = None;
+        for (bind_group, blend_mode, instances) in draw_calls {
+            if current_mode != Some(*blend_mode) {
+                render_pass.set_pipeline(self.blend_pipeline(*blend_mode));
+                current_mode = Some(*blend_mode);
+            }
+
+            render_pass.set_bind_group(1, bind_group);
+            render_pass.draw_instance_range(instance_buffer, instances.clone());
+        }
+    }
 }
 
 //===============================================================
@@ -352,6 +426,12 @@ impl TextureRenderer {
         &self.inner.get_texture_layout()
     }
 
+    /// Depth of the textures drawn this frame, for drawing more on top of them
+    #[inline]
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
     //----------------------------------------------
 
     pub fn render(
@@ -373,6 +453,28 @@ impl TextureRenderer {
         );
     }
 
+    /// Draw transparent textures on top of the opaque ones drawn by `render`
+    pub fn render_transparent(
+        &self,
+        render_tools: &mut RenderPassTools,
+        instance_buffer: &wgpu::Buffer,
+        draw_calls: &[(&wgpu::BindGroup, BlendMode, Range<u32>)],
+    ) {
+        self.inner.render_transparent(
+            render_tools,
+            instance_buffer,
+            draw_calls,
+            Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        );
+    }
+
     //----------------------------------------------
 }
 
@@ -0,0 +1,268 @@
+//===============================================================
+
+use brackens_assets::{handle::HandleInner, Asset, Handle};
+use serde::{Deserialize, Serialize};
+
+use crate::Size;
+
+use super::{texture_settings::GridSlicing, RendererTexture};
+
+//===============================================================
+
+/// Area of a texture in texture coordinates, where (0, 0) is the top left corner and
+/// (1, 1) the bottom right.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TextureRect {
+    pub top_left: [f32; 2],
+    pub bottom_right: [f32; 2],
+}
+
+impl Default for TextureRect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl TextureRect {
+    pub const FULL: TextureRect = TextureRect {
+        top_left: [0., 0.],
+        bottom_right: [1., 1.],
+    };
+
+    pub fn new(top_left: [f32; 2], bottom_right: [f32; 2]) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// Convert a pixel rect (x, y, width, height) of a texture of the given size
+    pub fn from_pixels(rect: [u32; 4], texture_size: Size<u32>) -> Self {
+        let width = texture_size.width.max(1) as f32;
+        let height = texture_size.height.max(1) as f32;
+
+        Self {
+            top_left: [rect[0] as f32 / width, rect[1] as f32 / height],
+            bottom_right: [
+                (rect[0] + rect[2]) as f32 / width,
+                (rect[1] + rect[3]) as f32 / height,
+            ],
+        }
+    }
+
+    /// Mirror the rect horizontally
+    pub fn flip_x(self) -> Self {
+        Self {
+            top_left: [self.bottom_right[0], self.top_left[1]],
+            bottom_right: [self.top_left[0], self.bottom_right[1]],
+        }
+    }
+
+    /// Mirror the rect vertically
+    pub fn flip_y(self) -> Self {
+        Self {
+            top_left: [self.top_left[0], self.bottom_right[1]],
+            bottom_right: [self.bottom_right[0], self.top_left[1]],
+        }
+    }
+}
+
+//===============================================================
+
+/// How a sprite sheet is split into sprites
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpriteSheetLayout {
+    /// Equally sized tiles, with optional margins and padding
+    Grid(GridSlicing),
+    /// Pixel rects (x, y, width, height) of every sprite
+    Rects(Vec<[u32; 4]>),
+}
+
+impl From<GridSlicing> for SpriteSheetLayout {
+    fn from(value: GridSlicing) -> Self {
+        Self::Grid(value)
+    }
+}
+
+impl SpriteSheetLayout {
+    /// Pixel rects of every sprite in a texture of the given size
+    pub fn rects(&self, texture_size: Size<u32>) -> Vec<[u32; 4]> {
+        match self {
+            SpriteSheetLayout::Grid(slicing) => {
+                slicing.rects(texture_size.width, texture_size.height)
+            }
+            SpriteSheetLayout::Rects(rects) => rects.clone(),
+        }
+    }
+}
+
+//===============================================================
+
+/// Texture split into sprites which can be drawn individually by index
+pub struct SpriteSheet {
+    texture: Handle<RendererTexture>,
+    texture_size: Size<u32>,
+    rects: Vec<[u32; 4]>,
+    uvs: Vec<TextureRect>,
+}
+
+impl Asset for SpriteSheet {
+    fn asset_name(&self) -> &str {
+        "Sprite Sheet"
+    }
+
+    fn byte_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.rects.len()
+                * (std::mem::size_of::<[u32; 4]>() + std::mem::size_of::<TextureRect>())
+    }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        vec![self.texture.inner_id()]
+    }
+}
+
+impl SpriteSheet {
+    //----------------------------------------------
+
+    pub fn new(
+        texture: Handle<RendererTexture>,
+        texture_size: Size<u32>,
+        layout: &SpriteSheetLayout,
+    ) -> Self {
+        Self::from_rects(texture, texture_size, layout.rects(texture_size))
+    }
+
+    pub fn from_grid(
+        texture: Handle<RendererTexture>,
+        texture_size: Size<u32>,
+        slicing: &GridSlicing,
+    ) -> Self {
+        Self::from_rects(
+            texture,
+            texture_size,
+            slicing.rects(texture_size.width, texture_size.height),
+        )
+    }
+
+    pub fn from_rects(
+        texture: Handle<RendererTexture>,
+        texture_size: Size<u32>,
+        rects: Vec<[u32; 4]>,
+    ) -> Self {
+        let uvs = rects
+            .iter()
+            .map(|rect| TextureRect::from_pixels(*rect, texture_size))
+            .collect();
+
+        Self {
+            texture,
+            texture_size,
+            rects,
+            uvs,
+        }
+    }
+
+    //----------------------------------------------
+
+    #[inline]
+    pub fn texture(&self) -> &Handle<RendererTexture> {
+        &self.texture
+    }
+
+    #[inline]
+    pub fn texture_size(&self) -> Size<u32> {
+        self.texture_size
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Pixel rect (x, y, width, height) of the sprite
+    #[inline]
+    pub fn rect(&self, index: usize) -> Option<[u32; 4]> {
+        self.rects.get(index).copied()
+    }
+
+    /// Texture coordinates of the sprite
+    #[inline]
+    pub fn uv(&self, index: usize) -> Option<TextureRect> {
+        self.uvs.get(index).copied()
+    }
+
+    /// Size of the sprite in pixels
+    pub fn sprite_size(&self, index: usize) -> Option<Size<u32>> {
+        self.rects
+            .get(index)
+            .map(|rect| Size::new(rect[2], rect[3]))
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_with_margin_and_padding() {
+        let layout = SpriteSheetLayout::Grid(GridSlicing {
+            tile_width: 10,
+            tile_height: 8,
+            margin: 2,
+            padding: 4,
+            ..Default::default()
+        });
+        let size = Size::new(32, 32);
+
+        let rects = layout.rects(size);
+        assert_eq!(
+            rects,
+            vec![
+                [2, 2, 10, 8],
+                [16, 2, 10, 8],
+                [2, 14, 10, 8],
+                [16, 14, 10, 8]
+            ]
+        );
+
+        let uvs = rects
+            .iter()
+            .map(|rect| TextureRect::from_pixels(*rect, size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uvs,
+            vec![
+                TextureRect::new([0.0625, 0.0625], [0.375, 0.3125]),
+                TextureRect::new([0.5, 0.0625], [0.8125, 0.3125]),
+                TextureRect::new([0.0625, 0.4375], [0.375, 0.6875]),
+                TextureRect::new([0.5, 0.4375], [0.8125, 0.6875]),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_with_fixed_columns_and_rows() {
+        let layout = SpriteSheetLayout::Grid(GridSlicing {
+            tile_width: 4,
+            tile_height: 4,
+            columns: Some(2),
+            rows: Some(1),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            layout.rects(Size::new(64, 64)),
+            vec![[0, 0, 4, 4], [4, 0, 4, 4]]
+        );
+    }
+}
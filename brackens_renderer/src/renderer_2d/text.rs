@@ -0,0 +1,656 @@
+//===============================================================
+
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use brackens_assets::HandleID;
+use glyph_brush::{
+    ab_glyph::PxScale, BrushAction, BrushError, GlyphBrush, GlyphBrushBuilder, GlyphVertex,
+    HorizontalAlign, Layout, Rectangle, Section, Text, VerticalAlign,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    pipelines::{
+        bind_group_templates::{
+            BindGroupEntry, BindGroupEntryLayout, BindGroupEntryType, BindGroupTemplate,
+        },
+        instance_pipeline::RawInstancePipeline,
+        PipelineBuilderDescriptor, Vertex,
+    },
+    render_tools::RenderPassTools,
+    Size,
+};
+
+use super::{
+    renderer_components::{RawTextureVertex, TEXTURE_INDICES, TEXTURE_VERTICES},
+    tools::TEXT_SHADER,
+    TextureDrawBuffer,
+};
+
+pub use glyph_brush::{ab_glyph::FontArc, FontId};
+
+//===============================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl From<TextAlign> for HorizontalAlign {
+    fn from(value: TextAlign) -> Self {
+        match value {
+            TextAlign::Left => HorizontalAlign::Left,
+            TextAlign::Center => HorizontalAlign::Center,
+            TextAlign::Right => HorizontalAlign::Right,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextVerticalAlign {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+}
+
+impl From<TextVerticalAlign> for VerticalAlign {
+    fn from(value: TextVerticalAlign) -> Self {
+        match value {
+            TextVerticalAlign::Top => VerticalAlign::Top,
+            TextVerticalAlign::Center => VerticalAlign::Center,
+            TextVerticalAlign::Bottom => VerticalAlign::Bottom,
+        }
+    }
+}
+
+/// What the position of text is relative to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextSpace {
+    /// Placed in the world and moved by the camera
+    #[default]
+    World,
+    /// Placed in pixels from the bottom left of the screen, ignoring the camera
+    Screen,
+}
+
+//===============================================================
+
+/// Text to be drawn by a `TextRenderer`
+#[derive(Clone, Debug)]
+pub struct TextSection<'a> {
+    pub text: &'a str,
+    pub font: FontId,
+    /// Height of the text in pixels
+    pub size: f32,
+    pub color: [f32; 4],
+    /// Horizontal alignment of the lines around the origin of the transform
+    pub align: TextAlign,
+    /// Vertical alignment of the lines around the origin of the transform
+    pub vertical_align: TextVerticalAlign,
+    /// Width and height the text is wrapped within and clipped to
+    pub bounds: Option<[f32; 2]>,
+    pub space: TextSpace,
+    pub transform: glam::Mat4,
+}
+
+impl<'a> TextSection<'a> {
+    pub fn new(text: &'a str, font: FontId, size: f32) -> Self {
+        Self {
+            text,
+            font,
+            size,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            bounds: None,
+            space: TextSpace::default(),
+            transform: glam::Mat4::IDENTITY,
+        }
+    }
+}
+
+//===============================================================
+
+/// Per section data given to the glyph brush and passed on to each glyph instance
+#[derive(Clone, Debug)]
+struct GlyphExtra {
+    color: [f32; 4],
+    transform: [f32; 16],
+    space: TextSpace,
+}
+
+impl GlyphExtra {
+    fn bits(&self) -> impl Iterator<Item = u32> + '_ {
+        self.color
+            .iter()
+            .chain(self.transform.iter())
+            .map(|value| value.to_bits())
+    }
+}
+
+impl PartialEq for GlyphExtra {
+    fn eq(&self, other: &Self) -> bool {
+        self.space == other.space && self.bits().eq(other.bits())
+    }
+}
+
+impl Hash for GlyphExtra {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().for_each(|bits| bits.hash(state));
+        self.space.hash(state);
+    }
+}
+
+//===============================================================
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawGlyphInstance {
+    /// Left, top, right and bottom of the glyph in pixels, with y going down
+    pub rect: [f32; 4],
+    pub tex_coord_top_left: [f32; 2],
+    pub tex_coord_bottom_right: [f32; 2],
+    pub transform: [f32; 16],
+    pub color: [f32; 4],
+    pub screen_space: u32,
+}
+impl Vertex for RawGlyphInstance {
+    fn buffer_layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<RawGlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                // Rect f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 1,
+                },
+                // Tex Coord f32x2
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                },
+                // Tex Coord f32x2
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                },
+                // Transform 1 f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                },
+                // Transform 2 f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                },
+                // Transform 3 f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                },
+                // Transform 4 f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                },
+                // Color f32x4
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 24]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                },
+                // Screen Space u32
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Uint32,
+                    offset: std::mem::size_of::<[f32; 28]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                },
+            ],
+        }
+    }
+}
+
+/// Create a glyph instance, clipping the glyph to the bounds of its section
+fn glyph_instance(vertex: GlyphVertex<GlyphExtra>) -> RawGlyphInstance {
+    let GlyphVertex {
+        mut tex_coords,
+        mut pixel_coords,
+        bounds,
+        extra,
+    } = vertex;
+
+    if pixel_coords.max.x > bounds.max.x {
+        let old_width = pixel_coords.width();
+        pixel_coords.max.x = bounds.max.x;
+        tex_coords.max.x = tex_coords.min.x + tex_coords.width() * pixel_coords.width() / old_width;
+    }
+    if pixel_coords.min.x < bounds.min.x {
+        let old_width = pixel_coords.width();
+        pixel_coords.min.x = bounds.min.x;
+        tex_coords.min.x = tex_coords.max.x - tex_coords.width() * pixel_coords.width() / old_width;
+    }
+    if pixel_coords.max.y > bounds.max.y {
+        let old_height = pixel_coords.height();
+        pixel_coords.max.y = bounds.max.y;
+        tex_coords.max.y =
+            tex_coords.min.y + tex_coords.height() * pixel_coords.height() / old_height;
+    }
+    if pixel_coords.min.y < bounds.min.y {
+        let old_height = pixel_coords.height();
+        pixel_coords.min.y = bounds.min.y;
+        tex_coords.min.y =
+            tex_coords.max.y - tex_coords.height() * pixel_coords.height() / old_height;
+    }
+
+    RawGlyphInstance {
+        rect: [
+            pixel_coords.min.x,
+            pixel_coords.min.y,
+            pixel_coords.max.x,
+            pixel_coords.max.y,
+        ],
+        tex_coord_top_left: [tex_coords.min.x, tex_coords.min.y],
+        tex_coord_bottom_right: [tex_coords.max.x, tex_coords.max.y],
+        transform: extra.transform,
+        color: extra.color,
+        screen_space: (extra.space == TextSpace::Screen) as u32,
+    }
+}
+
+//===============================================================
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextGlobals {
+    camera: [f32; 16],
+    screen: [f32; 16],
+}
+
+fn screen_projection(size: Size<u32>) -> [f32; 16] {
+    glam::Mat4::orthographic_lh(0., size.width as f32, 0., size.height as f32, 0., 100.)
+        .to_cols_array()
+}
+
+//===============================================================
+
+/// Draws text using glyphs rasterized into a single cache texture. Text is drawn on top of
+/// everything rendered before it, without depth testing.
+pub struct TextRenderer {
+    pipeline: RawInstancePipeline,
+    brush: GlyphBrush<RawGlyphInstance, GlyphExtra>,
+    fonts: HashMap<HandleID<FontArc>, FontId>,
+
+    globals: TextGlobals,
+    global_bind_group: wgpu::BindGroup,
+    global_buffer: wgpu::Buffer,
+
+    cache_template: BindGroupTemplate<TextGlobals>,
+    cache_sampler: wgpu::Sampler,
+    cache_texture: wgpu::Texture,
+    cache_bind_group: wgpu::BindGroup,
+
+    instance_buffer: Option<TextureDrawBuffer>,
+    instance_count: u32,
+}
+
+impl TextRenderer {
+    //----------------------------------------------
+
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, window_size: Size<u32>) -> Self {
+        //----------------------------------------------
+
+        let global_template = BindGroupTemplate::new(
+            device,
+            "Text Renderer",
+            vec![BindGroupEntryLayout::buffer(
+                "Text Renderer",
+                wgpu::ShaderStages::VERTEX,
+            )],
+        );
+
+        let screen = screen_projection(window_size);
+        let globals = TextGlobals {
+            camera: screen,
+            screen,
+        };
+
+        let (global_bind_group, global_buffer) =
+            global_template.create_bind_group(device, &[BindGroupEntry::Buffer(globals)]);
+
+        //----------------------------------------------
+
+        let cache_template = BindGroupTemplate::new(
+            device,
+            "Text Renderer Glyph Cache",
+            vec![
+                BindGroupEntryLayout {
+                    entry_type: BindGroupEntryType::TextureView,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                },
+                BindGroupEntryLayout {
+                    entry_type: BindGroupEntryType::Sampler,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                },
+            ],
+        );
+
+        let cache_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Text Renderer Glyph Cache Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let brush = GlyphBrushBuilder::using_fonts(Vec::<FontArc>::new()).build();
+        let (width, height) = brush.texture_dimensions();
+
+        let (cache_texture, cache_bind_group) =
+            Self::create_cache(device, &cache_template, &cache_sampler, width, height);
+
+        //----------------------------------------------
+
+        let builder = PipelineBuilderDescriptor {
+            name: "Text Renderer".into(),
+            bind_group_layouts: Some(vec![
+                global_template.get_layout(),
+                cache_template.get_layout(),
+            ]),
+            shader: device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Text Renderer - Shader"),
+                source: wgpu::ShaderSource::Wgsl(TEXT_SHADER.into()),
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment_targets: vec![Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            multiview: None,
+        };
+
+        let pipeline = RawInstancePipeline::new::<RawTextureVertex, RawGlyphInstance>(
+            device,
+            builder,
+            &TEXTURE_VERTICES,
+            &TEXTURE_INDICES,
+        );
+
+        //----------------------------------------------
+
+        Self {
+            pipeline,
+            brush,
+            fonts: HashMap::new(),
+            globals,
+            global_bind_group,
+            global_buffer: global_buffer.unwrap(),
+            cache_template,
+            cache_sampler,
+            cache_texture,
+            cache_bind_group,
+            instance_buffer: None,
+            instance_count: 0,
+        }
+
+        //----------------------------------------------
+    }
+
+    fn create_cache(
+        device: &wgpu::Device,
+        template: &BindGroupTemplate<TextGlobals>,
+        sampler: &wgpu::Sampler,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::BindGroup) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Text Renderer Glyph Cache"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let (bind_group, _) = template.create_bind_group(
+            device,
+            &[
+                BindGroupEntry::TextureView(&view),
+                BindGroupEntry::Sampler(sampler),
+            ],
+        );
+
+        (texture, bind_group)
+    }
+
+    //----------------------------------------------
+
+    /// Id of a font for use in text sections, adding it to the renderer the first time
+    pub fn font_id(&mut self, handle_id: HandleID<FontArc>, font: &FontArc) -> FontId {
+        *self
+            .fonts
+            .entry(handle_id)
+            .or_insert_with(|| self.brush.add_font(font.clone()))
+    }
+
+    /// Replace the font used for a handle after it has been reloaded. The old font can't be
+    /// removed from the glyph brush so it stays loaded.
+    pub fn reload_font(&mut self, handle_id: HandleID<FontArc>, font: &FontArc) {
+        if let Some(font_id) = self.fonts.get_mut(&handle_id) {
+            *font_id = self.brush.add_font(font.clone());
+        }
+    }
+
+    #[inline]
+    pub fn has_font(&self, handle_id: &HandleID<FontArc>) -> bool {
+        self.fonts.contains_key(handle_id)
+    }
+
+    /// Size in pixels of the glyph cache texture
+    pub fn cache_size(&self) -> Size<u32> {
+        let (width, height) = self.brush.texture_dimensions();
+        Size::new(width, height)
+    }
+
+    //----------------------------------------------
+
+    /// Projection used for text in world space
+    pub fn set_camera_projection(&mut self, queue: &wgpu::Queue, matrix: &glam::Mat4) {
+        self.globals.camera = matrix.to_cols_array();
+        queue.write_buffer(
+            &self.global_buffer,
+            0,
+            bytemuck::cast_slice(&[self.globals]),
+        );
+    }
+
+    /// Update the projection used for text in screen space
+    pub fn resize(&mut self, queue: &wgpu::Queue, new_size: Size<u32>) {
+        self.globals.screen = screen_projection(new_size);
+        queue.write_buffer(
+            &self.global_buffer,
+            0,
+            bytemuck::cast_slice(&[self.globals]),
+        );
+    }
+
+    //----------------------------------------------
+
+    /// Queue text to be drawn after the next call to `process`
+    pub fn queue(&mut self, section: &TextSection) {
+        let extra = GlyphExtra {
+            color: section.color,
+            transform: section.transform.to_cols_array(),
+            space: section.space,
+        };
+
+        let bounds = section
+            .bounds
+            .map(|bounds| (bounds[0], bounds[1]))
+            .unwrap_or((f32::INFINITY, f32::INFINITY));
+
+        self.brush.queue(Section {
+            screen_position: (0., 0.),
+            bounds,
+            layout: Layout::default_wrap()
+                .h_align(section.align.into())
+                .v_align(section.vertical_align.into()),
+            text: vec![Text {
+                text: section.text,
+                scale: PxScale::from(section.size),
+                font_id: section.font,
+                extra,
+            }],
+        });
+    }
+
+    /// Rasterize any new glyphs into the cache and create the instances of all queued text
+    pub fn process(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let action = loop {
+            let cache_texture = &self.cache_texture;
+
+            let result = self.brush.process_queued(
+                |rect, data| Self::update_cache(queue, cache_texture, rect, data),
+                glyph_instance,
+            );
+
+            match result {
+                Ok(action) => break action,
+                Err(BrushError::TextureTooSmall { suggested }) => {
+                    let max_size = device.limits().max_texture_dimension_2d;
+                    let (width, height) = (suggested.0.min(max_size), suggested.1.min(max_size));
+
+                    if (width, height) == self.brush.texture_dimensions() {
+                        warn!(
+                            "Warning: Text Renderer glyph cache can't grow beyond {}x{}. Text not drawn.",
+                            width, height
+                        );
+                        self.instance_count = 0;
+                        return;
+                    }
+
+                    let (texture, bind_group) = Self::create_cache(
+                        device,
+                        &self.cache_template,
+                        &self.cache_sampler,
+                        width,
+                        height,
+                    );
+                    self.cache_texture = texture;
+                    self.cache_bind_group = bind_group;
+                    self.brush.resize_texture(width, height);
+                }
+            }
+        };
+
+        // Nothing changed since the last frame so the previous instances can be drawn again
+        let instances = match action {
+            BrushAction::Draw(instances) => instances,
+            BrushAction::ReDraw => return,
+        };
+
+        self.instance_count = instances.len() as u32;
+        if instances.is_empty() {
+            return;
+        }
+
+        match &self.instance_buffer {
+            Some(buffer) if buffer.instance_count >= self.instance_count => {
+                queue.write_buffer(&buffer.instance_buffer, 0, bytemuck::cast_slice(&instances));
+            }
+            _ => {
+                let instance_buffer =
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Text Renderer Instance Buffer"),
+                        contents: bytemuck::cast_slice(&instances),
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    });
+
+                self.instance_buffer = Some(TextureDrawBuffer {
+                    instance_buffer,
+                    instance_count: self.instance_count,
+                });
+            }
+        }
+    }
+
+    fn update_cache(
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        rect: Rectangle<u32>,
+        data: &[u8],
+    ) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: rect.min[0],
+                    y: rect.min[1],
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(rect.width()),
+                rows_per_image: Some(rect.height()),
+            },
+            wgpu::Extent3d {
+                width: rect.width(),
+                height: rect.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    //----------------------------------------------
+
+    pub fn render(&self, render_tools: &mut RenderPassTools) {
+        let buffer = match (&self.instance_buffer, self.instance_count) {
+            (Some(buffer), count) if count > 0 => buffer,
+            _ => return,
+        };
+
+        let mut render_pass = self.pipeline.start_render_pass(render_tools, None);
+
+        render_pass.set_bind_group(0, &self.global_bind_group);
+        render_pass.set_bind_group(1, &self.cache_bind_group);
+        render_pass.draw_instanced(Some(&buffer.instance_buffer), self.instance_count);
+    }
+
+    //----------------------------------------------
+}
+
+//===============================================================
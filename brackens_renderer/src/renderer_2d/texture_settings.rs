@@ -0,0 +1,147 @@
+//===============================================================
+
+use serde::{Deserialize, Serialize};
+
+//===============================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterMode {
+    #[default]
+    Nearest,
+    Linear,
+}
+
+impl From<FilterMode> for wgpu::FilterMode {
+    fn from(value: FilterMode) -> Self {
+        match value {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressMode {
+    #[default]
+    ClampToEdge,
+    Repeat,
+    MirrorRepeat,
+}
+
+impl From<AddressMode> for wgpu::AddressMode {
+    fn from(value: AddressMode) -> Self {
+        match value {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Whether the texture holds colors (sRGB) or data such as normals (linear)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+//===============================================================
+
+/// Split a texture into a grid of equally sized tiles
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridSlicing {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Number of columns. Fits as many as possible if not set.
+    pub columns: Option<u32>,
+    /// Number of rows. Fits as many as possible if not set.
+    pub rows: Option<u32>,
+    /// Space around the edge of the texture
+    pub margin: u32,
+    /// Space between tiles
+    pub padding: u32,
+}
+
+impl GridSlicing {
+    /// Pixel rects (x, y, width, height) of every tile, row by row
+    pub fn rects(&self, width: u32, height: u32) -> Vec<[u32; 4]> {
+        if self.tile_width == 0 || self.tile_height == 0 {
+            return Vec::new();
+        }
+
+        let fit = |size: u32, tile: u32| {
+            (size.saturating_sub(self.margin * 2) + self.padding) / (tile + self.padding)
+        };
+
+        let columns = self.columns.unwrap_or_else(|| fit(width, self.tile_width));
+        let rows = self.rows.unwrap_or_else(|| fit(height, self.tile_height));
+
+        (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |column| {
+                    [
+                        self.margin + column * (self.tile_width + self.padding),
+                        self.margin + row * (self.tile_height + self.padding),
+                        self.tile_width,
+                        self.tile_height,
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+//===============================================================
+
+/// Import settings of a texture, usually read from the `.meta` file next to it.
+///
+/// ```ron
+/// (filter: Nearest, mipmaps: true, slicing: Some((tile_width: 16, tile_height: 16)))
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureSettings {
+    pub label: Option<String>,
+    pub filter: FilterMode,
+    pub address_mode: AddressMode,
+    pub mipmaps: bool,
+    pub color_space: ColorSpace,
+    pub slicing: Option<GridSlicing>,
+}
+
+impl TextureSettings {
+    pub fn sampler_descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        let mipmap_filter = match self.mipmaps {
+            true => self.filter.into(),
+            false => wgpu::FilterMode::Nearest,
+        };
+
+        wgpu::SamplerDescriptor {
+            address_mode_u: self.address_mode.into(),
+            address_mode_v: self.address_mode.into(),
+            address_mode_w: self.address_mode.into(),
+            mag_filter: self.filter.into(),
+            min_filter: self.filter.into(),
+            mipmap_filter,
+            ..Default::default()
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self.color_space {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    pub fn mip_level_count(&self, width: u32, height: u32) -> u32 {
+        match self.mipmaps {
+            true => 32 - width.max(height).max(1).leading_zeros(),
+            false => 1,
+        }
+    }
+}
+
+//===============================================================
@@ -1,14 +1,59 @@
 //===============================================================
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
 use wgpu::util::DeviceExt;
 
-use super::{RawTextureInstance, TextureDrawBuffer};
+use super::{BlendMode, RawTextureInstance, TextureDrawBuffer};
 
 //===============================================================
 
 pub const TEXTURE_SHADER: &str = include_str!("../shaders/texture_shader.wgsl");
+pub const TEXT_SHADER: &str = include_str!("../shaders/text_shader.wgsl");
+
+//===============================================================
+
+/// Texture instance drawn with a transparent blend mode
+#[derive(Clone, Copy)]
+pub struct TransparentInstance<T> {
+    pub texture_id: T,
+    pub blend_mode: BlendMode,
+    /// Instances are drawn from the highest depth to the lowest. Defaults to the z
+    /// translation of the instance but can be set to a layer instead.
+    pub depth: f32,
+    /// Instances at the same depth are drawn from the lowest order to the highest, such as
+    /// by entity id, so their order doesn't depend on the order they were queued in.
+    pub order: u64,
+    pub instance: RawTextureInstance,
+}
+
+impl<T> TransparentInstance<T> {
+    pub fn new(texture_id: T, blend_mode: BlendMode, instance: RawTextureInstance) -> Self {
+        Self {
+            texture_id,
+            blend_mode,
+            depth: instance.transform[14],
+            order: 0,
+            instance,
+        }
+    }
+
+    pub fn with_order(mut self, order: u64) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+/// Consecutive transparent instances sharing a texture and blend mode. The range refers to
+/// the processor's transparent instance buffer.
+pub struct TransparentDrawCall<T> {
+    pub texture_id: T,
+    pub blend_mode: BlendMode,
+    pub instances: Range<u32>,
+}
 
 //===============================================================
 
@@ -20,6 +65,10 @@ where
     unprocessed_draw_data: HashMap<T, Vec<RawTextureInstance>>,
 
     draw_data: HashMap<T, TextureDrawBuffer>,
+
+    unprocessed_transparent: Vec<TransparentInstance<T>>,
+    transparent_buffer: Option<TextureDrawBuffer>,
+    transparent_draw_calls: Vec<TransparentDrawCall<T>>,
 }
 impl<T> Default for TextureProcessor<T>
 where
@@ -30,6 +79,9 @@ where
             should_render: HashSet::new(),
             unprocessed_draw_data: HashMap::new(),
             draw_data: HashMap::new(),
+            unprocessed_transparent: Vec::new(),
+            transparent_buffer: None,
+            transparent_draw_calls: Vec::new(),
         }
     }
 }
@@ -59,6 +111,38 @@ where
             .push(instance);
     }
 
+    /// Draw an instance sorted with the other transparent instances if the blend mode is
+    /// transparent. See `TransparentInstance::order`.
+    pub fn draw_texture_blended(
+        &mut self,
+        texture_id: T,
+        instance: RawTextureInstance,
+        blend_mode: BlendMode,
+        order: u64,
+    ) {
+        match blend_mode.is_transparent() {
+            true => self
+                .unprocessed_transparent
+                .push(TransparentInstance::new(texture_id, blend_mode, instance).with_order(order)),
+            false => self.draw_texture(texture_id, instance),
+        }
+    }
+
+    pub fn get_unprocessed_transparent_mut(&mut self) -> &mut Vec<TransparentInstance<T>> {
+        &mut self.unprocessed_transparent
+    }
+
+    /// Transparent draw calls, back to front
+    pub fn get_transparent_draw_calls(&self) -> &[TransparentDrawCall<T>] {
+        &self.transparent_draw_calls
+    }
+
+    pub fn get_transparent_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.transparent_buffer
+            .as_ref()
+            .map(|buffer| &buffer.instance_buffer)
+    }
+
     //----------------------------------------------
 
     pub fn process_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
@@ -98,6 +182,36 @@ where
         // Removed unused data
         self.draw_data.retain(|k, _| self.should_render.contains(k));
         self.unprocessed_draw_data.clear();
+
+        self.process_transparent(device, queue);
+    }
+
+    /// Sort transparent instances back to front and batch consecutive instances sharing a
+    /// texture and blend mode
+    fn process_transparent(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.transparent_draw_calls.clear();
+
+        if self.unprocessed_transparent.is_empty() {
+            return;
+        }
+
+        let instances = batch_transparent(
+            &mut self.unprocessed_transparent,
+            &mut self.transparent_draw_calls,
+        );
+
+        match &self.transparent_buffer {
+            Some(buffer) if buffer.instance_count >= instances.len() as u32 => {
+                queue.write_buffer(&buffer.instance_buffer, 0, bytemuck::cast_slice(&instances));
+            }
+            _ => {
+                self.transparent_buffer = Some(Self::create_instance_buffer(
+                    device,
+                    &instances,
+                    "Texture Processor Transparent Buffer",
+                ));
+            }
+        }
     }
 
     fn create_instance_buffer(
@@ -121,3 +235,119 @@ where
 }
 
 //===============================================================
+
+/// Sort transparent instances back to front and split them into draw calls wherever the
+/// texture or blend mode changes
+fn batch_transparent<T: Copy + Eq>(
+    unprocessed: &mut Vec<TransparentInstance<T>>,
+    draw_calls: &mut Vec<TransparentDrawCall<T>>,
+) -> Vec<RawTextureInstance> {
+    // Stable so the instances queued together for the same order, such as the glyphs of
+    // a text, keep the order they were queued in
+    unprocessed.sort_by(|a, b| {
+        b.depth
+            .total_cmp(&a.depth)
+            .then_with(|| a.order.cmp(&b.order))
+    });
+
+    let mut instances = Vec::with_capacity(unprocessed.len());
+
+    for (index, transparent) in unprocessed.drain(..).enumerate() {
+        instances.push(transparent.instance);
+        let index = index as u32;
+
+        match draw_calls.last_mut() {
+            Some(draw_call)
+                if draw_call.texture_id == transparent.texture_id
+                    && draw_call.blend_mode == transparent.blend_mode =>
+            {
+                draw_call.instances.end = index + 1;
+            }
+            _ => draw_calls.push(TransparentDrawCall {
+                texture_id: transparent.texture_id,
+                blend_mode: transparent.blend_mode,
+                instances: index..index + 1,
+            }),
+        }
+    }
+
+    instances
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instance at a depth, marked through its color to tell it apart once sorted
+    fn transparent(
+        texture_id: u32,
+        blend_mode: BlendMode,
+        depth: f32,
+        order: u64,
+        marker: f32,
+    ) -> TransparentInstance<u32> {
+        let mut instance = RawTextureInstance::default();
+        instance.transform[14] = depth;
+        instance.color = [marker, 0., 0., 1.];
+
+        TransparentInstance::new(texture_id, blend_mode, instance).with_order(order)
+    }
+
+    fn markers(instances: &[RawTextureInstance]) -> Vec<f32> {
+        instances.iter().map(|instance| instance.color[0]).collect()
+    }
+
+    #[test]
+    fn equal_depths_drawn_by_order() {
+        let mut unprocessed = vec![
+            transparent(0, BlendMode::Alpha, 1., 3, 0.),
+            transparent(0, BlendMode::Alpha, 1., 1, 1.),
+            transparent(0, BlendMode::Alpha, 5., 9, 2.),
+            transparent(0, BlendMode::Alpha, 1., 2, 3.),
+            // Same depth and order keep the order they were queued in
+            transparent(0, BlendMode::Alpha, 1., 2, 4.),
+        ];
+        let mut draw_calls = Vec::new();
+
+        let instances = batch_transparent(&mut unprocessed, &mut draw_calls);
+
+        assert_eq!(markers(&instances), vec![2., 1., 3., 4., 0.]);
+        assert!(unprocessed.is_empty());
+
+        // One texture and blend mode is a single draw call
+        assert_eq!(draw_calls.len(), 1);
+        assert_eq!(draw_calls[0].instances, 0..5);
+    }
+
+    #[test]
+    fn blend_modes_split_draw_calls_at_the_same_depth() {
+        let mut unprocessed = vec![
+            transparent(0, BlendMode::Alpha, 1., 4, 3.),
+            transparent(0, BlendMode::Additive, 1., 3, 2.),
+            transparent(0, BlendMode::Alpha, 1., 1, 0.),
+            transparent(0, BlendMode::Alpha, 1., 2, 1.),
+            transparent(1, BlendMode::Alpha, 1., 5, 4.),
+        ];
+        let mut draw_calls = Vec::new();
+
+        let instances = batch_transparent(&mut unprocessed, &mut draw_calls);
+        assert_eq!(markers(&instances), vec![0., 1., 2., 3., 4.]);
+
+        let calls = draw_calls
+            .iter()
+            .map(|call| (call.texture_id, call.blend_mode, call.instances.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            calls,
+            vec![
+                (0, BlendMode::Alpha, 0..2),
+                (0, BlendMode::Additive, 2..3),
+                (0, BlendMode::Alpha, 3..4),
+                (1, BlendMode::Alpha, 4..5),
+            ]
+        );
+    }
+}
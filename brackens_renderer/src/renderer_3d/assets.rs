@@ -1,11 +1,13 @@
 //===============================================================
 
 use anyhow::Result;
-use brackens_assets::{file_loading::load_string, Asset, AssetStorageSingle, Handle};
+use brackens_assets::{
+    file_loading::load_string, handle::HandleInner, Asset, AssetStorageSingle, Handle, Vfs,
+};
 use std::{
     collections::HashMap,
-    fs::File,
     io::{BufReader, Cursor},
+    path::Path,
 };
 use tobj::load_mtl_buf;
 
@@ -27,6 +29,14 @@ impl Asset for RawMesh {
     fn asset_name(&self) -> &str {
         "Raw Mesh"
     }
+
+    fn byte_size(&self) -> usize {
+        std::mem::size_of_val(self.positions.as_slice())
+            + std::mem::size_of_val(self.vertex_color.as_slice())
+            + std::mem::size_of_val(self.normals.as_slice())
+            + std::mem::size_of_val(self.texcoords.as_slice())
+            + std::mem::size_of_val(self.indices.as_slice())
+    }
     // fn asset_name() -> &'static str {
     //     "Raw Mesh"
     // }
@@ -59,6 +69,21 @@ impl Asset for RawMaterial {
     fn asset_name(&self) -> &str {
         "Raw Material"
     }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        [
+            &self.ambient_texture,
+            &self.diffuse_texture,
+            &self.specular_texture,
+            &self.normal_texture,
+            &self.shininess_texture,
+            &self.dissolve_texture,
+        ]
+        .into_iter()
+        .flatten()
+        .map(HandleInner::from)
+        .collect()
+    }
     // fn asset_name() -> &'static str {
     //     "Raw Material"
     // }
@@ -76,6 +101,10 @@ impl Asset for RendererMesh {
     fn asset_name(&self) -> &str {
         "Renderer Model"
     }
+
+    fn byte_size(&self) -> usize {
+        (self.vertices.size() + self.indices.size()) as usize
+    }
     // fn asset_name() -> &'static str {
     //     "Renderer Model"
     // }
@@ -94,6 +123,10 @@ impl Asset for RendererMaterial {
     fn asset_name(&self) -> &str {
         "Renderer Material"
     }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        vec![self.diffuse.inner_id()]
+    }
     // fn asset_name() -> &'static str {
     //     "Renderer Material"
     // }
@@ -109,13 +142,23 @@ impl Asset for RendererModel {
     fn asset_name(&self) -> &str {
         "Renderer Model"
     }
+
+    fn dependencies(&self) -> Vec<HandleInner> {
+        self.meshes
+            .iter()
+            .flat_map(|(material, mesh)| [HandleInner::from(material), HandleInner::from(mesh)])
+            .collect()
+    }
     // fn asset_name() -> &'static str {
     //     "Renderer Model"
     // }
 }
 
 impl RendererModel {
+    /// Load an obj model and its materials. Model and material paths are inside the
+    /// `res/` folder of the filesystem, see `file_loading::RESOURCE_FOLDER`.
     pub fn load_model(
+        vfs: &Vfs,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
 
@@ -128,7 +171,9 @@ impl RendererModel {
 
         path: String,
     ) -> Result<Self> {
-        let txt = load_string(&path)?;
+        let txt = load_string(vfs, &path)?;
+        // Material files are relative to the model file
+        let directory = Path::new(&path).parent().unwrap_or(Path::new(""));
 
         let cursor = Cursor::new(txt);
         let mut reader = BufReader::new(cursor);
@@ -141,8 +186,10 @@ impl RendererModel {
                 ..Default::default()
             },
             |p| {
-                let file = File::open(p).unwrap();
-                let mut reader = BufReader::new(file);
+                let mtl_path = directory.join(p);
+                let txt = load_string(vfs, &mtl_path.to_string_lossy())
+                    .map_err(|_| tobj::LoadError::OpenFileFailed)?;
+                let mut reader = BufReader::new(Cursor::new(txt));
 
                 load_mtl_buf(&mut reader)
             },
@@ -169,6 +216,7 @@ impl RendererModel {
         } in model_materials?
         {
             let ambient_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -179,6 +227,7 @@ impl RendererModel {
             )?;
 
             let diffuse_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -189,6 +238,7 @@ impl RendererModel {
             )?;
 
             let specular_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -199,6 +249,7 @@ impl RendererModel {
             )?;
 
             let normal_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -208,6 +259,7 @@ impl RendererModel {
                 normal_texture,
             )?;
             let shininess_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -217,6 +269,7 @@ impl RendererModel {
                 shininess_texture,
             )?;
             let dissolve_texture = load_texture(
+                vfs,
                 device,
                 queue,
                 texture_storage,
@@ -250,7 +303,9 @@ impl RendererModel {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn load_texture(
+    vfs: &Vfs,
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     texture_storage: &mut AssetStorageSingle<RendererTexture>,
@@ -264,10 +319,11 @@ fn load_texture(
         Some(path) => match texture_storage.get_loaded_file(&path) {
             Some(handle) => Some(handle.id()),
             None => {
-                let texture = RendererTexture::from_file(
+                let bytes = vfs.read(&path)?;
+                let texture = RendererTexture::from_bytes(
                     device,
                     queue,
-                    &path,
+                    &bytes,
                     &name,
                     sampler,
                     bind_group_layout,
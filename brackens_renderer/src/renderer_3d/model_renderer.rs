@@ -88,6 +88,7 @@ impl Vertex for RawMeshInstance {
 pub struct ModelRenderer {
     pipeline: RawPipeline,
 
+    projection: glam::Mat4,
     projection_bind_group: wgpu::BindGroup,
     projection_uniform_buffer: wgpu::Buffer,
 
@@ -217,6 +218,7 @@ impl ModelRenderer {
 
         Self {
             pipeline,
+            projection: projection_matrix,
             projection_bind_group,
             projection_uniform_buffer,
             material_bind_group_layout,
@@ -227,20 +229,19 @@ impl ModelRenderer {
     }
 
     pub fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, new_size: Size<u32>) {
-        let projection_data = glam::Mat4::orthographic_rh(
+        self.projection = glam::Mat4::orthographic_rh(
             0.,
             new_size.width as f32,
             0.,
             new_size.height as f32,
             0.,
             100.,
-        )
-        .to_cols_array();
+        );
 
         queue.write_buffer(
             &self.projection_uniform_buffer,
             0,
-            bytemuck::cast_slice(&projection_data),
+            bytemuck::cast_slice(&self.projection.to_cols_array()),
         );
 
         self.depth_texture = Texture::create_depth_texture(device, new_size, "ModelDepthTexture");
@@ -250,6 +251,18 @@ impl ModelRenderer {
         &self.material_bind_group_layout
     }
 
+    /// Depth of the models drawn this frame, for drawing more on top of them
+    #[inline]
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
+    /// Projection the models are drawn with
+    #[inline]
+    pub fn projection(&self) -> glam::Mat4 {
+        self.projection
+    }
+
     pub fn render(
         &self,
         render_tools: &mut RenderPassTools,
@@ -0,0 +1,82 @@
+//===============================================================
+
+struct Globals {
+    projection: mat4x4<f32>,
+    viewport: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+struct VertexInput {
+    // Vertex Data
+    @location(0) position: vec3<f32>,
+    // Instance Data
+    @location(1) start: vec3<f32>,
+    @location(2) end: vec3<f32>,
+    @location(3) color: vec4<f32>,
+    @location(4) thickness: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+//===============================================================
+
+// Move a point behind the camera along the line until it is in front of it
+fn clip_near(point: vec4<f32>, other: vec4<f32>) -> vec4<f32> {
+    let near = 0.0001;
+    if (point.w >= near) {
+        return point;
+    }
+    return mix(point, other, (near - point.w) / (other.w - point.w));
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = in.color;
+
+    var start = globals.projection * vec4<f32>(in.start, 1.);
+    var end = globals.projection * vec4<f32>(in.end, 1.);
+
+    // Lines completely behind the camera are moved outside of the clip volume
+    if (start.w < 0.0001 && end.w < 0.0001) {
+        out.clip_position = vec4<f32>(0., 0., -1., 1.);
+        return out;
+    }
+
+    start = clip_near(start, end);
+    end = clip_near(end, start);
+
+    // Expand the line into a quad in pixels so the thickness doesn't depend on the camera
+    let screen_start = start.xy / start.w * globals.viewport / 2.;
+    let screen_end = end.xy / end.w * globals.viewport / 2.;
+
+    var direction = vec2<f32>(1., 0.);
+    if (distance(screen_start, screen_end) > 0.0001) {
+        direction = normalize(screen_end - screen_start);
+    }
+    let normal = vec2<f32>(-direction.y, direction.x);
+
+    // Ends are extended by half the thickness so the segments of a polyline join up
+    let offset = (direction * in.position.x * 2. + normal * in.position.y * 2.) * in.thickness / 2.;
+
+    var clip = mix(start, end, in.position.x + 0.5);
+    clip = vec4<f32>(clip.xy + offset * 2. / globals.viewport * clip.w, clip.zw);
+
+    out.clip_position = clip;
+
+    return out;
+}
+
+//===============================================================
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+
+//===============================================================
@@ -0,0 +1,78 @@
+//===============================================================
+
+struct Globals {
+    camera: mat4x4<f32>,
+    screen: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+struct VertexInput {
+    // Vertex Data
+    @location(0) position: vec3<f32>,
+    // Instance Data
+    @location(1) rect: vec4<f32>,
+    @location(2) tex_coord_tl: vec2<f32>,
+    @location(3) tex_coord_br: vec2<f32>,
+    @location(4) transform_0: vec4<f32>,
+    @location(5) transform_1: vec4<f32>,
+    @location(6) transform_2: vec4<f32>,
+    @location(7) transform_3: vec4<f32>,
+    @location(8) color: vec4<f32>,
+    @location(9) screen_space: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+//===============================================================
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let transform = mat4x4<f32>(
+        in.transform_0,
+        in.transform_1,
+        in.transform_2,
+        in.transform_3
+    );
+
+    // Glyph rects are laid out with y going down from the top left
+    let corner = vec2<f32>(in.position.x + 0.5, 0.5 - in.position.y);
+    let pixel = mix(in.rect.xy, in.rect.zw, corner);
+
+    var projection = globals.camera;
+    if (in.screen_space != 0u) {
+        projection = globals.screen;
+    }
+
+    out.clip_position =
+        projection *
+        transform *
+        vec4<f32>(pixel.x, -pixel.y, 0., 1.);
+
+    out.tex_coord = mix(in.tex_coord_tl, in.tex_coord_br, corner);
+    out.color = in.color;
+
+    return out;
+}
+
+//===============================================================
+
+@group(1) @binding(0) var glyph_cache: texture_2d<f32>;
+@group(1) @binding(1) var glyph_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+
+    let coverage = textureSample(glyph_cache, glyph_sampler, in.tex_coord).r;
+
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+
+}
+
+//===============================================================
@@ -3,11 +3,16 @@
 use brackens_assets::{
     asset_storage::{AssetStorage as AssetStorageInner, AssetStorageError},
     handle::HandleInner,
-    Asset, AssetFileLoadable,
+    Asset, AssetError, AssetFileLoadable, AssetLoader, AssetMeta, AssetSource, FallbackPolicy, Vfs,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use shipyard::Unique;
 
-pub use brackens_assets::handle::{Handle, HandleID};
+pub use brackens_assets::{
+    handle::{Handle, HandleID, UntypedHandle, WeakHandle},
+    AssetEvent, AssetEventReader, AssetStats, FolderHandle, LoadState, RetentionPolicy,
+    RetentionTime,
+};
 
 //===============================================================
 
@@ -22,6 +27,36 @@ impl AssetStorage {
         &self.0
     }
 
+    #[inline]
+    pub fn fallback_policy(&self) -> FallbackPolicy {
+        self.0.fallback_policy()
+    }
+
+    #[inline]
+    pub fn set_fallback_policy(&mut self, policy: FallbackPolicy) {
+        self.0.set_fallback_policy(policy)
+    }
+
+    #[inline]
+    pub fn vfs(&self) -> &Vfs {
+        self.0.vfs()
+    }
+
+    #[inline]
+    pub fn mount<S: AssetSource>(&mut self, mount_point: &str, source: S) {
+        self.0.mount(mount_point, source)
+    }
+
+    #[inline]
+    pub fn unmount(&mut self, mount_point: &str) -> bool {
+        self.0.unmount(mount_point)
+    }
+
+    #[inline]
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.0.register_loader(loader)
+    }
+
     #[inline]
     pub fn insert_data<T: Asset>(&mut self, data: T) -> Handle<T> {
         self.0.insert_data(data)
@@ -36,7 +71,60 @@ impl AssetStorage {
     }
 
     #[inline]
-    pub fn load_from_data<T: Asset, F: Fn(&[u8]) -> T>(
+    pub fn load_untyped(&mut self, path: &str) -> anyhow::Result<UntypedHandle> {
+        self.0.load_untyped(path)
+    }
+
+    #[inline]
+    pub fn load_untyped_with_settings<S: Serialize>(
+        &mut self,
+        path: &str,
+        settings: &S,
+    ) -> anyhow::Result<UntypedHandle> {
+        self.0.load_untyped_with_settings(path, settings)
+    }
+
+    #[inline]
+    pub fn load_with_settings<T: Asset, S: Serialize>(
+        &mut self,
+        path: &str,
+        settings: &S,
+    ) -> anyhow::Result<Handle<T>> {
+        self.0.load_with_settings(path, settings)
+    }
+
+    #[inline]
+    pub fn load_folder(&mut self, path: &str, recursive: bool) -> anyhow::Result<FolderHandle> {
+        self.0.load_folder(path, recursive)
+    }
+
+    #[inline]
+    pub fn load_glob(&mut self, pattern: &str) -> anyhow::Result<FolderHandle> {
+        self.0.load_glob(pattern)
+    }
+
+    #[inline]
+    pub fn rescan(&mut self, folder: &mut FolderHandle) -> anyhow::Result<usize> {
+        self.0.rescan(folder)
+    }
+
+    #[inline]
+    pub fn folder_load_state(&self, folder: &FolderHandle) -> LoadState {
+        self.0.folder_load_state(folder)
+    }
+
+    #[inline]
+    pub fn is_folder_loaded(&self, folder: &FolderHandle) -> bool {
+        self.0.is_folder_loaded(folder)
+    }
+
+    #[inline]
+    pub fn load<T: Asset>(&mut self, path: &str) -> anyhow::Result<Handle<T>> {
+        self.0.load(path)
+    }
+
+    #[inline]
+    pub fn load_from_data<T: Asset, F: Fn(&[u8]) -> anyhow::Result<T>>(
         &mut self,
         path: &str,
         load_data: F,
@@ -44,11 +132,191 @@ impl AssetStorage {
         self.0.load_from_data(path, load_data)
     }
 
+    #[inline]
+    pub fn load_from_data_with_settings<T, S, F>(
+        &mut self,
+        path: &str,
+        settings: Option<S>,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        T: Asset,
+        S: Serialize + DeserializeOwned + Default,
+        F: Fn(&[u8], &S) -> anyhow::Result<T>,
+    {
+        self.0
+            .load_from_data_with_settings(path, settings, load_data)
+    }
+
+    #[inline]
+    pub fn read_meta(&self, path: &str) -> Result<Option<AssetMeta>, AssetError> {
+        self.0.read_meta(path)
+    }
+
+    #[inline]
+    pub fn load_settings<S: DeserializeOwned + Default>(
+        &self,
+        path: &str,
+    ) -> Result<S, AssetError> {
+        self.0.load_settings(path)
+    }
+
+    #[inline]
+    pub fn load_from_file_async<T: AssetFileLoadable>(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<Handle<T>> {
+        self.0.load_from_file_async(path)
+    }
+
+    #[inline]
+    pub fn load_from_data_async<T, F>(
+        &mut self,
+        path: &str,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        T: Asset,
+        F: FnOnce(&[u8]) -> anyhow::Result<T> + Send + 'static,
+    {
+        self.0.load_from_data_async(path, load_data)
+    }
+
+    #[inline]
+    pub fn load_from_data_staged<I, T, F>(
+        &mut self,
+        path: &str,
+        load_data: F,
+    ) -> anyhow::Result<Handle<T>>
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: Fn(&[u8]) -> anyhow::Result<I> + Send + Sync + 'static,
+    {
+        self.0.load_from_data_staged(path, load_data)
+    }
+
+    #[inline]
+    pub fn set_reload_staged<I, T, F>(
+        &mut self,
+        id: HandleID<T>,
+        path: &str,
+        load_data: F,
+    ) -> Result<(), AssetStorageError>
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: Fn(&[u8]) -> anyhow::Result<I> + Send + Sync + 'static,
+    {
+        self.0.set_reload_staged(id, path, load_data)
+    }
+
+    #[inline]
+    pub fn finalize_staged<I, T, F>(&mut self, finalize: F)
+    where
+        I: Send + Sync + 'static,
+        T: Asset,
+        F: FnMut(I) -> anyhow::Result<T>,
+    {
+        self.0.finalize_staged(finalize)
+    }
+
     #[inline]
     pub fn is_file_loaded(&self, path: &str) -> bool {
         self.0.is_file_loaded(path)
     }
 
+    #[inline]
+    pub fn load_state<HI: Into<HandleInner>>(&self, id: HI) -> Option<&LoadState> {
+        self.0.load_state(id)
+    }
+
+    #[inline]
+    pub fn is_loaded<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        self.0.is_loaded(id)
+    }
+
+    #[inline]
+    pub fn add_dependency<P: Into<HandleInner>, D: Into<HandleInner>>(
+        &mut self,
+        dependent: P,
+        dependency: D,
+    ) -> Result<(), AssetStorageError> {
+        self.0.add_dependency(dependent, dependency)
+    }
+
+    #[inline]
+    pub fn get_dependents<HI: Into<HandleInner>>(&self, id: HI) -> Vec<HandleInner> {
+        self.0.get_dependents(id)
+    }
+
+    #[inline]
+    pub fn dependency_load_state<HI: Into<HandleInner>>(&self, id: HI) -> Option<LoadState> {
+        self.0.dependency_load_state(id)
+    }
+
+    #[inline]
+    pub fn is_fully_loaded<HI: Into<HandleInner>>(&self, id: HI) -> bool {
+        self.0.is_fully_loaded(id)
+    }
+
+    #[inline]
+    pub fn reload<HI: Into<HandleInner>>(&mut self, id: HI) {
+        self.0.reload(id)
+    }
+
+    #[inline]
+    pub fn reload_path(&mut self, path: &str) -> bool {
+        self.0.reload_path(path)
+    }
+
+    #[inline]
+    pub fn get_reloaded_assets(&self) -> &Vec<HandleInner> {
+        self.0.get_reloaded_assets()
+    }
+
+    #[inline]
+    pub fn get_mut<T: Asset>(&mut self, id: HandleID<T>) -> Result<&mut T, AssetStorageError> {
+        self.0.get_mut(id)
+    }
+
+    #[inline]
+    pub fn make_mut<T: Asset + Clone>(
+        &mut self,
+        id: HandleID<T>,
+    ) -> Result<&mut T, AssetStorageError> {
+        self.0.make_mut(id)
+    }
+
+    #[inline]
+    pub fn modify<T: Asset, R, F: FnOnce(&mut T) -> R>(
+        &mut self,
+        id: HandleID<T>,
+        modify: F,
+    ) -> Result<R, AssetStorageError> {
+        self.0.modify(id, modify)
+    }
+
+    #[inline]
+    pub fn event_reader<T: Asset>(&mut self) -> AssetEventReader<T> {
+        self.0.event_reader()
+    }
+
+    #[inline]
+    pub fn read_events<T: Asset>(&self, reader: &mut AssetEventReader<T>) -> Vec<AssetEvent<T>> {
+        self.0.read_events(reader)
+    }
+
+    #[inline]
+    pub fn set_retention_policy(&mut self, policy: RetentionPolicy) {
+        self.0.set_retention_policy(policy)
+    }
+
+    #[inline]
+    pub fn stats(&self) -> AssetStats {
+        self.0.stats()
+    }
+
     #[inline]
     pub fn get_file_loaded<T: Asset>(&self, path: &str) -> Result<Handle<T>, AssetStorageError> {
         self.0.get_file_loaded(path)
@@ -62,6 +330,22 @@ impl AssetStorage {
         self.0.get_handle(id)
     }
 
+    #[inline]
+    pub fn get_weak_handle<T: Asset, HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<WeakHandle<T>, AssetStorageError> {
+        self.0.get_weak_handle(id)
+    }
+
+    #[inline]
+    pub fn get_untyped_handle<HI: Into<HandleInner>>(
+        &self,
+        id: HI,
+    ) -> Result<UntypedHandle, AssetStorageError> {
+        self.0.get_untyped_handle(id)
+    }
+
     #[inline]
     pub fn get_data<T: Asset>(&self, id: HandleID<T>) -> Result<&T, AssetStorageError> {
         self.0.get_data(id)
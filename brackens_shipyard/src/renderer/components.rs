@@ -1,12 +1,22 @@
 //===============================================================
 
 use brackens_assets::Handle;
-use brackens_renderer::renderer_2d::RendererTexture;
+use brackens_renderer::renderer_2d::{
+    AnimationEvent, BlendMode, NineSliceLayout, RendererBitmapFont, RendererTexture, SpriteSheet,
+    SubTexture, TextureRect,
+};
 use brackens_tools::glam::Vec2;
 use shipyard::Component;
 
-pub use brackens_renderer::tools::{
-    CameraOrthographic as CameraOrthographicInner, CameraPerspective as CameraPerspectiveInner,
+pub use brackens_renderer::{
+    renderer_2d::{
+        AnimationClip, AnimationFrame, AnimationMode, AtlasBuilder, FontArc, NineSliceBorder,
+        PackedAtlas, SliceMode, SpriteAnimator, SpriteSheetLayout, TextAlign, TextSpace,
+        TextVerticalAlign, TextureAtlas,
+    },
+    tools::{
+        CameraOrthographic as CameraOrthographicInner, CameraPerspective as CameraPerspectiveInner,
+    },
 };
 
 //===============================================================
@@ -17,6 +27,12 @@ pub struct CameraActive;
 
 //--------------------------------------------------
 
+/// Entities with this component aren't drawn by the 2D renderer
+#[derive(Component)]
+pub struct Hidden;
+
+//--------------------------------------------------
+
 #[derive(Component)]
 #[track(All)]
 pub struct CameraOrthographic(pub CameraOrthographicInner);
@@ -90,6 +106,12 @@ pub struct Texture2D {
     pub size: Vec2,
     pub handle: Handle<RendererTexture>,
     pub color: [f32; 4],
+    pub blend_mode: BlendMode,
+    /// Area of the texture to draw
+    pub rect: TextureRect,
+    /// Depth to draw at instead of the z translation of the transform. Textures with a
+    /// higher layer are drawn behind ones with a lower layer.
+    pub layer: Option<f32>,
 }
 impl Texture2D {
     pub fn new(handle: Handle<RendererTexture>, width: f32, height: f32) -> Self {
@@ -97,6 +119,9 @@ impl Texture2D {
             size: Vec2::new(width, height),
             handle,
             color: [1., 1., 1., 1.],
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layer: None,
         }
     }
 
@@ -110,8 +135,284 @@ impl Texture2D {
             size: Vec2::new(width, height),
             handle,
             color,
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layer: None,
+        }
+    }
+
+    /// Draw an image packed into an atlas at its size in pixels
+    pub fn from_sub_texture(sub_texture: &SubTexture) -> Self {
+        Self::new(
+            sub_texture.texture.clone(),
+            sub_texture.size.width as f32,
+            sub_texture.size.height as f32,
+        )
+        .with_rect(sub_texture.rect)
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn with_rect(mut self, rect: TextureRect) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = Some(layer);
+        self
+    }
+}
+
+//--------------------------------------------------
+
+/// Texture drawn as nine slices so its borders keep their size when the area is scaled.
+/// The borders are in pixels of the texture.
+#[derive(Component, Clone)]
+pub struct NineSlice {
+    pub size: Vec2,
+    pub handle: Handle<RendererTexture>,
+    pub color: [f32; 4],
+    pub blend_mode: BlendMode,
+    /// Area of the texture containing all nine slices
+    pub rect: TextureRect,
+    pub layout: NineSliceLayout,
+}
+impl NineSlice {
+    pub fn new(
+        handle: Handle<RendererTexture>,
+        width: f32,
+        height: f32,
+        border: NineSliceBorder,
+    ) -> Self {
+        Self {
+            size: Vec2::new(width, height),
+            handle,
+            color: [1., 1., 1., 1.],
+            blend_mode: BlendMode::default(),
+            rect: TextureRect::FULL,
+            layout: NineSliceLayout::new(border),
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_rect(mut self, rect: TextureRect) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    /// Size of each pixel of the borders when drawn
+    pub fn with_border_scale(mut self, border_scale: f32) -> Self {
+        self.layout.border_scale = border_scale;
+        self
+    }
+
+    /// Repeat the edges and center instead of stretching them
+    pub fn with_tiling(mut self, edges: SliceMode, center: SliceMode) -> Self {
+        self.layout.edges = edges;
+        self.layout.center = center;
+        self
+    }
+}
+
+//--------------------------------------------------
+
+/// Draw a single sprite of a sprite sheet. The texture and rect of the `Texture2D` on the
+/// same entity are kept in sync with the sheet.
+#[derive(Component, Clone)]
+pub struct Sprite {
+    pub sheet: Handle<SpriteSheet>,
+    pub index: usize,
+}
+impl Sprite {
+    pub fn new(sheet: Handle<SpriteSheet>, index: usize) -> Self {
+        Self { sheet, index }
+    }
+}
+
+//--------------------------------------------------
+
+/// Changes the index of the `Sprite` on the same entity over time
+#[derive(Component, Clone, Default)]
+pub struct SpriteAnimation(pub(crate) SpriteAnimator);
+impl SpriteAnimation {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    #[inline]
+    pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.0.add_clip(name, clip);
+        self
+    }
+    #[inline]
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip) {
+        self.0.add_clip(name, clip);
+    }
+
+    #[inline]
+    pub fn play(&mut self, clip: &str) -> bool {
+        self.0.play(clip)
+    }
+    #[inline]
+    pub fn restart(&mut self, clip: &str) -> bool {
+        self.0.restart(clip)
+    }
+    #[inline]
+    pub fn pause(&mut self) {
+        self.0.pause();
+    }
+    #[inline]
+    pub fn resume(&mut self) {
+        self.0.resume();
+    }
+    #[inline]
+    pub fn stop(&mut self) {
+        self.0.stop();
+    }
+    #[inline]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.0.set_speed(speed);
+    }
+
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.0.is_playing()
+    }
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+    #[inline]
+    pub fn current_clip(&self) -> Option<&str> {
+        self.0.current_clip()
+    }
+    #[inline]
+    pub fn current_frame(&self) -> usize {
+        self.0.current_frame()
+    }
+    /// Events of the frames reached this frame
+    #[inline]
+    pub fn events(&self) -> &[AnimationEvent] {
+        self.0.events()
+    }
+}
+
+impl From<SpriteAnimator> for SpriteAnimation {
+    fn from(animator: SpriteAnimator) -> Self {
+        Self(animator)
+    }
+}
+
+//===============================================================
+
+/// Text drawn at the transform of the entity. The alignment is relative to the translation,
+/// so centered text is centered on the entity.
+#[derive(Component, Clone)]
+pub struct Text2D {
+    pub text: String,
+    pub font: Handle<FontArc>,
+    /// Height of the text in pixels
+    pub size: f32,
+    pub color: [f32; 4],
+    pub align: TextAlign,
+    pub vertical_align: TextVerticalAlign,
+    /// Width and height to wrap the text within. Text outside the bounds is clipped.
+    pub bounds: Option<Vec2>,
+    /// Screen space text is positioned in pixels from the bottom left of the window
+    pub space: TextSpace,
+}
+impl Text2D {
+    pub fn new(text: &str, font: Handle<FontArc>, size: f32) -> Self {
+        Self {
+            text: text.to_string(),
+            font,
+            size,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            bounds: None,
+            space: TextSpace::default(),
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign, vertical_align: TextVerticalAlign) -> Self {
+        self.align = align;
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_bounds(mut self, width: f32, height: f32) -> Self {
+        self.bounds = Some(Vec2::new(width, height));
+        self
+    }
+
+    pub fn with_space(mut self, space: TextSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+//--------------------------------------------------
+
+/// Text drawn with a bitmap font at the transform of the entity. Glyphs are drawn by the
+/// texture renderer, so bitmap text is sorted and batched along with other textures.
+#[derive(Component, Clone)]
+pub struct BitmapText {
+    pub text: String,
+    pub font: Handle<RendererBitmapFont>,
+    /// Size of each pixel of the font
+    pub scale: f32,
+    pub color: [f32; 4],
+    pub align: TextAlign,
+    pub vertical_align: TextVerticalAlign,
+    pub blend_mode: BlendMode,
+}
+impl BitmapText {
+    pub fn new(text: &str, font: Handle<RendererBitmapFont>) -> Self {
+        Self {
+            text: text.to_string(),
+            font,
+            scale: 1.,
+            color: [1., 1., 1., 1.],
+            align: TextAlign::default(),
+            vertical_align: TextVerticalAlign::default(),
+            blend_mode: BlendMode::Alpha,
         }
     }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign, vertical_align: TextVerticalAlign) -> Self {
+        self.align = align;
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
 }
 
 //===============================================================
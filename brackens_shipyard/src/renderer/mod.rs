@@ -75,16 +75,53 @@ impl crate::runner::RunnerWorkloads for Renderer2dWorkload {
             .with_system(sys_resize_renderer_2d.skip_if_missing_unique::<ResizeEvent>())
             .with_system(sys_update_camera_active)
             .with_system(sys_renderer2d_update_camera.after_all(sys_update_camera_active))
+            .with_system(sys_update_sprite_animations)
     }
 
     fn render(&self) -> Workload {
         Workload::new("")
-            .with_system(sys_renderer2d_process_textures)
+            .with_system(sys_update_sprites)
+            .with_system(sys_renderer2d_queue_bitmap_text)
+            .with_system(sys_renderer2d_queue_nine_slices)
+            .with_system(
+                sys_renderer2d_process_textures
+                    .after_all(sys_update_sprites)
+                    .after_all(sys_renderer2d_queue_bitmap_text)
+                    .after_all(sys_renderer2d_queue_nine_slices),
+            )
+            .with_system(sys_renderer2d_process_text.after_all(sys_renderer2d_process_textures))
             .with_system(
                 sys_renderer2d_render_textures
                     .after_all(sys_renderer2d_process_textures)
                     .skip_if_missing_unique::<RenderPassTools>(),
             )
+            .with_system(
+                sys_renderer2d_render_text
+                    .after_all(sys_renderer2d_render_textures)
+                    .after_all(sys_renderer2d_process_text)
+                    .skip_if_missing_unique::<RenderPassTools>(),
+            )
+            .with_system(sys_renderer2d_process_gizmos)
+            .with_system(
+                sys_renderer2d_render_gizmos
+                    .after_all(sys_renderer2d_render_text)
+                    .after_all(sys_renderer2d_process_gizmos)
+                    .skip_if_missing_unique::<RenderPassTools>(),
+            )
+    }
+
+    fn end(&self) -> Workload {
+        Workload::new("")
+            .with_system(
+                sys_renderer2d_finalize_textures.after_all(crate::assets::sys_reset_asset_storage),
+            )
+            .with_system(
+                sys_renderer2d_reload_fonts.after_all(crate::assets::sys_reset_asset_storage),
+            )
+            .with_system(
+                sys_renderer2d_update_image_textures
+                    .after_all(crate::assets::sys_reset_asset_storage),
+            )
     }
 }
 
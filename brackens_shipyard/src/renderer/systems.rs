@@ -2,12 +2,17 @@
 
 use std::collections::HashMap;
 
+use brackens_assets::AssetEvent;
 use brackens_renderer::{
     render_tools,
-    renderer_2d::{RawTextureInstance, TextureID},
+    renderer_2d::{
+        tools::TransparentInstance, BitmapTextSection, FontArc, RawTextureInstance,
+        RendererTexture, TextSection, TextureID,
+    },
     wgpu::{PresentMode, SurfaceError},
     RenderComponents, RenderPrefs, Size,
 };
+use log::warn;
 use rayon::prelude::ParallelIterator;
 use shipyard::{
     AllStoragesView, EntitiesView, Get, IntoIter, IntoWithId, UniqueView, UniqueViewMut, View,
@@ -15,19 +20,27 @@ use shipyard::{
 };
 
 use super::{
-    CameraActive, CameraOrthographic, CameraPerspective, ClearColor, Device, Queue,
-    RenderPassTools, Renderer2D, Surface, SurfaceConfig, Texture2D,
+    BitmapText, CameraActive, CameraOrthographic, CameraPerspective, ClearColor, Device,
+    FrameCapture, Gizmos, Hidden, NineSlice, Queue, RenderPassTools, Renderer2D, RendererSetup,
+    Screenshots, Sprite, SpriteAnimation, Surface, SurfaceConfig, Text2D, Texture2D,
 };
 #[cfg(feature = "runner")]
-use crate::runner::uniques::{ResizeEvent, RunnerErrorManager};
+use crate::runner::{
+    tools::StagedTexture,
+    uniques::{ResizeEvent, RunnerErrorManager},
+};
 use crate::{
     assets::AssetStorage,
-    tools::{Transform, Window},
+    tools::{Transform, UpkeepTracker, Window},
 };
 
 //===============================================================
 
 pub fn setup_renderer(all_storages: AllStoragesView, window: UniqueView<Window>) {
+    let allow_capture = all_storages
+        .borrow::<UniqueView<RendererSetup>>()
+        .is_ok_and(|setup| setup.allow_capture);
+
     let RenderComponents {
         device,
         queue,
@@ -36,6 +49,7 @@ pub fn setup_renderer(all_storages: AllStoragesView, window: UniqueView<Window>)
     } = RenderComponents::new_winit(
         RenderPrefs {
             present_mode: PresentMode::Mailbox,
+            allow_capture,
             ..Default::default()
         },
         window.inner(),
@@ -47,6 +61,8 @@ pub fn setup_renderer(all_storages: AllStoragesView, window: UniqueView<Window>)
     all_storages.add_unique(SurfaceConfig::new(config));
 
     all_storages.add_unique(ClearColor::new(0.3, 0.3, 0.3));
+    all_storages.add_unique(Screenshots::default());
+    all_storages.add_unique(FrameCapture::default());
 }
 
 //===============================================================
@@ -99,9 +115,33 @@ pub fn sys_start_render_pass(
     }
 }
 
-pub fn sys_end_render_pass(all_storages: AllStoragesView, queue: UniqueView<Queue>) {
+pub fn sys_end_render_pass(
+    all_storages: AllStoragesView,
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+) {
     if let Ok(tools) = all_storages.remove_unique::<RenderPassTools>() {
-        render_tools::end_render_pass(queue.inner(), tools.destroy());
+        let mut screenshots = all_storages.borrow::<UniqueViewMut<Screenshots>>().ok();
+        let mut frame_capture = all_storages.borrow::<UniqueViewMut<FrameCapture>>().ok();
+
+        render_tools::end_render_pass_with_capture(
+            device.inner(),
+            queue.inner(),
+            tools.destroy(),
+            screenshots
+                .as_mut()
+                .map(|screenshots| screenshots.inner_mut()),
+            frame_capture
+                .as_mut()
+                .and_then(|frame_capture| frame_capture.inner_mut()),
+        );
+
+        if let Some(finished) = frame_capture
+            .as_mut()
+            .and_then(|frame_capture| frame_capture.take_finished())
+        {
+            finished.finish(device.inner());
+        }
     }
 }
 
@@ -125,6 +165,7 @@ pub fn sys_setup_renderer_2d(
         config.inner(),
         window.size(),
     ));
+    all_storages.add_unique(Gizmos::default());
 }
 
 //--------------------------------------------------
@@ -140,6 +181,9 @@ pub fn sys_resize_renderer_2d(
     v_perspective: View<CameraPerspective>,
     v_active: View<CameraActive>,
 ) {
+    renderer.resize_text(queue.inner(), resize.inner());
+    renderer.resize_gizmos(queue.inner(), resize.inner());
+
     if (&v_orthographic, &v_active).iter().next().is_none()
         && (&v_perspective, &v_active).iter().next().is_none()
     {
@@ -219,6 +263,47 @@ pub fn sys_renderer2d_update_camera(
 
 //--------------------------------------------------
 
+pub fn sys_update_sprite_animations(
+    upkeep: UniqueView<UpkeepTracker>,
+    mut vm_animation: ViewMut<SpriteAnimation>,
+    mut vm_sprite: ViewMut<Sprite>,
+) {
+    let delta = upkeep.delta();
+
+    for (animation, sprite) in (&mut vm_animation, &mut vm_sprite).iter() {
+        animation.0.update(delta);
+
+        if let Some(index) = animation.0.sprite_index() {
+            sprite.index = index;
+        }
+    }
+}
+
+/// Point textures of sprites at the area of their sprite sheet to draw
+pub fn sys_update_sprites(
+    asset_storage: UniqueView<AssetStorage>,
+    v_sprite: View<Sprite>,
+    mut vm_texture: ViewMut<Texture2D>,
+) {
+    for (sprite, texture) in (&v_sprite, &mut vm_texture).iter() {
+        // Sheet may not have finished loading
+        let sheet = match asset_storage.get_data(sprite.sheet.id()) {
+            Ok(sheet) => sheet,
+            Err(_) => continue,
+        };
+
+        if texture.handle.id() != sheet.texture().id() {
+            texture.handle = sheet.texture().clone();
+        }
+
+        if let Some(rect) = sheet.uv(sprite.index) {
+            texture.rect = rect;
+        }
+    }
+}
+
+//--------------------------------------------------
+
 pub fn sys_renderer2d_process_textures(
     device: UniqueView<Device>,
     queue: UniqueView<Queue>,
@@ -226,24 +311,37 @@ pub fn sys_renderer2d_process_textures(
     mut renderer: UniqueViewMut<Renderer2D>,
     v_texture: View<Texture2D>,
     v_transform: View<Transform>,
+    v_hidden: View<Hidden>,
 ) {
-    *renderer.get_unprocessed_mut() = (&v_texture, &v_transform)
+    let to_instance = |texture: &Texture2D, transform: &Transform| {
+        let mut instance = RawTextureInstance {
+            tex_coord_top_left: texture.rect.top_left,
+            tex_coord_bottom_right: texture.rect.bottom_right,
+            transform: (&Transform::from_scale(texture.size.extend(1.)) + transform).to_raw(),
+            color: texture.color,
+        };
+
+        if let Some(layer) = texture.layer {
+            instance.transform[14] = layer;
+        }
+        instance
+    };
+
+    let opaque = (&v_texture, &v_transform, !&v_hidden)
         .par_iter()
         .fold(
-            || HashMap::<TextureID, Vec<RawTextureInstance>>::new(),
-            |mut acc, (texture, transform)| {
-                // Add RawTextureInstance to hashmap of values to be rendered
-                acc.entry(texture.handle.id())
-                    // Insert empty vec if texture not already present
-                    .or_insert(vec![])
-                    // Add instance of texture to new or existing hashmap entry
-                    .push(RawTextureInstance {
-                        transform: (&Transform::from_scale(texture.size.extend(1.)) + transform)
-                            .to_raw(),
-                        color: texture.color,
-                        ..Default::default()
-                    });
-                acc
+            HashMap::<TextureID, Vec<RawTextureInstance>>::new,
+            |mut opaque, (texture, transform, _)| {
+                // Transparent textures are sorted and batched by the processor below
+                if !texture.blend_mode.is_transparent() {
+                    opaque
+                        .entry(texture.handle.id())
+                        // Insert empty vec if texture not already present
+                        .or_insert(vec![])
+                        // Add instance of texture to new or existing hashmap entry
+                        .push(to_instance(texture, transform));
+                }
+                opaque
             },
         )
         .reduce_with(|mut m1, m2| {
@@ -252,11 +350,216 @@ pub fn sys_renderer2d_process_textures(
             }
             m1
         })
-        .unwrap();
+        .unwrap_or_default();
+
+    // Parallel iterators can't give entity ids, which order transparent textures at the
+    // same depth
+    let transparent = (&v_texture, &v_transform, !&v_hidden)
+        .iter()
+        .with_id()
+        .filter(|(_, (texture, _, _))| texture.blend_mode.is_transparent())
+        .map(|(id, (texture, transform, _))| {
+            TransparentInstance::new(
+                texture.handle.id(),
+                texture.blend_mode,
+                to_instance(texture, transform),
+            )
+            .with_order(id.inner())
+        })
+        .collect::<Vec<_>>();
+
+    // Keep anything else queued this frame, such as bitmap text and nine slices
+    let unprocessed = renderer.get_unprocessed_mut();
+    for (id, instances) in opaque {
+        unprocessed.entry(id).or_default().extend(instances);
+    }
+    renderer
+        .get_unprocessed_transparent_mut()
+        .extend(transparent);
 
     renderer.process_texture(device.inner(), queue.inner());
 }
 
+/// Queue an instance for each slice of the nine slices to be processed with the rest of
+/// the textures
+pub fn sys_renderer2d_queue_nine_slices(
+    asset_storage: UniqueView<AssetStorage>,
+
+    mut renderer: UniqueViewMut<Renderer2D>,
+    v_nine_slice: View<NineSlice>,
+    v_transform: View<Transform>,
+    v_hidden: View<Hidden>,
+) {
+    for (id, (nine_slice, transform, _)) in
+        (&v_nine_slice, &v_transform, !&v_hidden).iter().with_id()
+    {
+        // Texture may not have finished loading
+        let size = match asset_storage.get_data(nine_slice.handle.id()) {
+            Ok(texture) => texture.texture.texture.size(),
+            Err(_) => continue,
+        };
+
+        let instances = nine_slice.layout.instances(
+            nine_slice.rect,
+            Size::new(size.width, size.height),
+            nine_slice.size,
+            transform.to_mat4(),
+            nine_slice.color,
+        );
+
+        for instance in instances {
+            renderer.draw_texture_blended(
+                nine_slice.handle.id(),
+                instance,
+                nine_slice.blend_mode,
+                id.inner(),
+            );
+        }
+    }
+}
+
+/// Queue the glyphs of bitmap text to be processed with the rest of the textures
+pub fn sys_renderer2d_queue_bitmap_text(
+    asset_storage: UniqueView<AssetStorage>,
+
+    mut renderer: UniqueViewMut<Renderer2D>,
+    v_text: View<BitmapText>,
+    v_transform: View<Transform>,
+    v_hidden: View<Hidden>,
+) {
+    for (id, (text, transform, _)) in (&v_text, &v_transform, !&v_hidden).iter().with_id() {
+        // Font may not have finished loading
+        let font = match asset_storage.get_data(text.font.id()) {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+
+        let glyphs = font.instances(&BitmapTextSection {
+            text: &text.text,
+            scale: text.scale,
+            color: text.color,
+            align: text.align,
+            vertical_align: text.vertical_align,
+            transform: transform.to_mat4(),
+        });
+
+        for (texture_id, instance) in glyphs {
+            renderer.draw_texture_blended(texture_id, instance, text.blend_mode, id.inner());
+        }
+    }
+}
+
+/// Upload textures decoded by asynchronous loads and reloads to the gpu. Staged data
+/// isn't finalized by the asset storage tick, so this system is required for
+/// `load_texture_async` and texture reloads and must run after `sys_reset_asset_storage`.
+/// Added to the end of `Renderer2dWorkload`.
+#[cfg(feature = "runner")]
+pub fn sys_renderer2d_finalize_textures(
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    renderer: UniqueView<Renderer2D>,
+    mut asset_storage: UniqueViewMut<AssetStorage>,
+) {
+    asset_storage.finalize_staged(|staged: StagedTexture| {
+        RendererTexture::from_image_with_settings(
+            device.inner(),
+            queue.inner(),
+            &staged.image,
+            Some(&staged.label),
+            &staged.settings,
+            Some(&staged.sampler),
+            renderer.get_layout(),
+        )
+    });
+}
+
+/// Write modified or reloaded images to the textures created from them with
+/// `TextureLoader::load_image_texture`
+pub fn sys_renderer2d_update_image_textures(
+    queue: UniqueView<Queue>,
+    mut asset_storage: UniqueViewMut<AssetStorage>,
+    mut renderer: UniqueViewMut<Renderer2D>,
+) {
+    let reader = renderer.image_events_mut(&mut asset_storage);
+    for event in asset_storage.read_events(reader) {
+        let id = match event {
+            AssetEvent::Modified(id) => id,
+            _ => continue,
+        };
+
+        let image = match asset_storage.get_data(id) {
+            Ok(image) => image,
+            Err(_) => continue,
+        };
+
+        for dependent in asset_storage.get_dependents(id) {
+            let texture = match asset_storage.get_data_raw::<RendererTexture, _>(dependent) {
+                Ok(texture) => texture,
+                Err(_) => continue,
+            };
+
+            if let Err(e) = texture.write_image(queue.inner(), image) {
+                warn!(
+                    "Warning: Failed to update texture from modified image: {}",
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Pass reloaded fonts on to the text renderer
+pub fn sys_renderer2d_reload_fonts(
+    asset_storage: UniqueView<AssetStorage>,
+    mut renderer: UniqueViewMut<Renderer2D>,
+) {
+    for id in asset_storage.get_reloaded_assets() {
+        let handle = match asset_storage.get_handle::<FontArc, _>(*id) {
+            Ok(handle) => handle,
+            Err(_) => continue,
+        };
+
+        if let Ok(font) = asset_storage.get_data(handle.id()) {
+            renderer.reload_font(handle.id(), font);
+        }
+    }
+}
+
+pub fn sys_renderer2d_process_text(
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    asset_storage: UniqueView<AssetStorage>,
+
+    mut renderer: UniqueViewMut<Renderer2D>,
+    v_text: View<Text2D>,
+    v_transform: View<Transform>,
+    v_hidden: View<Hidden>,
+) {
+    for (text, transform, _) in (&v_text, &v_transform, !&v_hidden).iter() {
+        // Font may not have finished loading
+        let font = match asset_storage.get_data(text.font.id()) {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+
+        let font_id = renderer.font_id(text.font.id(), font);
+
+        renderer.queue_text(&TextSection {
+            text: &text.text,
+            font: font_id,
+            size: text.size,
+            color: text.color,
+            align: text.align,
+            vertical_align: text.vertical_align,
+            bounds: text.bounds.map(|bounds| bounds.to_array()),
+            space: text.space,
+            transform: transform.to_mat4(),
+        });
+    }
+
+    renderer.process_text(device.inner(), queue.inner());
+}
+
 pub fn sys_renderer2d_render_textures(
     mut renderer: UniqueViewMut<Renderer2D>,
     mut render_tools: UniqueViewMut<RenderPassTools>,
@@ -265,4 +568,31 @@ pub fn sys_renderer2d_render_textures(
     renderer.render(&asset_storage, render_tools.inner_mut());
 }
 
+/// Text is drawn on top of all textures
+pub fn sys_renderer2d_render_text(
+    renderer: UniqueView<Renderer2D>,
+    mut render_tools: UniqueViewMut<RenderPassTools>,
+) {
+    renderer.render_text(render_tools.inner_mut());
+}
+
+/// Upload the gizmos queued this frame and clear them for the next one
+pub fn sys_renderer2d_process_gizmos(
+    device: UniqueView<Device>,
+    queue: UniqueView<Queue>,
+    mut renderer: UniqueViewMut<Renderer2D>,
+    mut gizmos: UniqueViewMut<Gizmos>,
+) {
+    renderer.process_gizmos(device.inner(), queue.inner(), &gizmos);
+    gizmos.clear();
+}
+
+/// Gizmos are drawn after everything else in the scene
+pub fn sys_renderer2d_render_gizmos(
+    renderer: UniqueView<Renderer2D>,
+    mut render_tools: UniqueViewMut<RenderPassTools>,
+) {
+    renderer.render_gizmos(render_tools.inner_mut());
+}
+
 //===============================================================
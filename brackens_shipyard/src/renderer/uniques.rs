@@ -1,10 +1,22 @@
 //===============================================================
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
 
+use brackens_assets::{AssetEventReader, HandleID};
 use brackens_renderer::{
+    capture::{FrameCapture as FrameCaptureInner, Screenshots as ScreenshotsInner},
+    gizmos::{GizmoBuffer, GizmoRenderer},
+    image::DynamicImage,
     render_tools::{self, RenderPassTools as RenderPassToolsInner},
-    renderer_2d::{tools::TextureProcessor, RawTextureInstance, TextureID, TextureRenderer},
+    renderer_2d::{
+        tools::{TextureProcessor, TransparentInstance},
+        BlendMode, FontArc, FontId, RawTextureInstance, TextRenderer, TextSection, TextureID,
+        TextureRenderer,
+    },
     wgpu, Size,
 };
 use brackens_tools::glam::Mat4;
@@ -12,6 +24,8 @@ use shipyard::Unique;
 
 use crate::assets::AssetStorage;
 
+pub use brackens_renderer::capture::{CaptureFormat, FrameCaptureSettings};
+
 //===============================================================
 
 #[derive(Unique)]
@@ -88,6 +102,107 @@ impl RenderPassTools {
 
 //===============================================================
 
+/// Options the renderer is created with, added by the runner before setup
+#[derive(Unique, Default)]
+pub(crate) struct RendererSetup {
+    pub allow_capture: bool,
+}
+
+/// Request screenshots of the window. They are taken at the end of the frame and saved
+/// as PNG files in the background. Requires `ShipyardRunner::allow_capture`.
+#[derive(Unique, Default)]
+pub struct Screenshots(ScreenshotsInner);
+impl Screenshots {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self(ScreenshotsInner::new(directory))
+    }
+
+    pub fn inner(&self) -> &ScreenshotsInner {
+        &self.0
+    }
+    pub fn inner_mut(&mut self) -> &mut ScreenshotsInner {
+        &mut self.0
+    }
+
+    /// Returns the path the screenshot will be saved to
+    #[inline]
+    pub fn request(&mut self) -> PathBuf {
+        self.0.request()
+    }
+    #[inline]
+    pub fn request_path(&mut self, path: impl Into<PathBuf>) {
+        self.0.request_path(path)
+    }
+
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        self.0.directory()
+    }
+    #[inline]
+    pub fn set_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.0.set_directory(directory)
+    }
+
+    #[inline]
+    pub fn in_progress(&self) -> usize {
+        self.0.in_progress()
+    }
+}
+
+/// Record a number of seconds of frames to a PNG sequence or Y4M file. While capturing,
+/// the upkeep tracker advances by exactly one captured frame every frame. Requires
+/// `ShipyardRunner::allow_capture`.
+#[derive(Unique, Default)]
+pub struct FrameCapture(Option<FrameCaptureInner>);
+impl FrameCapture {
+    /// Returns false without starting if a capture is already in progress
+    pub fn start(&mut self, settings: FrameCaptureSettings) -> bool {
+        if self.is_capturing() {
+            return false;
+        }
+        self.0 = Some(FrameCaptureInner::new(settings));
+        true
+    }
+
+    /// Stop capturing early. Frames captured so far are still written.
+    pub fn stop(&mut self) {
+        if let Some(capture) = &mut self.0 {
+            capture.stop();
+        }
+    }
+
+    #[inline]
+    pub fn is_capturing(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Captured and total number of frames
+    pub fn progress(&self) -> Option<(u32, u32)> {
+        self.0
+            .as_ref()
+            .map(|capture| (capture.frames_captured(), capture.frame_count()))
+    }
+
+    /// Time to advance the game by each frame while capturing
+    pub fn frame_delta(&self) -> Option<f32> {
+        self.0.as_ref().map(|capture| capture.frame_delta())
+    }
+
+    pub(crate) fn inner_mut(&mut self) -> Option<&mut FrameCaptureInner> {
+        self.0.as_mut()
+    }
+
+    /// Remove the capture once all of its frames have been captured
+    pub(crate) fn take_finished(&mut self) -> Option<FrameCaptureInner> {
+        match self.0.as_ref().is_some_and(|capture| capture.is_finished()) {
+            true => self.0.take(),
+            false => None,
+        }
+    }
+}
+
+//===============================================================
+
 #[derive(Unique)]
 pub struct ClearColor(pub [f64; 3]);
 impl ClearColor {
@@ -110,10 +225,33 @@ impl ClearColor {
 
 //===============================================================
 
+/// Lines and shapes queued by any system, drawn on top of the 2D renderer for the current
+/// frame only.
+#[derive(Unique, Default)]
+pub struct Gizmos(GizmoBuffer);
+impl Deref for Gizmos {
+    type Target = GizmoBuffer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for Gizmos {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+//===============================================================
+
 #[derive(Unique)]
 pub struct Renderer2D {
     renderer: TextureRenderer,
     processor: TextureProcessor<TextureID>,
+    text_renderer: TextRenderer,
+    gizmo_renderer: GizmoRenderer,
+    // Registered the first time images are checked for changes
+    image_events: Option<AssetEventReader<DynamicImage>>,
 }
 
 impl Renderer2D {
@@ -125,6 +263,9 @@ impl Renderer2D {
         Self {
             renderer: TextureRenderer::new(device, config.format, window_size),
             processor: TextureProcessor::default(),
+            text_renderer: TextRenderer::new(device, config.format, window_size),
+            gizmo_renderer: GizmoRenderer::new(device, config.format, window_size),
+            image_events: None,
         }
     }
 
@@ -133,6 +274,15 @@ impl Renderer2D {
         self.renderer.get_texture_layout()
     }
 
+    #[inline]
+    pub(crate) fn image_events_mut(
+        &mut self,
+        asset_storage: &mut AssetStorage,
+    ) -> &mut AssetEventReader<DynamicImage> {
+        self.image_events
+            .get_or_insert_with(|| asset_storage.event_reader())
+    }
+
     #[inline]
     pub(crate) fn resize_depth(&mut self, device: &wgpu::Device, new_size: Size<u32>) {
         self.renderer.resize_depth(device, new_size);
@@ -141,6 +291,8 @@ impl Renderer2D {
     #[inline]
     pub(crate) fn resize_projection(&mut self, queue: &wgpu::Queue, matrix: &Mat4) {
         self.renderer.set_projection(queue, matrix);
+        self.text_renderer.set_camera_projection(queue, matrix);
+        self.gizmo_renderer.set_projection(queue, matrix);
     }
 
     #[inline]
@@ -151,6 +303,21 @@ impl Renderer2D {
         size: Size<u32>,
     ) {
         self.renderer.resize_depth_projection(device, queue, size);
+
+        let projection =
+            Mat4::orthographic_lh(0., size.width as f32, 0., size.height as f32, 0., 100.);
+        self.text_renderer.set_camera_projection(queue, &projection);
+        self.gizmo_renderer.set_projection(queue, &projection);
+    }
+
+    #[inline]
+    pub(crate) fn resize_text(&mut self, queue: &wgpu::Queue, size: Size<u32>) {
+        self.text_renderer.resize(queue, size);
+    }
+
+    #[inline]
+    pub(crate) fn resize_gizmos(&mut self, queue: &wgpu::Queue, size: Size<u32>) {
+        self.gizmo_renderer.resize(queue, size);
     }
 
     #[inline]
@@ -160,11 +327,72 @@ impl Renderer2D {
         self.processor.get_unprocessed_mut()
     }
 
+    #[inline]
+    pub(crate) fn get_unprocessed_transparent_mut(
+        &mut self,
+    ) -> &mut Vec<TransparentInstance<TextureID>> {
+        self.processor.get_unprocessed_transparent_mut()
+    }
+
+    #[inline]
+    pub(crate) fn draw_texture_blended(
+        &mut self,
+        texture_id: TextureID,
+        instance: RawTextureInstance,
+        blend_mode: BlendMode,
+        order: u64,
+    ) {
+        self.processor
+            .draw_texture_blended(texture_id, instance, blend_mode, order);
+    }
+
     #[inline]
     pub(crate) fn process_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
         self.processor.process_texture(device, queue);
     }
 
+    #[inline]
+    pub(crate) fn font_id(&mut self, handle_id: HandleID<FontArc>, font: &FontArc) -> FontId {
+        self.text_renderer.font_id(handle_id, font)
+    }
+
+    #[inline]
+    pub(crate) fn reload_font(&mut self, handle_id: HandleID<FontArc>, font: &FontArc) {
+        self.text_renderer.reload_font(handle_id, font);
+    }
+
+    #[inline]
+    pub(crate) fn queue_text(&mut self, section: &TextSection) {
+        self.text_renderer.queue(section);
+    }
+
+    #[inline]
+    pub(crate) fn process_text(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.text_renderer.process(device, queue);
+    }
+
+    #[inline]
+    pub(crate) fn render_text(&self, render_tools: &mut render_tools::RenderPassTools) {
+        self.text_renderer.render(render_tools);
+    }
+
+    #[inline]
+    pub(crate) fn process_gizmos(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        gizmos: &Gizmos,
+    ) {
+        self.gizmo_renderer.process(device, queue, gizmos);
+    }
+
+    /// Gizmos are tested against the depth of the textures drawn this frame
+    #[inline]
+    pub(crate) fn render_gizmos(&self, render_tools: &mut render_tools::RenderPassTools) {
+        self.gizmo_renderer
+            .render(render_tools, self.renderer.depth_texture());
+    }
+
     pub(crate) fn render(
         &mut self,
         texture_storage: &AssetStorage,
@@ -174,13 +402,37 @@ impl Renderer2D {
             .processor
             .get_draw_data()
             .iter()
-            .map(|(id, buffer)| {
-                let bind_group = &texture_storage.get_data(*id).unwrap().bind_group;
-                (bind_group, buffer)
+            // Skip textures that are still loading
+            .filter_map(|(id, buffer)| {
+                let bind_group = &texture_storage.get_data(*id).ok()?.bind_group;
+                Some((bind_group, buffer))
             })
             .collect::<Vec<_>>();
 
         self.renderer.render(render_tools, &draw);
+
+        // Transparent textures are drawn back to front after all opaque ones
+        if let Some(instance_buffer) = self.processor.get_transparent_buffer() {
+            let transparent = self
+                .processor
+                .get_transparent_draw_calls()
+                .iter()
+                .filter_map(|draw_call| {
+                    let bind_group = &texture_storage
+                        .get_data(draw_call.texture_id)
+                        .ok()?
+                        .bind_group;
+                    Some((
+                        bind_group,
+                        draw_call.blend_mode,
+                        draw_call.instances.clone(),
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            self.renderer
+                .render_transparent(render_tools, instance_buffer, &transparent);
+        }
     }
 }
 
@@ -12,7 +12,7 @@ use shipyard::{
 use crate::{
     assets::AssetsWorkload,
     hierarchies::HierarchyWorkload,
-    renderer::{Renderer2dWorkload, RendererWorkload},
+    renderer::{Renderer2dWorkload, RendererSetup, RendererWorkload},
     tools::{ToolsWorkload, Window},
 };
 
@@ -139,21 +139,35 @@ pub trait RunnerWorkloads {
 #[derive(Default)]
 pub struct ShipyardRunner {
     pub window_builder: WindowBuilder,
+    /// Allow taking screenshots and capturing frames, if the surface supports it
+    pub allow_capture: bool,
 }
 
 impl ShipyardRunner {
     pub fn run(self, core: WorkloadGroup) {
-        Runner::run_with_data::<WorkloadGroup, ShipyardRunnerInner>(self.window_builder, core);
+        let data = RunnerData {
+            workloads: core,
+            renderer: RendererSetup {
+                allow_capture: self.allow_capture,
+            },
+        };
+
+        Runner::run_with_data::<RunnerData, ShipyardRunnerInner>(self.window_builder, data);
     }
 
     pub fn run_all_plugins(self, plugins: WorkloadGroup) {
         let mut core = WorkloadGroup::default_workloads();
         core.add_workload_group(plugins);
 
-        Runner::run_with_data::<WorkloadGroup, ShipyardRunnerInner>(self.window_builder, core);
+        self.run(core);
     }
 }
 
+struct RunnerData {
+    workloads: WorkloadGroup,
+    renderer: RendererSetup,
+}
+
 //===============================================================
 
 // shipyard core
@@ -162,17 +176,23 @@ struct ShipyardRunnerInner {
     proxy: EventLoopProxy<RunnerLoopEvent>,
 }
 
-impl RunnerDataCore<WorkloadGroup> for ShipyardRunnerInner {
+impl RunnerDataCore<RunnerData> for ShipyardRunnerInner {
     fn new_data(
         window: brackens_tools::Window,
         event_loop: &brackens_tools::EventLoop<RunnerLoopEvent>,
-        mut workloads: WorkloadGroup,
+        data: RunnerData,
     ) -> Self {
         //--------------------------------------------------
 
+        let RunnerData {
+            mut workloads,
+            renderer,
+        } = data;
+
         let world = World::new();
 
         world.add_unique(Window::new(window));
+        world.add_unique(renderer);
 
         let proxy = event_loop.create_proxy();
 
@@ -213,8 +233,11 @@ impl RunnerCore for ShipyardRunnerInner {
         window: brackens_tools::Window,
         event_loop: &brackens_tools::EventLoop<brackens_tools::RunnerLoopEvent>,
     ) -> Self {
-        let workloads = WorkloadGroup::default_workloads();
-        Self::new_data(window, event_loop, workloads)
+        let data = RunnerData {
+            workloads: WorkloadGroup::default_workloads(),
+            renderer: RendererSetup::default(),
+        };
+        Self::new_data(window, event_loop, data)
     }
 
     fn input(&mut self, event: brackens_tools::WindowEvent) {
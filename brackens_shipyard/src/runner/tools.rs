@@ -1,7 +1,14 @@
 //===============================================================
 
-use brackens_assets::Handle;
-use brackens_renderer::{renderer_2d::RendererTexture, wgpu};
+use brackens_assets::{BitmapFont, Handle};
+use brackens_renderer::{
+    image::DynamicImage,
+    renderer_2d::{
+        PackedAtlas, RendererBitmapFont, RendererTexture, SpriteSheet, SpriteSheetLayout,
+        TextureAtlas, TextureSettings,
+    },
+    wgpu, Size,
+};
 use shipyard::{Borrow, BorrowInfo, IntoBorrow, UniqueView, UniqueViewMut};
 
 use crate::{
@@ -17,31 +24,221 @@ pub struct TextureLoader<'v> {
     queue: UniqueView<'v, Queue>,
 }
 impl<'v> TextureLoader<'v> {
+    /// Load a texture using the import settings from its `.meta` file. The given sampler
+    /// replaces the sampler described by the settings. Reloads are decoded like
+    /// `load_texture_async` and uploaded using the 2D renderer layout.
     pub fn load_texture(
         &mut self,
         layout: &wgpu::BindGroupLayout,
         label: &str,
         path: &str,
         sampler: Option<wgpu::SamplerDescriptor>,
-    ) -> Handle<RendererTexture> {
+    ) -> anyhow::Result<Handle<RendererTexture>> {
+        let handle = self.assets.load_from_data_with_settings(
+            path,
+            None,
+            |bytes, settings: &TextureSettings| {
+                RendererTexture::from_bytes_with_settings(
+                    self.device.inner(),
+                    self.queue.inner(),
+                    bytes,
+                    label,
+                    settings,
+                    sampler.as_ref(),
+                    layout,
+                )
+            },
+        )?;
+
+        let settings = self.assets.load_settings::<TextureSettings>(path)?;
         let sampler = match sampler {
-            Some(sampler) => sampler,
-            None => wgpu::SamplerDescriptor::default(),
+            Some(sampler) => owned_sampler(&sampler),
+            None => settings.sampler_descriptor(),
         };
+        self.assets.set_reload_staged(
+            handle.id(),
+            path,
+            stage_texture(label.to_string(), sampler, settings),
+        )?;
 
-        self.assets
-            .load_from_data(path, |bytes| {
-                RendererTexture::from_bytes(
+        Ok(handle)
+    }
+
+    /// Load a texture using the given import settings instead of its `.meta` file. The
+    /// same texture loaded with different settings produces separate assets.
+    pub fn load_texture_with_settings(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        path: &str,
+        settings: TextureSettings,
+    ) -> anyhow::Result<Handle<RendererTexture>> {
+        let handle = self.assets.load_from_data_with_settings(
+            path,
+            Some(settings.clone()),
+            |bytes, settings| {
+                RendererTexture::from_bytes_with_settings(
                     self.device.inner(),
                     self.queue.inner(),
                     bytes,
                     label,
-                    &sampler,
+                    settings,
+                    None,
                     layout,
                 )
-                .unwrap()
-            })
-            .unwrap()
+            },
+        )?;
+
+        self.assets.set_reload_staged(
+            handle.id(),
+            path,
+            stage_texture(label.to_string(), settings.sampler_descriptor(), settings),
+        )?;
+
+        Ok(handle)
+    }
+
+    /// Decode the texture on a background worker. The texture is uploaded to the gpu
+    /// using the 2D renderer layout by `sys_renderer2d_finalize_textures` once decoded.
+    /// The handle stays `Loading` until that system has run.
+    pub fn load_texture_async(
+        &mut self,
+        label: &str,
+        path: &str,
+        sampler: Option<wgpu::SamplerDescriptor>,
+    ) -> anyhow::Result<Handle<RendererTexture>> {
+        let label = label.to_string();
+        let settings = self.assets.load_settings::<TextureSettings>(path)?;
+        let sampler = match sampler {
+            Some(sampler) => owned_sampler(&sampler),
+            None => settings.sampler_descriptor(),
+        };
+
+        self.assets
+            .load_from_data_staged(path, stage_texture(label, sampler, settings))
+    }
+
+    /// Load a texture and split it into sprites. Uses the slicing from the `.meta` file of
+    /// the texture if no layout is given.
+    pub fn load_sprite_sheet(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        path: &str,
+        sprite_layout: Option<SpriteSheetLayout>,
+    ) -> anyhow::Result<Handle<SpriteSheet>> {
+        let settings = self.assets.load_settings::<TextureSettings>(path)?;
+
+        let sprite_layout = match sprite_layout.or(settings.slicing.map(SpriteSheetLayout::Grid)) {
+            Some(sprite_layout) => sprite_layout,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "no sprite sheet layout given or set in the .meta file of '{}'",
+                    path
+                ))
+            }
+        };
+
+        let texture = self.load_texture(layout, label, path, None)?;
+        let size = self.assets.get_data(texture.id())?.texture.texture.size();
+
+        Ok(self.assets.insert_data(SpriteSheet::new(
+            texture,
+            Size::new(size.width, size.height),
+            &sprite_layout,
+        )))
+    }
+
+    /// Upload the pages of a packed atlas as textures. Sub textures on the same page are
+    /// drawn in the same batch.
+    pub fn create_atlas(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        atlas: &PackedAtlas,
+        settings: &TextureSettings,
+    ) -> anyhow::Result<Handle<TextureAtlas>> {
+        let pages = atlas
+            .create_textures(
+                self.device.inner(),
+                self.queue.inner(),
+                label,
+                settings,
+                layout,
+            )?
+            .into_iter()
+            .map(|page| self.assets.insert_data(page))
+            .collect::<Vec<_>>();
+
+        Ok(self.assets.insert_data(TextureAtlas::new(pages, atlas)))
+    }
+
+    /// Load an atlas saved with `PackedAtlas::save` from the path of its index, read through
+    /// the asset storage's virtual filesystem
+    pub fn load_atlas(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        index_path: &str,
+        settings: &TextureSettings,
+    ) -> anyhow::Result<Handle<TextureAtlas>> {
+        let atlas = PackedAtlas::load_from_vfs(self.assets.vfs(), index_path)?;
+        self.create_atlas(layout, label, &atlas, settings)
+    }
+
+    /// Load a BMFont `.fnt` file along with the textures of its pages. Pages use the
+    /// import settings from their `.meta` files, so pixel fonts usually want a nearest sampler.
+    pub fn load_bitmap_font(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        path: &str,
+        sampler: Option<wgpu::SamplerDescriptor>,
+    ) -> anyhow::Result<Handle<RendererBitmapFont>> {
+        let font_handle = self.assets.load_from_file::<BitmapFont>(path)?;
+        let font = self.assets.get_data(font_handle.id())?.clone();
+
+        let pages = font
+            .pages
+            .iter()
+            .map(|page| self.load_texture(layout, label, page, sampler.clone()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let handle = self
+            .assets
+            .insert_data(RendererBitmapFont::new(font, pages));
+        self.assets.add_dependency(handle.id(), font_handle.id())?;
+
+        Ok(handle)
+    }
+
+    /// Upload an image asset as a texture. Modifying or reloading the image writes the new
+    /// pixels to the texture, see `sys_renderer2d_update_image_textures`.
+    pub fn load_image_texture(
+        &mut self,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+        image: &Handle<DynamicImage>,
+        sampler: Option<wgpu::SamplerDescriptor>,
+    ) -> anyhow::Result<Handle<RendererTexture>> {
+        let sampler = match sampler {
+            Some(sampler) => sampler,
+            None => wgpu::SamplerDescriptor::default(),
+        };
+
+        let texture = RendererTexture::from_image(
+            self.device.inner(),
+            self.queue.inner(),
+            self.assets.get_data(image.id())?,
+            Some(label),
+            &sampler,
+            layout,
+        )?;
+
+        let handle = self.assets.insert_data(texture);
+        self.assets.add_dependency(handle.id(), image.id())?;
+
+        Ok(handle)
     }
 
     pub fn load_color(
@@ -50,7 +247,7 @@ impl<'v> TextureLoader<'v> {
         label: &str,
         color: [u8; 3],
         sampler: Option<wgpu::SamplerDescriptor>,
-    ) -> Handle<RendererTexture> {
+    ) -> anyhow::Result<Handle<RendererTexture>> {
         let sampler = match sampler {
             Some(sampler) => sampler,
             None => wgpu::SamplerDescriptor::default(),
@@ -63,10 +260,53 @@ impl<'v> TextureLoader<'v> {
             label,
             &sampler,
             layout,
-        )
-        .unwrap();
+        )?;
+
+        Ok(self.assets.insert_data(texture))
+    }
+}
+
+//--------------------------------------------------
+
+/// Texture data decoded on a worker thread waiting to be uploaded to the gpu.
+pub struct StagedTexture {
+    pub image: DynamicImage,
+    pub label: String,
+    pub sampler: wgpu::SamplerDescriptor<'static>,
+    pub settings: TextureSettings,
+}
+
+/// Decodes the bytes of a texture file for `finalize_staged`
+fn stage_texture(
+    label: String,
+    sampler: wgpu::SamplerDescriptor<'static>,
+    settings: TextureSettings,
+) -> impl Fn(&[u8]) -> anyhow::Result<StagedTexture> + Send + Sync + 'static {
+    move |bytes| {
+        Ok(StagedTexture {
+            image: brackens_renderer::image::load_from_memory(bytes)?,
+            label: label.clone(),
+            sampler: sampler.clone(),
+            settings: settings.clone(),
+        })
+    }
+}
 
-        self.assets.insert_data(texture)
+/// Copy a sampler descriptor without its label so it can be sent to other threads.
+fn owned_sampler(sampler: &wgpu::SamplerDescriptor) -> wgpu::SamplerDescriptor<'static> {
+    wgpu::SamplerDescriptor {
+        label: None,
+        address_mode_u: sampler.address_mode_u,
+        address_mode_v: sampler.address_mode_v,
+        address_mode_w: sampler.address_mode_w,
+        mag_filter: sampler.mag_filter,
+        min_filter: sampler.min_filter,
+        mipmap_filter: sampler.mipmap_filter,
+        lod_min_clamp: sampler.lod_min_clamp,
+        lod_max_clamp: sampler.lod_max_clamp,
+        compare: sampler.compare,
+        anisotropy_clamp: sampler.anisotropy_clamp,
+        border_color: sampler.border_color,
     }
 }
 
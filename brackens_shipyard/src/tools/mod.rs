@@ -13,7 +13,10 @@ pub use uniques::*;
 //===============================================================
 
 #[cfg(feature = "runner")]
-use shipyard::Workload;
+use {
+    crate::renderer::FrameCapture,
+    shipyard::{SystemModificator, Workload},
+};
 
 #[cfg(feature = "runner")]
 pub struct ToolsWorkload;
@@ -24,7 +27,9 @@ impl crate::runner::RunnerWorkloads for ToolsWorkload {
     }
 
     fn start(&self) -> Workload {
-        Workload::new("").with_system(sys_update_upkeep)
+        Workload::new("")
+            .with_system(sys_update_upkeep.run_if_missing_unique::<FrameCapture>())
+            .with_system(sys_update_upkeep_capture.skip_if_missing_unique::<FrameCapture>())
     }
 
     fn pre_update(&self) -> Workload {
@@ -2,6 +2,8 @@
 
 use shipyard::{AllStoragesView, IntoIter, UniqueView, UniqueViewMut, ViewMut};
 
+#[cfg(feature = "runner")]
+use crate::renderer::FrameCapture;
 use crate::runner::uniques::InputEventManager;
 
 use super::{KeyManager, MouseKeyManager, MousePositionManager, Timer, UpkeepTracker};
@@ -22,6 +24,18 @@ pub fn sys_update_upkeep(mut upkeep: UniqueViewMut<UpkeepTracker>) {
     upkeep.tick();
 }
 
+/// Advance by exactly one captured frame while capturing frames
+#[cfg(feature = "runner")]
+pub fn sys_update_upkeep_capture(
+    mut upkeep: UniqueViewMut<UpkeepTracker>,
+    frame_capture: UniqueView<FrameCapture>,
+) {
+    match frame_capture.frame_delta() {
+        Some(delta) => upkeep.tick_fixed(delta),
+        None => upkeep.tick(),
+    }
+}
+
 pub fn sys_tick_timers(upkeep: UniqueView<UpkeepTracker>, mut vm_timer: ViewMut<Timer>) {
     let delta = upkeep.delta();
     for timer in (&mut vm_timer).iter() {
@@ -139,6 +139,11 @@ impl UpkeepTracker {
     pub(crate) fn tick(&mut self) {
         self.0.tick()
     }
+
+    #[inline]
+    pub(crate) fn tick_fixed(&mut self, delta: f32) {
+        self.0.tick_fixed(delta)
+    }
 }
 
 //===============================================================
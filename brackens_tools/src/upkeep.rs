@@ -44,7 +44,18 @@ impl UpkeepTracker {
         Self::default()
     }
     pub fn tick(&mut self) {
-        self.delta = self.last_frame_instant.elapsed().as_secs_f32();
+        let delta = self.last_frame_instant.elapsed().as_secs_f32();
+        self.advance(delta);
+    }
+
+    /// Advance by exactly `delta` seconds regardless of how much time has actually passed.
+    /// Used to step the game at a fixed rate, such as while capturing frames.
+    pub fn tick_fixed(&mut self, delta: f32) {
+        self.advance(delta);
+    }
+
+    fn advance(&mut self, delta: f32) {
+        self.delta = delta;
 
         self.last_frame_instant = Instant::now();
 